@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_2048::bench::{Direction, GameState, SpawnPolicy, Variant};
+
+// A game some way in, so copies of it carry a long history.
+fn played(variant: Variant, moves: usize) -> GameState {
+    let mut game_state = GameState::with_seed(variant, SpawnPolicy::Classic, 7);
+    for i in 0..moves {
+        let direction = Direction::ALL[i % Direction::ALL.len()];
+        if !game_state.move_tiles(direction).moved && game_state.legal_moves().next().is_none() {
+            game_state = GameState::with_seed(variant, SpawnPolicy::Classic, i as u64);
+        }
+    }
+    game_state
+}
+
+// Legal moves read off the board against the baseline of trying every move
+// on a copy of the game, which is what finding them used to take.
+fn legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+    for variant in [Variant::Classic, Variant::Obstacles(2), Variant::Triples] {
+        let game_state = played(variant, 500);
+        group.bench_function(format!("{:?}/read", variant), |b| {
+            b.iter(|| black_box(&game_state).legal_moves().count())
+        });
+        group.bench_function(format!("{:?}/copied (baseline)", variant), |b| {
+            b.iter(|| {
+                Direction::ALL
+                    .iter()
+                    .filter(|&&direction| black_box(&game_state).preview_move(direction).is_some())
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+// The loop of a game: a move, and a new game once nothing moves any more.
+fn move_tiles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move_tiles");
+    for variant in [Variant::Classic, Variant::Obstacles(2), Variant::Triples] {
+        group.bench_function(format!("{:?}", variant), |b| {
+            let mut game_state = GameState::with_seed(variant, SpawnPolicy::Classic, 7);
+            let mut i = 0;
+            b.iter(|| {
+                let direction = Direction::ALL[i % Direction::ALL.len()];
+                i += 1;
+                if !game_state.move_tiles(direction).moved
+                    && game_state.legal_moves().next().is_none()
+                {
+                    game_state = GameState::with_seed(variant, SpawnPolicy::Classic, i as u64);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, legal_moves, move_tiles);
+criterion_main!(benches);
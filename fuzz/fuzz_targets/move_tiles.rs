@@ -0,0 +1,7 @@
+#![no_main]
+
+// Arbitrary boards and moves thrown at the move engine, see `fuzz::check`.
+// Run with `cargo +nightly fuzz run move_tiles`.
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    rust_2048::fuzz::run(data);
+});
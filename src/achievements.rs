@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use crate::stats::Stats;
+
+// The counter of `Stats` an achievement is reached on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Counter {
+    GamesPlayed,
+    GamesWon,
+    BestScore,
+    BestTile,
+    TilesMerged,
+}
+
+pub struct Achievement {
+    pub name: &'static str,
+    pub description: &'static str,
+    counter: Counter,
+    pub goal: usize,
+}
+
+pub const ACHIEVEMENTS: [Achievement; 12] = [
+    Achievement {
+        name: "First Steps",
+        description: "Play your first game",
+        counter: Counter::GamesPlayed,
+        goal: 1,
+    },
+    Achievement {
+        name: "Regular",
+        description: "Play 100 games",
+        counter: Counter::GamesPlayed,
+        goal: 100,
+    },
+    Achievement {
+        name: "Winner",
+        description: "Win a game",
+        counter: Counter::GamesWon,
+        goal: 1,
+    },
+    Achievement {
+        name: "Champion",
+        description: "Win 10 games",
+        counter: Counter::GamesWon,
+        goal: 10,
+    },
+    Achievement {
+        name: "Halfway There",
+        description: "Reach the 512 tile",
+        counter: Counter::BestTile,
+        goal: 512,
+    },
+    Achievement {
+        name: "So Close",
+        description: "Reach the 1024 tile",
+        counter: Counter::BestTile,
+        goal: 1024,
+    },
+    Achievement {
+        name: "Beyond 2048",
+        description: "Reach the 4096 tile",
+        counter: Counter::BestTile,
+        goal: 4096,
+    },
+    Achievement {
+        name: "High Scorer",
+        description: "Score 10000 points in a game",
+        counter: Counter::BestScore,
+        goal: 10000,
+    },
+    Achievement {
+        name: "Record Breaker",
+        description: "Score 50000 points in a game",
+        counter: Counter::BestScore,
+        goal: 50000,
+    },
+    Achievement {
+        name: "Merger",
+        description: "Merge 1000 tiles",
+        counter: Counter::TilesMerged,
+        goal: 1000,
+    },
+    Achievement {
+        name: "Tycoon",
+        description: "Merge 10000 tiles",
+        counter: Counter::TilesMerged,
+        goal: 10000,
+    },
+    Achievement {
+        name: "Monopoly",
+        description: "Merge 100000 tiles",
+        counter: Counter::TilesMerged,
+        goal: 100000,
+    },
+];
+
+impl Achievement {
+    // How far the player got, never past the goal.
+    pub fn progress(&self, stats: &Stats) -> usize {
+        let count = match self.counter {
+            Counter::GamesPlayed => stats.games_played(),
+            Counter::GamesWon => stats.games_won(),
+            Counter::BestScore => stats.best_score(),
+            Counter::BestTile => stats.best_tile(),
+            Counter::TilesMerged => stats.tiles_merged(),
+        };
+        count.min(self.goal)
+    }
+
+    // Whether it is worked towards bit by bit, and so worth a progress bar.
+    // A tile is either reached or not.
+    pub fn is_incremental(&self) -> bool {
+        self.goal > 1 && self.counter != Counter::BestTile
+    }
+}
+
+// Notes when the achievements reached since last time were unlocked, by
+// name, and returns them.
+pub fn unlock(
+    unlocked: &mut BTreeMap<String, f64>,
+    stats: &Stats,
+    now: f64,
+) -> Vec<&'static Achievement> {
+    let reached: Vec<&'static Achievement> = ACHIEVEMENTS
+        .iter()
+        .filter(|achievement| {
+            achievement.progress(stats) == achievement.goal
+                && !unlocked.contains_key(achievement.name)
+        })
+        .collect();
+    for achievement in &reached {
+        unlocked.insert(achievement.name.to_string(), now);
+    }
+    reached
+}
+
+// Keeps the earliest unlock of every achievement unlocked on either side.
+pub fn merge(unlocked: &mut BTreeMap<String, f64>, other: &BTreeMap<String, f64>) {
+    for (name, &at) in other {
+        let mine = unlocked.entry(name.clone()).or_insert(at);
+        *mine = mine.min(at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::achievements::{merge, unlock, ACHIEVEMENTS};
+    use crate::game_state::GameEvent;
+    use crate::stats::Stats;
+
+    #[test]
+    fn test_unlock() {
+        let mut stats = Stats::default();
+        let mut unlocked = BTreeMap::new();
+        assert!(unlock(&mut unlocked, &stats, 1.0).is_empty(), "Nothing Yet");
+
+        stats.record_new_game();
+        for _ in 0..742 {
+            stats.record_event(GameEvent::TilesMerged {
+                index: 0,
+                value: 512,
+            });
+        }
+        let names: Vec<&str> = unlock(&mut unlocked, &stats, 2.0)
+            .iter()
+            .map(|achievement| achievement.name)
+            .collect();
+        assert_eq!(vec!["First Steps", "Halfway There"], names, "Unlocked");
+        assert!(unlock(&mut unlocked, &stats, 3.0).is_empty(), "Only Once");
+        assert_eq!(Some(&2.0), unlocked.get("First Steps"), "Unlocked At");
+
+        let merger = ACHIEVEMENTS.iter().find(|a| a.name == "Merger").unwrap();
+        assert_eq!(742, merger.progress(&stats), "Progress");
+        assert!(merger.is_incremental(), "Incremental");
+
+        let mut other = BTreeMap::new();
+        other.insert("First Steps".to_string(), 1.5);
+        other.insert("Winner".to_string(), 4.0);
+        merge(&mut unlocked, &other);
+        assert_eq!(Some(&1.5), unlocked.get("First Steps"), "Merged: Earliest");
+        assert_eq!(3, unlocked.len(), "Merged: Both Sides");
+    }
+}
@@ -0,0 +1,766 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::evaluate::{evaluate, Weights};
+use crate::game_state::{Direction, GameState, SpawnPolicy};
+use crate::ntuple::NTupleEvaluator;
+use crate::rng::GameRng;
+use crate::transposition::{Bound, TranspositionTable};
+
+// Games played after each move by the Monte Carlo computer of races, and
+// how far. Enough to beat the greedy computer, little enough for a move
+// within the fastest pace.
+const RACE_ROLLOUTS: usize = 20;
+const RACE_DEPTH: usize = 40;
+
+// Moves the computer looks ahead on evil boards. Every spawn is a branch
+// there, so a third move takes too long between two of the fastest pace.
+const RACE_PLIES: usize = 2;
+
+// The move whose board, before the next tile lands, evaluates best. Ties go
+// to the higher score. None when the board is stuck.
+pub fn best_move(game_state: &GameState, weights: &Weights) -> Option<Direction> {
+    Direction::ALL
+        .iter()
+        .filter_map(|&direction| Some((direction, game_state.preview_move(direction)?)))
+        .map(|(direction, next)| (direction, evaluate(&next, weights), next.score()))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)))
+        .map(|(direction, _, _)| direction)
+}
+
+// The move with the most points and the best board after it by the
+// network, before the next tile lands. Boards the network can't judge go by
+// `best_move`.
+pub fn ntuple_move(game_state: &GameState, network: &NTupleEvaluator) -> Option<Direction> {
+    let moves: Option<Vec<(Direction, f64)>> = Direction::ALL
+        .iter()
+        .filter_map(|&direction| Some((direction, game_state.preview_move(direction)?)))
+        .map(|(direction, next)| {
+            let points = (next.score() - game_state.score()) as f64;
+            Some((direction, points + network.evaluate(&next)?))
+        })
+        .collect();
+    match moves {
+        Some(moves) => moves
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(direction, _)| direction),
+        None => best_move(game_state, &Weights::default()),
+    }
+}
+
+// Plays `rollouts` games of random moves, at most `depth` of them, after
+// every legal move and picks the move whose games scored most on average.
+// The games draw their spawns from `rng`, not knowing those of the game.
+pub fn rollout_move(
+    game_state: &GameState,
+    rollouts: usize,
+    depth: usize,
+    rng: &mut impl Rng,
+) -> Option<Direction> {
+    // Without the history every copy is as cheap as the board.
+    let mut base = game_state.clone();
+    base.forget_history();
+
+    let mut best = None;
+    let mut best_score = f64::NEG_INFINITY;
+    for direction in game_state.legal_moves() {
+        let mut total = 0;
+        for _ in 0..rollouts {
+            let mut rollout = base.clone();
+            rollout.reseed(rng.gen());
+            rollout.apply_moves(&[direction]);
+            for _ in 0..depth {
+                let mut directions = Direction::ALL;
+                directions.shuffle(rng);
+                if !directions
+                    .iter()
+                    .any(|&random| rollout.apply_moves(&[random]).applied == 1)
+                {
+                    break;
+                }
+            }
+            total += rollout.score();
+        }
+        let score = total as f64 / rollouts.max(1) as f64;
+        if score > best_score {
+            best = Some(direction);
+            best_score = score;
+        }
+    }
+    best
+}
+
+// Looks `plies` moves ahead against a spawner that puts every tile where it
+// hurts most, as on evil boards, where weighing the spawns by their odds
+// would count on tiles that never come. Positions already searched come out
+// of `table`. None when the board is stuck.
+pub fn minimax_move(
+    game_state: &GameState,
+    plies: usize,
+    weights: &Weights,
+    table: &mut TranspositionTable,
+) -> Option<Direction> {
+    let mut base = game_state.clone();
+    base.forget_history();
+
+    let mut best = None;
+    let mut alpha = f64::NEG_INFINITY;
+    let moves = Direction::ALL
+        .iter()
+        .filter_map(|&direction| Some((direction, base.preview_move(direction)?)));
+    for (direction, next) in moves {
+        let value = spawner_ply(
+            &next,
+            plies.saturating_sub(1),
+            alpha,
+            f64::INFINITY,
+            weights,
+            table,
+        );
+        // Even a lost board is played on, with the first move that loses.
+        if best.is_none() || value > alpha {
+            best = Some(direction);
+            alpha = value;
+        }
+    }
+    best
+}
+
+// Moves hints look ahead on evil boards, as far as the computer of races.
+pub const HINT_PLIES: usize = RACE_PLIES;
+
+// The move hints point to: on evil boards the minimax move, elsewhere
+// `best_move`.
+pub fn hint_move(game_state: &GameState, weights: &Weights) -> Option<Direction> {
+    if game_state.spawn_policy() != SpawnPolicy::Evil {
+        return best_move(game_state, weights);
+    }
+    let mut table = TranspositionTable::new(1 << 12);
+    minimax_move(game_state, HINT_PLIES, weights, &mut table)
+}
+
+// What the board after a move is worth against the worst tiles, the move
+// counting as the first of `plies`, as `minimax_move` weighs it.
+pub fn minimax_value(
+    next: &GameState,
+    plies: usize,
+    weights: &Weights,
+    table: &mut TranspositionTable,
+) -> f64 {
+    spawner_ply(
+        next,
+        plies.saturating_sub(1),
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        weights,
+        table,
+    )
+}
+
+// The best the player can make of the board, `plies` moves on. Stuck boards
+// are worth nothing to the player.
+fn player_ply(
+    game_state: &GameState,
+    plies: usize,
+    alpha: f64,
+    beta: f64,
+    weights: &Weights,
+    table: &mut TranspositionTable,
+) -> f64 {
+    if game_state.legal_moves().next().is_none() {
+        return f64::NEG_INFINITY;
+    }
+    if plies == 0 {
+        return evaluate(game_state, weights);
+    }
+    let hash = game_state.board_hash();
+    if let Some(value) = table.probe(hash, plies, alpha, beta) {
+        return value;
+    }
+
+    let mut value = alpha;
+    for direction in game_state.legal_moves() {
+        if let Some(next) = game_state.preview_move(direction) {
+            value = value.max(spawner_ply(&next, plies - 1, value, beta, weights, table));
+            if value >= beta {
+                break;
+            }
+        }
+    }
+    let bound = if value >= beta {
+        Bound::Lower
+    } else if value <= alpha {
+        Bound::Upper
+    } else {
+        Bound::Exact
+    };
+    table.store(hash, plies, value, bound);
+    value
+}
+
+// The worst tile the spawner can place after the player's move, every value
+// the spawn policy has in every empty cell.
+fn spawner_ply(
+    game_state: &GameState,
+    plies: usize,
+    alpha: f64,
+    mut beta: f64,
+    weights: &Weights,
+    table: &mut TranspositionTable,
+) -> f64 {
+    let spawns = spawns(game_state);
+    if spawns.is_empty() {
+        return player_ply(game_state, plies, alpha, beta, weights, table);
+    }
+    for (index, value) in spawns {
+        let mut spawned = game_state.clone();
+        spawned.set_value(index, value);
+        beta = beta.min(player_ply(&spawned, plies, alpha, beta, weights, table));
+        if alpha >= beta {
+            return beta;
+        }
+    }
+    beta
+}
+
+// Every tile the spawner can place, as (cell, value).
+fn spawns(game_state: &GameState) -> Vec<(usize, usize)> {
+    let values = game_state.spawn_policy().odds(game_state.score());
+    game_state
+        .cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.is_none())
+        .flat_map(|(index, _)| values.iter().map(move |&(value, _)| (index, value)))
+        .collect()
+}
+
+// Moves the spawner of evil boards looks ahead after its tile, with the
+// tile after each. Outlooks play hundreds of spawns a move, a second would
+// take them seconds.
+pub const EVIL_PLIES: usize = 1;
+
+// The tile, as (cell, value), after which the player makes least of the
+// board `plies` moves on, against a spawner as evil. Tiles that leave no
+// move at all win. None on a full board.
+pub fn minimax_spawn(
+    game_state: &GameState,
+    plies: usize,
+    weights: &Weights,
+    table: &mut TranspositionTable,
+) -> Option<(usize, usize)> {
+    let mut base = game_state.clone();
+    base.forget_history();
+
+    let mut worst = None;
+    let mut beta = f64::INFINITY;
+    for (index, value) in spawns(&base) {
+        let mut spawned = base.clone();
+        spawned.set_value(index, value);
+        let value_after = player_ply(&spawned, plies, f64::NEG_INFINITY, beta, weights, table);
+        if worst.is_none() || value_after < beta {
+            worst = Some((index, value));
+            beta = value_after;
+        }
+    }
+    worst
+}
+
+// Moves ahead the survival chance of an outlook looks.
+pub const OUTLOOK_MOVES: usize = 10;
+
+// How precarious a board is, from greedy games played on from it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Outlook {
+    // The share of the games still going after `OUTLOOK_MOVES` moves.
+    pub survival: f64,
+    // The chance of reaching the target, if every stretch of moves until the
+    // tiles add up to it is as risky as the next. None without a target.
+    pub win: Option<f64>,
+}
+
+// Plays `playouts` games of `OUTLOOK_MOVES` greedy moves on from the board,
+// with spawns drawn from `rng`.
+pub fn outlook(
+    game_state: &GameState,
+    playouts: usize,
+    weights: &Weights,
+    rng: &mut impl Rng,
+) -> Outlook {
+    let mut base = game_state.clone();
+    base.forget_history();
+
+    let mut survived = 0;
+    for _ in 0..playouts {
+        let mut playout = base.clone();
+        playout.reseed(rng.gen());
+        for _ in 0..OUTLOOK_MOVES {
+            match best_move(&playout, weights) {
+                Some(direction) => playout.apply_moves(&[direction]),
+                None => break,
+            };
+        }
+        if playout.is_won() || playout.legal_moves().next().is_some() {
+            survived += 1;
+        }
+    }
+
+    let win = game_state.target().map(|target| {
+        if game_state.is_won() {
+            return 1.0;
+        }
+        if game_state.legal_moves().next().is_none() {
+            return 0.0;
+        }
+        // Every move adds a tile, so at least this many moves are to come.
+        let sum: usize = game_state
+            .cells()
+            .iter()
+            .flatten()
+            .map(|tile| tile.get_value())
+            .sum();
+        let spawned: f64 = game_state
+            .spawn_policy()
+            .odds(game_state.score())
+            .iter()
+            .map(|&(value, chance)| value as f64 * chance)
+            .sum();
+        let moves = target.saturating_sub(sum) as f64 / spawned;
+        // Never quite sure of surviving, however the games went.
+        let stretch = (survived + 1) as f64 / (playouts + 2) as f64;
+        stretch.powf(moves / OUTLOOK_MOVES as f64)
+    });
+
+    Outlook {
+        survival: survived as f64 / playouts.max(1) as f64,
+        win,
+    }
+}
+
+// Moves a projection plays on at most, long enough for all but the best of
+// the greedy computer's games.
+const PROJECTION_MOVES: usize = 3000;
+
+// The final scores the game is headed for, from greedy games played on from
+// the board to their end.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Projection {
+    pub low: usize,
+    pub mean: f64,
+    pub high: usize,
+}
+
+// Greedy games played on from a board until they're stuck, past any target,
+// a slice of moves at a time so the page stays responsive in between.
+#[derive(Debug, Clone)]
+pub struct Projector {
+    weights: Weights,
+    // The games still going, each with the moves it has left.
+    playing: Vec<(GameState, usize)>,
+    scores: Vec<usize>,
+}
+
+impl Projector {
+    // `playouts` games, with spawns drawn from `rng`.
+    pub fn new(
+        game_state: &GameState,
+        playouts: usize,
+        weights: &Weights,
+        rng: &mut impl Rng,
+    ) -> Projector {
+        let mut base = game_state.clone();
+        base.forget_history();
+        base.set_target(None);
+
+        let playing = (0..playouts.max(1))
+            .map(|_| {
+                let mut playout = base.clone();
+                playout.reseed(rng.gen());
+                (playout, PROJECTION_MOVES)
+            })
+            .collect();
+        Projector {
+            weights: *weights,
+            playing,
+            scores: Vec::new(),
+        }
+    }
+
+    // Plays at most `moves` moves, across the games in turn. Returns the
+    // projection once every game is over.
+    pub fn play(&mut self, mut moves: usize) -> Option<Projection> {
+        while moves > 0 {
+            let (playout, left) = match self.playing.last_mut() {
+                Some(playing) => playing,
+                None => break,
+            };
+            let direction = match best_move(playout, &self.weights) {
+                Some(direction) if *left > 0 => direction,
+                _ => {
+                    let (playout, _) = self.playing.pop()?;
+                    self.scores.push(playout.score());
+                    continue;
+                }
+            };
+            playout.apply_moves(&[direction]);
+            // Every preview would copy the growing history.
+            playout.forget_history();
+            *left -= 1;
+            moves -= 1;
+        }
+        self.projection()
+    }
+
+    fn projection(&self) -> Option<Projection> {
+        if !self.playing.is_empty() {
+            return None;
+        }
+        Some(Projection {
+            low: self.scores.iter().copied().min()?,
+            mean: self.scores.iter().sum::<usize>() as f64 / self.scores.len() as f64,
+            high: self.scores.iter().copied().max()?,
+        })
+    }
+}
+
+// What picks the moves of the computer in a race.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Brain {
+    Greedy,
+    MonteCarlo,
+    NTuple,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Racer {
+    Player,
+    Computer,
+}
+
+// The computer playing its own copy of the player's board, same seed and
+// spawns, first to the target or the higher score once either is stuck.
+#[derive(Debug, Clone)]
+pub struct Race {
+    pub game_state: GameState,
+    pub brain: Brain,
+    // What the greedy and the minimax brain judge boards by.
+    pub weights: Weights,
+    // What the n-tuple brain plays by. Greedy moves stand in until it's there.
+    pub network: Option<Rc<NTupleEvaluator>>,
+    rng: GameRng,
+    // Kept from move to move, the boards a move ahead were searched before.
+    table: TranspositionTable,
+    // Set once the race is decided, with None for a draw.
+    result: Option<Option<Racer>>,
+}
+
+impl Race {
+    pub fn new(game_state: GameState, brain: Brain) -> Race {
+        Race {
+            rng: GameRng::new(game_state.seed()),
+            game_state,
+            brain,
+            weights: Weights::default(),
+            network: None,
+            table: TranspositionTable::default(),
+            result: None,
+        }
+    }
+
+    // Plays the computer's next move, if it has one. On evil boards the
+    // spawner plays against the computer, whatever brain it was given.
+    pub fn step(&mut self) {
+        let direction = match (self.brain, &self.network) {
+            _ if self.game_state.spawn_policy() == SpawnPolicy::Evil => {
+                minimax_move(&self.game_state, RACE_PLIES, &self.weights, &mut self.table)
+            }
+            (Brain::MonteCarlo, _) => {
+                rollout_move(&self.game_state, RACE_ROLLOUTS, RACE_DEPTH, &mut self.rng)
+            }
+            (Brain::NTuple, Some(network)) => ntuple_move(&self.game_state, network),
+            (Brain::Greedy | Brain::NTuple, _) => best_move(&self.game_state, &self.weights),
+        };
+        if let Some(direction) = direction {
+            self.game_state.move_tiles(direction);
+        }
+    }
+
+    // Decides the race if it's over. Called after every move on either board,
+    // so whoever reaches the target first wins it.
+    pub fn settle(&mut self, player: &GameState) {
+        if self.result.is_some() {
+            return;
+        }
+        let computer = &self.game_state;
+        let stuck = |game_state: &GameState| game_state.legal_moves().next().is_none();
+        self.result = if player.is_won() {
+            Some(Some(Racer::Player))
+        } else if computer.is_won() {
+            Some(Some(Racer::Computer))
+        } else if stuck(player) || stuck(computer) {
+            Some(match player.score().cmp(&computer.score()) {
+                Ordering::Greater => Some(Racer::Player),
+                Ordering::Less => Some(Racer::Computer),
+                Ordering::Equal => None,
+            })
+        } else {
+            None
+        };
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    // None for a draw, or while the race goes on.
+    pub fn winner(&self) -> Option<Racer> {
+        self.result.flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::{
+        best_move, hint_move, minimax_move, minimax_spawn, ntuple_move, outlook, rollout_move,
+        Brain, Projector, Race, Racer,
+    };
+    use crate::evaluate::Weights;
+    use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+    use crate::ntuple::NTupleEvaluator;
+    use crate::rng::GameRng;
+    use crate::transposition::TranspositionTable;
+
+    #[test]
+    fn test_best_move() {
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        assert_eq!(
+            None,
+            best_move(&stuck, &Weights::default()),
+            "Best Move: Stuck"
+        );
+
+        // Only sideways moves merge the pair.
+        let pair = GameState::from_values(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(
+            matches!(
+                best_move(&pair, &Weights::default()),
+                Some(Direction::Left | Direction::Right)
+            ),
+            "Best Move: Merge"
+        );
+    }
+
+    #[test]
+    fn test_ntuple_move() {
+        // A single tuple on the corner cell, weighing each code by itself.
+        let mut bytes = b"NTUP\x01\x01\x01\x00".to_vec();
+        for code in 0..16 {
+            bytes.extend_from_slice(&(code as f32).to_le_bytes());
+        }
+        let network = NTupleEvaluator::from_bytes(&bytes).unwrap();
+
+        let edge = GameState::from_values(&[0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(
+            matches!(
+                ntuple_move(&edge, &network),
+                Some(Direction::Left | Direction::Right)
+            ),
+            "N-Tuple: Into The Corner"
+        );
+    }
+
+    #[test]
+    fn test_rollout_move() {
+        let mut rng = GameRng::new(1);
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        assert_eq!(
+            None,
+            rollout_move(&stuck, 4, 10, &mut rng),
+            "Rollout: Stuck"
+        );
+
+        // Games cut off right after the move only score its merge.
+        let pair = GameState::from_values(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(
+            matches!(
+                rollout_move(&pair, 4, 0, &mut rng),
+                Some(Direction::Left | Direction::Right)
+            ),
+            "Rollout: Merge"
+        );
+    }
+
+    #[test]
+    fn test_minimax_move() {
+        let weights = Weights::default();
+        let mut table = TranspositionTable::default();
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        assert_eq!(
+            None,
+            minimax_move(&stuck, 2, &weights, &mut table),
+            "Minimax: Stuck"
+        );
+
+        // Only the left column is free.
+        let mut left = GameState::from_values(&[0, 2, 4, 2, 0, 4, 2, 4, 0, 2, 4, 2, 0, 4, 2, 4]);
+        left.set_spawn(SpawnPolicy::Evil);
+        assert_eq!(
+            Some(Direction::Left),
+            minimax_move(&left, 2, &weights, &mut table),
+            "Minimax: Only Move"
+        );
+    }
+
+    #[test]
+    fn test_hint_move() {
+        let weights = Weights::default();
+        let corner = [64, 32, 16, 8, 32, 16, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let classic = GameState::from_values(&corner);
+        assert_eq!(
+            best_move(&classic, &weights),
+            hint_move(&classic, &weights),
+            "Hint: Classic"
+        );
+
+        let mut evil = GameState::from_values(&corner);
+        evil.set_spawn(SpawnPolicy::Evil);
+        let mut table = TranspositionTable::default();
+        assert_eq!(
+            minimax_move(&evil, 2, &weights, &mut table),
+            hint_move(&evil, &weights),
+            "Hint: Evil"
+        );
+    }
+
+    #[test]
+    fn test_minimax_spawn() {
+        let weights = Weights::default();
+        let mut table = TranspositionTable::default();
+        let full = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        assert_eq!(
+            None,
+            minimax_spawn(&full, 1, &weights, &mut table),
+            "Minimax Spawn: Full"
+        );
+
+        // A 4 in the corner would merge with both neighbours.
+        let mut corner = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 0]);
+        corner.set_spawn(SpawnPolicy::Evil);
+        assert_eq!(
+            Some((15, 2)),
+            minimax_spawn(&corner, 1, &weights, &mut table),
+            "Minimax Spawn: Ends The Game"
+        );
+    }
+
+    #[test]
+    fn test_outlook() {
+        let weights = Weights::default();
+        let mut rng = GameRng::new(1);
+
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        let got = outlook(&stuck, 8, &weights, &mut rng);
+        assert_eq!(0.0, got.survival, "Outlook: Stuck Survival");
+        assert_eq!(Some(0.0), got.win, "Outlook: Stuck Win");
+
+        let mut open = GameState::from_values(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let got = outlook(&open, 8, &weights, &mut rng);
+        assert_eq!(1.0, got.survival, "Outlook: Open Survival");
+        assert!(
+            got.win.is_some_and(|win| win > 0.0 && win < 1.0),
+            "Outlook: Open Win"
+        );
+
+        open.set_target(None);
+        assert_eq!(
+            None,
+            outlook(&open, 8, &weights, &mut rng).win,
+            "Outlook: No Target"
+        );
+    }
+
+    #[test]
+    fn test_projector() {
+        let weights = Weights::default();
+        let mut rng = GameRng::new(1);
+
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        let got = Projector::new(&stuck, 4, &weights, &mut rng).play(4);
+        assert_eq!(
+            Some((0, 0.0, 0)),
+            got.map(|got| (got.low, got.mean, got.high)),
+            "Projector: Stuck"
+        );
+
+        let fresh = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 1);
+        let mut projector = Projector::new(&fresh, 4, &weights, &mut rng);
+        assert_eq!(None, projector.play(10), "Projector: Still Playing");
+        let got = std::iter::repeat_with(|| projector.play(100))
+            .flatten()
+            .next()
+            .unwrap();
+        assert!(got.low > 100, "Projector: Played On");
+        assert!(
+            got.low as f64 <= got.mean && got.mean <= got.high as f64,
+            "Projector: Range"
+        );
+    }
+
+    #[test]
+    fn test_settle() {
+        struct TestCase<'a> {
+            name: &'a str,
+            player: &'a [usize],
+            computer: &'a [usize],
+            want_over: bool,
+            want_winner: Option<Racer>,
+        }
+
+        let open = &[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let stuck = &[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2];
+        let tests = [
+            TestCase {
+                name: "Settle: Going On",
+                player: open,
+                computer: open,
+                want_over: false,
+                want_winner: None,
+            },
+            TestCase {
+                name: "Settle: Player Reached The Target",
+                player: &[1024, 1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                computer: open,
+                want_over: true,
+                want_winner: Some(Racer::Player),
+            },
+            TestCase {
+                name: "Settle: Computer Stuck Behind",
+                player: &[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                computer: stuck,
+                want_over: true,
+                want_winner: Some(Racer::Player),
+            },
+            TestCase {
+                name: "Settle: Both Scoreless",
+                player: stuck,
+                computer: open,
+                want_over: true,
+                want_winner: None,
+            },
+        ];
+
+        for t in tests {
+            let mut player = GameState::from_values(t.player);
+            // Merging the pair is what reaches the target.
+            player.move_tiles(Direction::Left);
+            let mut race = Race::new(GameState::from_values(t.computer), Brain::Greedy);
+            race.settle(&player);
+            assert_eq!(t.want_over, race.is_over(), "{}: Over", t.name);
+            assert_eq!(t.want_winner, race.winner(), "{}: Winner", t.name);
+        }
+    }
+}
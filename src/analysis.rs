@@ -0,0 +1,177 @@
+use crate::ai;
+use crate::evaluate::Weights;
+use crate::game_state::{Action, Direction, GameState, Step};
+use crate::grade::{grade_move, Accuracy, Grade, GradedMove};
+use crate::luck::{self, Luck};
+use crate::rng::GameRng;
+
+// The costliest blunders the report shows.
+pub const BLUNDERS: usize = 3;
+// Greedy games played on from a board to tell if it was lost already.
+const LOST_PLAYOUTS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Blunder {
+    // Counted like the history, power-ups and shuffles included, from 1.
+    pub move_number: usize,
+    pub played: Direction,
+    pub graded: GradedMove,
+    // The board after the computer's move instead, before the next tile.
+    pub better: GameState,
+}
+
+// What the computer makes of a finished game.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub accuracy: Option<f64>,
+    // The costliest first.
+    pub blunders: Vec<Blunder>,
+    // The score as if every tile that landed had been worth what tiles land
+    // with on average, so lucky 4s don't count.
+    pub luck_adjusted_score: usize,
+    pub luck: Luck,
+    // The move after which no greedy game played on survived the next
+    // `ai::OUTLOOK_MOVES` moves, from there to the end, 0 if that was from
+    // the start. None for games not lost.
+    pub lost_at: Option<usize>,
+}
+
+// Plays the game again and grades every move. None for games that can't be
+// replayed.
+pub fn analyze(game_state: &GameState, weights: &Weights) -> Option<Analysis> {
+    let mut boards = Vec::new();
+    let replayed = game_state.replay(|frame| {
+        let mut board = frame.clone();
+        board.forget_history();
+        boards.push(board);
+    });
+    if !replayed {
+        return None;
+    }
+
+    let mut accuracy = Accuracy::default();
+    let mut blunders = Vec::new();
+    for (i, action) in game_state.history().iter().enumerate() {
+        let played = match *action {
+            Action::Move(direction) => direction,
+            _ => continue,
+        };
+        let graded = grade_move(&boards[i], played, weights);
+        accuracy.record(graded);
+        if let Some(graded) = graded.filter(|graded| graded.grade == Grade::Blunder) {
+            blunders.push(Blunder {
+                move_number: i + 1,
+                played,
+                graded,
+                better: boards[i].preview_move(graded.best)?,
+            });
+        }
+    }
+    blunders.sort_by(|a, b| b.graded.loss.total_cmp(&a.graded.loss));
+    blunders.truncate(BLUNDERS);
+
+    let steps = game_state.steps();
+    Some(Analysis {
+        accuracy: accuracy.percent(),
+        blunders,
+        luck_adjusted_score: luck_adjusted_score(game_state, &steps),
+        luck: luck::luck(&boards[1..], &steps, weights),
+        lost_at: lost_at(game_state, &boards, weights),
+    })
+}
+
+fn luck_adjusted_score(game_state: &GameState, steps: &[Step]) -> usize {
+    let mut score = 0;
+    let (mut expected, mut landed) = (0.0, 0.0);
+    for step in steps {
+        if let Some((_, value)) = step.spawned {
+            expected += game_state
+                .spawn_policy()
+                .odds(score)
+                .iter()
+                .map(|&(value, chance)| value as f64 * chance)
+                .sum::<f64>();
+            landed += value as f64;
+        }
+        score += step.score_gained;
+    }
+    if landed == 0.0 {
+        return game_state.score();
+    }
+    (game_state.score() as f64 * expected / landed).round() as usize
+}
+
+// Walks back from the stuck board for as long as the boards were lost.
+fn lost_at(game_state: &GameState, boards: &[GameState], weights: &Weights) -> Option<usize> {
+    if game_state.is_won() || game_state.legal_moves().next().is_some() {
+        return None;
+    }
+    let lost = |board: &GameState| {
+        let mut rng = GameRng::new(board.board_hash());
+        ai::outlook(board, LOST_PLAYOUTS, weights, &mut rng).survival == 0.0
+    };
+    Some(
+        boards
+            .iter()
+            .rposition(|board| !lost(board))
+            .map_or(0, |i| i + 1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{analyze, lost_at, BLUNDERS};
+    use crate::evaluate::Weights;
+    use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+
+    #[test]
+    fn test_analyze() {
+        let weights = Weights::default();
+        assert!(
+            analyze(&GameState::from_values(&[2; 16]), &weights).is_none(),
+            "Analyze: Nothing To Replay"
+        );
+
+        // Down first and up after, out of every corner.
+        let mut game_state = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 7);
+        for &direction in [Direction::Down, Direction::Up].iter().cycle().take(30) {
+            game_state.move_tiles(direction);
+        }
+        let analysis = analyze(&game_state, &weights).unwrap();
+        assert!(
+            analysis.accuracy.is_some_and(|accuracy| accuracy < 100.0),
+            "Analyze: Accuracy"
+        );
+        assert!(analysis.blunders.len() <= BLUNDERS, "Analyze: Blunders");
+        assert!(
+            analysis
+                .blunders
+                .windows(2)
+                .all(|pair| pair[0].graded.loss >= pair[1].graded.loss),
+            "Analyze: Costliest First"
+        );
+        assert!(
+            analysis.luck_adjusted_score > 0,
+            "Analyze: Luck Adjusted Score"
+        );
+        assert_eq!(30, analysis.luck.spawns, "Analyze: Luck");
+        assert_eq!(None, analysis.lost_at, "Analyze: Not Lost");
+    }
+
+    #[test]
+    fn test_lost_at() {
+        let weights = Weights::default();
+        let stuck = GameState::from_values(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]);
+        let open = GameState::from_values(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(
+            Some(2),
+            lost_at(&stuck, &[open.clone(), open, stuck.clone()], &weights),
+            "Lost At: Last Move"
+        );
+        assert_eq!(
+            Some(0),
+            lost_at(&stuck, &[stuck.clone(), stuck.clone()], &weights),
+            "Lost At: From The Start"
+        );
+    }
+}
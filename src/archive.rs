@@ -0,0 +1,307 @@
+use seed::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode};
+
+use crate::game_state::GameState;
+use crate::mode::Mode;
+use crate::records::GameRecord;
+use crate::storage::{Seal, Storage};
+
+// How many of the latest games keep their moves when the archive is compressed.
+const KEPT_REPLAYS: usize = 50;
+
+const DATABASE: &str = "2048";
+const VERSION: u32 = 1;
+// Games are keyed by when they ended.
+const STORE: &str = "archive";
+
+// A finished game with everything needed to replay it, kept in IndexedDB as
+// there is no telling how many a player collects.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArchivedGame {
+    pub record: GameRecord,
+    pub won: bool,
+    pub game_state: GameState,
+}
+
+impl ArchivedGame {
+    pub fn new(record: GameRecord, game_state: &GameState) -> ArchivedGame {
+        ArchivedGame {
+            record,
+            won: game_state.is_won(),
+            game_state: game_state.clone(),
+        }
+    }
+
+    // How many cells the board has, the same for every game of a variant.
+    pub fn size(&self) -> usize {
+        self.game_state.cells().len()
+    }
+}
+
+// Which archived games are listed, everything that is none matches all.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub won: Option<bool>,
+    pub mode: Option<Mode>,
+    pub size: Option<usize>,
+}
+
+impl Filter {
+    pub fn matches(&self, game: &ArchivedGame) -> bool {
+        self.won.is_none_or(|won| won == game.won)
+            && self.mode.is_none_or(|mode| mode == game.record.mode)
+            && self.size.is_none_or(|size| size == game.size())
+    }
+}
+
+// Frees space by dropping the older half of the games.
+pub fn prune(games: &mut Vec<ArchivedGame>) {
+    games.drain(..games.len() / 2);
+}
+
+// Frees space by dropping the moves of all but the latest games, which keep
+// their summary and final board.
+pub fn compress(games: &mut [ArchivedGame]) {
+    let old = games.len().saturating_sub(KEPT_REPLAYS);
+    for game in &mut games[..old] {
+        game.game_state.forget_history();
+    }
+}
+
+// Resolves once the request succeeded, with what it returned.
+async fn finished(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await?;
+    request.result()
+}
+
+// Games sharing a page each have their own database.
+fn database(namespace: &str) -> String {
+    if namespace.is_empty() {
+        DATABASE.to_string()
+    } else {
+        format!("{}:{}", DATABASE, namespace)
+    }
+}
+
+async fn open(namespace: &str) -> Result<IdbDatabase, JsValue> {
+    let request = window()
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from("IndexedDB isn't available"))?
+        .open_with_u32(&database(namespace), VERSION)?;
+
+    // Only called the first time, or once the version goes up.
+    let upgraded = request.clone();
+    let upgrade = Closure::once_into_js(move || {
+        if let Some(database) = upgraded
+            .result()
+            .ok()
+            .and_then(|database| database.dyn_into::<IdbDatabase>().ok())
+        {
+            if let Err(err) = database.create_object_store(STORE) {
+                log!("Creating the archive failed:", err);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+
+    finished(&request).await?.dyn_into()
+}
+
+pub async fn add(game: ArchivedGame, storage: Storage) -> Result<(), String> {
+    save_all(vec![game], false, storage).await
+}
+
+// Stores the games in one go, in place of every archived game when `clear`.
+// Nothing is touched while saves are locked.
+pub async fn save_all(
+    games: Vec<ArchivedGame>,
+    clear: bool,
+    storage: Storage,
+) -> Result<(), String> {
+    let seal = storage.seal();
+    if seal == Seal::Locked {
+        return Ok(());
+    }
+    let mut values = Vec::new();
+    for game in &games {
+        let json = serde_json::to_string(game).map_err(|err| err.to_string())?;
+        if let Some(stored) = seal.encode(json) {
+            values.push((JsValue::from(stored), JsValue::from(game.record.ended_at)));
+        }
+    }
+
+    let result: Result<_, JsValue> = async {
+        let store = open(storage.namespace())
+            .await?
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?
+            .object_store(STORE)?;
+        // Requests of a transaction finish in order, and a failed one fails
+        // the rest, so the last one tells how all went.
+        let mut last = None;
+        if clear {
+            last = Some(store.clear()?);
+        }
+        for (json, key) in &values {
+            last = Some(store.put_with_key(json, key)?);
+        }
+        if let Some(last) = last {
+            finished(&last).await?;
+        }
+        Ok(())
+    }
+    .await;
+
+    result.map_err(|err| format!("{:?}", err))
+}
+
+// Every archived game, oldest first. Ones that no longer parse, or were sealed
+// with another key, are left out.
+pub async fn load(storage: Storage) -> Result<Vec<ArchivedGame>, String> {
+    let seal = storage.seal();
+    if seal == Seal::Locked {
+        return Ok(Vec::new());
+    }
+
+    let result: Result<_, JsValue> = async {
+        let store = open(storage.namespace())
+            .await?
+            .transaction_with_str(STORE)?
+            .object_store(STORE)?;
+        finished(&store.get_all()?).await
+    }
+    .await;
+
+    let values = result.map_err(|err| format!("{:?}", err))?;
+    Ok(js_sys::Array::from(&values)
+        .iter()
+        .filter_map(|value| value.as_string())
+        .filter_map(|stored| seal.decode(stored))
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::archive::{compress, prune, ArchivedGame, Filter, KEPT_REPLAYS};
+    use crate::game_state::Direction;
+    use crate::game_state::{GameState, Variant};
+    use crate::mode::Mode;
+    use crate::records::GameRecord;
+
+    #[test]
+    fn test_filter() {
+        struct TestCase<'a> {
+            name: &'a str,
+            filter: Filter,
+            want: bool,
+        }
+
+        let game = ArchivedGame {
+            record: GameRecord {
+                ended_at: 0.0,
+                seed: 1,
+                variant: Variant::Classic,
+                mode: Mode::TimeAttack,
+                score: 1000,
+                max_tile: 128,
+                moves: 90,
+                duration: None,
+            },
+            won: false,
+            game_state: GameState::from_values(&[0; 16]),
+        };
+
+        let tests = [
+            TestCase {
+                name: "Filter: Everything",
+                filter: Filter::default(),
+                want: true,
+            },
+            TestCase {
+                name: "Filter: Lost In Time Attack On 16 Cells",
+                filter: Filter {
+                    won: Some(false),
+                    mode: Some(Mode::TimeAttack),
+                    size: Some(16),
+                },
+                want: true,
+            },
+            TestCase {
+                name: "Filter: Won",
+                filter: Filter {
+                    won: Some(true),
+                    ..Filter::default()
+                },
+                want: false,
+            },
+            TestCase {
+                name: "Filter: Other Mode",
+                filter: Filter {
+                    mode: Some(Mode::Endless),
+                    ..Filter::default()
+                },
+                want: false,
+            },
+            TestCase {
+                name: "Filter: Other Size",
+                filter: Filter {
+                    size: Some(19),
+                    ..Filter::default()
+                },
+                want: false,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, t.filter.matches(&game), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_prune_and_compress() {
+        let mut game_state =
+            GameState::from_values(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        game_state.move_tiles(Direction::Left);
+        let games = (0..KEPT_REPLAYS + 10).map(|i| ArchivedGame {
+            record: GameRecord {
+                ended_at: i as f64,
+                seed: 1,
+                variant: Variant::Classic,
+                mode: Mode::Endless,
+                score: 4,
+                max_tile: 4,
+                moves: 1,
+                duration: None,
+            },
+            won: false,
+            game_state: game_state.clone(),
+        });
+
+        let mut pruned: Vec<ArchivedGame> = games.clone().collect();
+        prune(&mut pruned);
+        assert_eq!(30, pruned.len(), "Prune: Half Left");
+        assert_eq!(30.0, pruned[0].record.ended_at, "Prune: Oldest Dropped");
+
+        let mut compressed: Vec<ArchivedGame> = games.collect();
+        compress(&mut compressed);
+        let replayable = compressed
+            .iter()
+            .filter(|game| !game.game_state.history().is_empty())
+            .count();
+        assert_eq!(KEPT_REPLAYS, replayable, "Compress: Latest Keep Moves");
+        assert!(
+            compressed[0].game_state.history().is_empty(),
+            "Compress: Oldest Lose Moves"
+        );
+        assert_eq!(
+            Some(4),
+            compressed[0].game_state.cells()[0].map(|tile| tile.get_value()),
+            "Compress: Board Kept"
+        );
+    }
+}
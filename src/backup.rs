@@ -0,0 +1,219 @@
+use seed::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::achievements;
+use crate::archive::ArchivedGame;
+use crate::game_state::{GameState, Variant};
+use crate::leaderboard::Leaderboard;
+use crate::mode::Mode;
+use crate::records::{self, GameRecord};
+use crate::settings::Settings;
+use crate::stats::Stats;
+
+// Bumped whenever a backup changes in a way older versions can't read.
+pub const VERSION: u32 = 1;
+
+// How an imported backup is combined with what this browser has.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Import {
+    // Keeps everything here and adds what the backup has on top.
+    Merge,
+    Replace,
+}
+
+// Everything the game keeps about a player, to move it to another browser.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Backup {
+    pub version: u32,
+    pub created_at: f64,
+    pub settings: Settings,
+    pub stats: Stats,
+    #[serde(default)]
+    pub games: Vec<GameRecord>,
+    #[serde(default)]
+    pub solved_puzzles: BTreeSet<String>,
+    // When each achievement was unlocked, by name.
+    #[serde(default)]
+    pub achievements: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub leaderboards: Vec<(Mode, Leaderboard)>,
+    // The saved endless game of each variant.
+    #[serde(default)]
+    pub saves: Vec<(Variant, GameState)>,
+    #[serde(default)]
+    pub archive: Vec<ArchivedGame>,
+}
+
+impl Backup {
+    pub fn parse(json: &str) -> Result<Backup, String> {
+        let backup: Backup =
+            serde_json::from_str(json).map_err(|_| "That isn't a backup of this game.")?;
+        if backup.version > VERSION {
+            return Err("That backup is from a newer version of the game.".to_string());
+        }
+        Ok(backup)
+    }
+
+    // Settings and saved games stay as they are here, the backup only fills
+    // in variants without one. Everything else is combined.
+    pub fn merge(mut self, other: Backup) -> Backup {
+        self.stats = self.stats.merge(&other.stats);
+        records::merge(&mut self.games, &other.games);
+        self.solved_puzzles.extend(other.solved_puzzles);
+        achievements::merge(&mut self.achievements, &other.achievements);
+
+        for (mode, leaderboard) in other.leaderboards {
+            match self.leaderboards.iter_mut().find(|(m, _)| *m == mode) {
+                Some((_, mine)) => *mine = mine.merge(&leaderboard),
+                None => self.leaderboards.push((mode, leaderboard)),
+            }
+        }
+        for (variant, game_state) in other.saves {
+            if self.saves.iter().all(|(v, _)| *v != variant) {
+                self.saves.push((variant, game_state));
+            }
+        }
+        for game in other.archive {
+            if self
+                .archive
+                .iter()
+                .all(|g| g.record.ended_at != game.record.ended_at)
+            {
+                self.archive.push(game);
+            }
+        }
+        self.archive
+            .sort_by(|a, b| a.record.ended_at.total_cmp(&b.record.ended_at));
+
+        self
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|err| err.to_string())
+    }
+}
+
+pub async fn read(file: web_sys::File) -> Result<String, String> {
+    JsFuture::from(file.text())
+        .await
+        .ok()
+        .and_then(|text| text.as_string())
+        .ok_or_else(|| "Couldn't read the file.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backup::{Backup, VERSION};
+    use crate::game_state::{GameState, Variant};
+    use crate::leaderboard::{Entry, Leaderboard};
+    use crate::mode::Mode;
+    use crate::records::GameRecord;
+    use crate::settings::Settings;
+    use crate::stats::Stats;
+
+    fn record(ended_at: f64) -> GameRecord {
+        GameRecord {
+            ended_at,
+            seed: 1,
+            variant: Variant::Classic,
+            mode: Mode::Endless,
+            score: 0,
+            max_tile: 2,
+            moves: 0,
+            duration: None,
+        }
+    }
+
+    fn leaderboard(scores: &[usize]) -> Leaderboard {
+        let mut leaderboard = Leaderboard::default();
+        for &score in scores {
+            leaderboard.record(Entry {
+                score,
+                best_tile: 2,
+            });
+        }
+        leaderboard
+    }
+
+    fn backup(games: &[f64], puzzle: &str, scores: &[usize], saves: &[Variant]) -> Backup {
+        Backup {
+            version: VERSION,
+            created_at: 0.0,
+            settings: Settings::default(),
+            stats: Stats::default(),
+            games: games.iter().map(|&ended_at| record(ended_at)).collect(),
+            solved_puzzles: std::iter::once(puzzle.to_string()).collect(),
+            achievements: Default::default(),
+            leaderboards: vec![(Mode::TimeAttack, leaderboard(scores))],
+            saves: saves
+                .iter()
+                .map(|&variant| (variant, GameState::from_values(&[0; 16])))
+                .collect(),
+            archive: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mine = backup(&[1.0, 3.0], "Corner", &[500, 100], &[Variant::Classic]);
+        let other = backup(
+            &[2.0, 3.0],
+            "Snake",
+            &[300, 100],
+            &[Variant::Classic, Variant::Hex],
+        );
+
+        let merged = mine.merge(other);
+        let games: Vec<f64> = merged.games.iter().map(|game| game.ended_at).collect();
+        assert_eq!(vec![1.0, 2.0, 3.0], games, "Merge: Games");
+        assert_eq!(2, merged.solved_puzzles.len(), "Merge: Puzzles");
+        let scores: Vec<usize> = merged.leaderboards[0]
+            .1
+            .entries()
+            .iter()
+            .map(|entry| entry.score)
+            .collect();
+        assert_eq!(vec![500, 300, 100], scores, "Merge: Leaderboards");
+        let saves: Vec<Variant> = merged.saves.iter().map(|&(variant, _)| variant).collect();
+        assert_eq!(vec![Variant::Classic, Variant::Hex], saves, "Merge: Saves");
+    }
+
+    #[test]
+    fn test_parse() {
+        struct TestCase<'a> {
+            name: &'a str,
+            json: String,
+            want: bool,
+        }
+
+        let json = backup(&[1.0], "Corner", &[100], &[Variant::Classic])
+            .to_json()
+            .unwrap();
+        let tests = [
+            TestCase {
+                name: "Parse: Backup",
+                json: json.clone(),
+                want: true,
+            },
+            TestCase {
+                name: "Parse: Newer Version",
+                json: json.replacen(
+                    &format!("\"version\":{}", VERSION),
+                    &format!("\"version\":{}", VERSION + 1),
+                    1,
+                ),
+                want: false,
+            },
+            TestCase {
+                name: "Parse: Something Else",
+                json: "{\"score\":4}".to_string(),
+                want: false,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, Backup::parse(&t.json).is_ok(), "{}", t.name);
+        }
+    }
+}
@@ -0,0 +1,3 @@
+// What the benchmarks in benches/ play with; they only see what the crate
+// exports.
+pub use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use rust_2048::simulate::{self, Options};
+
+// Plays games headlessly and prints a JSON report, e.g.
+// `cargo run --release --bin simulate -- '{"games": 1000, "strategy": "random"}'`.
+// See `simulate::Options` for what the options may set. A file after the
+// options gets samples of the moves, a line of JSON each.
+fn main() {
+    let options = std::env::args().nth(1).unwrap_or_else(|| "{}".to_string());
+    let samples = std::env::args().nth(2);
+    match run(&options, samples.as_deref()) {
+        Ok(report) => println!("{}", report),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(options: &str, samples: Option<&str>) -> Result<String, String> {
+    let samples = match samples {
+        Some(path) => path,
+        None => return simulate::run_json(options),
+    };
+    let options: Options =
+        serde_json::from_str(options).map_err(|err| format!("Invalid options: {}", err))?;
+    let file = File::create(samples).map_err(|err| format!("{}: {}", samples, err))?;
+    let mut out = BufWriter::new(file);
+    let report = simulate::run_sampled(&options, &mut out)?;
+    out.flush().map_err(|err| format!("{}: {}", samples, err))?;
+    serde_json::to_string(&report).map_err(|err| err.to_string())
+}
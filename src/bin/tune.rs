@@ -0,0 +1,13 @@
+// Tunes the weights the computer judges boards by and prints the best as
+// JSON, e.g. `cargo run --release --bin tune -- '{"search": "evolve"}'`.
+// See `tune::TuneOptions` for what the options may set.
+fn main() {
+    let options = std::env::args().nth(1).unwrap_or_else(|| "{}".to_string());
+    match rust_2048::tune::tune_json(&options) {
+        Ok(report) => println!("{}", report),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
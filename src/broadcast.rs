@@ -0,0 +1,197 @@
+use seed::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{Action, GameState};
+
+// What goes over a channel, as JSON text. Like the rooms of the versus
+// game, the server only passes every message on to the others listening.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum Message {
+    // Sent by spectators on joining, asking for the game so far.
+    Watching,
+    Log(Log),
+}
+
+// The actions of a game from `from` on. A new game, or one whose history
+// changed rather than grew, comes with the board to start from.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Log {
+    pub seed: u64,
+    pub start: Option<Box<GameState>>,
+    pub from: usize,
+    pub actions: Vec<Action>,
+}
+
+// The broadcasting side, keeping track of what went out.
+#[derive(Debug, Default)]
+pub struct Feed {
+    seed: u64,
+    sent: Vec<Action>,
+}
+
+impl Feed {
+    // Everything a spectator needs to follow from here on.
+    pub fn full(&mut self, game_state: &GameState) -> Log {
+        let mut start = None;
+        game_state.replay(|frame| {
+            start.get_or_insert_with(|| frame.clone());
+        });
+        self.seed = game_state.seed();
+        self.sent = game_state.history().to_vec();
+        match start {
+            Some(start) => Log {
+                seed: self.seed,
+                start: Some(Box::new(start)),
+                from: 0,
+                actions: self.sent.clone(),
+            },
+            // Games without a start to go back to are followed from here.
+            None => Log {
+                seed: self.seed,
+                start: Some(Box::new(game_state.clone())),
+                from: self.sent.len(),
+                actions: Vec::new(),
+            },
+        }
+    }
+
+    // What changed since the last log, if anything.
+    pub fn next(&mut self, game_state: &GameState) -> Option<Log> {
+        let history = game_state.history();
+        if game_state.seed() != self.seed || !history.starts_with(&self.sent) {
+            return Some(self.full(game_state));
+        }
+        if history.len() == self.sent.len() {
+            return None;
+        }
+        let from = self.sent.len();
+        self.sent.extend_from_slice(&history[from..]);
+        Some(Log {
+            seed: self.seed,
+            start: None,
+            from,
+            actions: history[from..].to_vec(),
+        })
+    }
+}
+
+// The spectating side, playing the actions on its own copy of the board.
+#[derive(Debug, Default)]
+pub struct Follower {
+    pub game_state: Option<GameState>,
+    seed: u64,
+    applied: usize,
+}
+
+impl Follower {
+    // Returns false when actions went missing, e.g. from joining halfway,
+    // and the game has to be asked for again.
+    pub fn follow(&mut self, log: Log) -> bool {
+        if let Some(start) = log.start {
+            self.game_state = Some(*start);
+            self.seed = log.seed;
+            self.applied = log.from;
+        }
+        let game_state = match self.game_state.as_mut() {
+            Some(game_state) if log.seed == self.seed && log.from <= self.applied => game_state,
+            _ => return false,
+        };
+        for action in log.actions.iter().skip(self.applied - log.from) {
+            game_state.play(action);
+            self.applied += 1;
+        }
+        true
+    }
+}
+
+// The game going out on a channel, closed when dropped. Logs that don't
+// make it are asked for again by the spectators that miss them.
+pub struct Broadcast {
+    socket: WebSocket,
+    pub channel: String,
+    feed: Feed,
+}
+
+impl Broadcast {
+    pub fn new(socket: WebSocket, channel: String) -> Broadcast {
+        Broadcast {
+            socket,
+            channel,
+            feed: Feed::default(),
+        }
+    }
+
+    pub fn follow(&mut self, game_state: &GameState) {
+        if let Some(log) = self.feed.next(game_state) {
+            self.socket.send_json(&Message::Log(log)).ok();
+        }
+    }
+
+    pub fn send_all(&mut self, game_state: &GameState) {
+        let log = self.feed.full(game_state);
+        self.socket.send_json(&Message::Log(log)).ok();
+    }
+}
+
+// A channel watched, closed when dropped.
+pub struct Spectator {
+    socket: WebSocket,
+    pub channel: String,
+    pub follower: Follower,
+}
+
+impl Spectator {
+    pub fn new(socket: WebSocket, channel: String) -> Spectator {
+        Spectator {
+            socket,
+            channel,
+            follower: Follower::default(),
+        }
+    }
+
+    pub fn ask(&self) {
+        self.socket.send_json(&Message::Watching).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::broadcast::{Feed, Follower, Log};
+    use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+
+    fn round_trip(log: Log) -> Log {
+        serde_json::from_str(&serde_json::to_string(&log).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_follow() {
+        let mut game_state = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 7);
+        let mut feed = Feed::default();
+        let mut follower = Follower::default();
+        assert!(follower.follow(round_trip(feed.full(&game_state))), "Start");
+
+        game_state.move_tiles(Direction::Left);
+        game_state.move_tiles(Direction::Up);
+        let log = feed.next(&game_state).unwrap();
+        assert_eq!((0, 2), (log.from, log.actions.len()), "Increment");
+        assert!(follower.follow(round_trip(log)), "Followed");
+        assert!(feed.next(&game_state).is_none(), "Nothing New");
+
+        // A spectator joining now has missed the start.
+        game_state.move_tiles(Direction::Right);
+        let log = feed.next(&game_state).unwrap();
+        assert!(!Follower::default().follow(log.clone()), "Joined Halfway");
+        assert!(follower.follow(round_trip(log)), "Followed Again");
+        assert_eq!(
+            game_state.to_string(),
+            follower.game_state.as_ref().unwrap().to_string(),
+            "Same Board"
+        );
+
+        let new_game = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 8);
+        let log = feed.next(&new_game).unwrap();
+        assert!(log.start.is_some(), "New Game: Start");
+        assert!(follower.follow(round_trip(log)), "New Game: Followed");
+    }
+}
@@ -0,0 +1,178 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::game_state::{SpawnPolicy, Variant};
+use crate::mode::Mode;
+
+// Names longer than this are cut, the banner has to fit above the board.
+const MAX_NAME: usize = 30;
+
+// A game sent by link to be played again from the same seed, with the score
+// of whoever sent it to beat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    pub seed: u64,
+    pub variant: Variant,
+    pub mode: Mode,
+    // Spawns and combo scoring as the sender played them, so the tiles and
+    // the score come out the same.
+    pub spawn: SpawnPolicy,
+    pub combo: bool,
+    pub score: usize,
+    pub name: String,
+}
+
+impl Challenge {
+    // The query of a challenge link. Variants and modes go by their names.
+    pub fn to_query(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("seed", self.seed.to_string()),
+            ("variant", to_param(&self.variant)),
+            ("mode", to_param(&self.mode)),
+            ("spawn", to_param(&self.spawn)),
+            ("combo", self.combo.to_string()),
+            ("score", self.score.to_string()),
+            ("name", self.name.clone()),
+        ]
+    }
+
+    // Puzzles and the editor don't play from a seed, so they are never
+    // challenged to.
+    pub fn from_query(get: impl Fn(&str) -> Option<String>) -> Option<Challenge> {
+        let mode = from_param(&get("mode")?)?;
+        if matches!(mode, Mode::Puzzle | Mode::Editor) {
+            return None;
+        }
+        Some(Challenge {
+            seed: get("seed")?.parse().ok()?,
+            variant: from_param(&get("variant")?)?,
+            mode,
+            spawn: get("spawn").and_then(|spawn| from_param(&spawn))?,
+            combo: get("combo").is_some_and(|combo| combo == "true"),
+            score: get("score")?.parse().ok()?,
+            name: get("name")
+                .unwrap_or_default()
+                .trim()
+                .chars()
+                .take(MAX_NAME)
+                .collect(),
+        })
+    }
+}
+
+// Plain names stay readable, e.g. `TimeAttack`, the rest are left as JSON,
+// e.g. `{"Obstacles":2}`.
+fn to_param(value: &impl Serialize) -> String {
+    let json = serde_json::to_string(value).unwrap_or_default();
+    json.trim_matches('"').to_string()
+}
+
+fn from_param<T: DeserializeOwned>(param: &str) -> Option<T> {
+    serde_json::from_str(param)
+        .or_else(|_| serde_json::from_str(&format!("\"{}\"", param)))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::challenge::Challenge;
+    use crate::game_state::{SpawnPolicy, Variant};
+    use crate::mode::Mode;
+
+    fn parse(pairs: &[(&str, &str)]) -> Option<Challenge> {
+        let query: HashMap<&str, &str> = pairs.iter().copied().collect();
+        Challenge::from_query(|key| query.get(key).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn test_query() {
+        let challenge = Challenge {
+            seed: 42,
+            variant: Variant::Obstacles(2),
+            mode: Mode::TimeAttack,
+            spawn: SpawnPolicy::Classic,
+            combo: true,
+            score: 12345,
+            name: "Sam".to_string(),
+        };
+        let query = challenge.to_query();
+        assert!(
+            query.contains(&("mode", "TimeAttack".to_string())),
+            "Query: Mode By Name"
+        );
+        let pairs: Vec<(&str, &str)> = query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(Some(challenge), parse(&pairs), "Query: Round Trip");
+    }
+
+    #[test]
+    fn test_from_query() {
+        struct TestCase<'a> {
+            name: &'a str,
+            pairs: &'a [(&'a str, &'a str)],
+            want: Option<(u64, Mode, &'a str)>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Challenge: No Name",
+                pairs: &[
+                    ("seed", "7"),
+                    ("variant", "Classic"),
+                    ("mode", "Endless"),
+                    ("spawn", "Classic"),
+                    ("score", "100"),
+                ],
+                want: Some((7, Mode::Endless, "")),
+            },
+            TestCase {
+                name: "Challenge: Long Name Cut",
+                pairs: &[
+                    ("seed", "7"),
+                    ("variant", "Hex"),
+                    ("mode", "MoveLimit"),
+                    ("spawn", "Classic"),
+                    ("score", "100"),
+                    ("name", "  A name far longer than any banner could fit  "),
+                ],
+                want: Some((7, Mode::MoveLimit, "A name far longer than any ban")),
+            },
+            TestCase {
+                name: "Challenge: Puzzle",
+                pairs: &[
+                    ("seed", "7"),
+                    ("variant", "Classic"),
+                    ("mode", "Puzzle"),
+                    ("spawn", "Classic"),
+                    ("score", "100"),
+                ],
+                want: None,
+            },
+            TestCase {
+                name: "Challenge: Bad Seed",
+                pairs: &[
+                    ("seed", "-1"),
+                    ("variant", "Classic"),
+                    ("mode", "Endless"),
+                    ("spawn", "Classic"),
+                    ("score", "100"),
+                ],
+                want: None,
+            },
+            TestCase {
+                name: "Challenge: Not A Challenge",
+                pairs: &[("utm_source", "chat")],
+                want: None,
+            },
+        ];
+
+        for t in tests {
+            let got = parse(t.pairs);
+            let got = got
+                .as_ref()
+                .map(|challenge| (challenge.seed, challenge.mode, challenge.name.as_str()));
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+// Sealed with the key to tell a wrong passphrase from a right one.
+const CHECK: &str = "2048";
+
+// The key saves are sealed with, derived from the player's passphrase.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Key([u8; 32]);
+
+// Never printed, storage errors are shown on the page.
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Key {
+    fn derive(passphrase: &str, salt: &[u8]) -> Option<Key> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .ok()?;
+        Some(Key(key))
+    }
+
+    // Hex of a fresh nonce followed by the ciphertext.
+    pub fn seal(&self, text: &str) -> String {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let ciphertext = XChaCha20Poly1305::new(&self.0.into())
+            .encrypt(XNonce::from_slice(&nonce), text.as_bytes())
+            .unwrap_or_default();
+        to_hex(&[&nonce[..], &ciphertext].concat())
+    }
+
+    // None for text that wasn't sealed with this key, or was changed since.
+    pub fn open(&self, sealed: &str) -> Option<String> {
+        let bytes = from_hex(sealed)?;
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let text = XChaCha20Poly1305::new(&self.0.into())
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .ok()?;
+        String::from_utf8(text).ok()
+    }
+}
+
+// What is kept in the clear about encrypted saves, to unlock them again.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Vault {
+    salt: String,
+    check: String,
+}
+
+impl Vault {
+    pub fn new(passphrase: &str) -> Option<(Vault, Key)> {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let key = Key::derive(passphrase, &salt)?;
+        let vault = Vault {
+            salt: to_hex(&salt),
+            check: key.seal(CHECK),
+        };
+        Some((vault, key))
+    }
+
+    // The key, if the passphrase is the one the vault was made with.
+    pub fn unlock(&self, passphrase: &str) -> Option<Key> {
+        let key = Key::derive(passphrase, &from_hex(&self.salt)?)?;
+        (key.open(&self.check)? == CHECK).then_some(key)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypt::{from_hex, to_hex, Vault};
+
+    #[test]
+    fn test_hex() {
+        assert_eq!("00ff10", to_hex(&[0, 255, 16]), "Hex: Encode");
+        assert_eq!(Some(vec![0, 255, 16]), from_hex("00ff10"), "Hex: Decode");
+        assert_eq!(None, from_hex("0f1"), "Hex: Odd Length");
+        assert_eq!(None, from_hex("zz"), "Hex: Not Hex");
+    }
+
+    #[test]
+    fn test_vault() {
+        let (vault, key) = Vault::new("correct horse").unwrap();
+        assert_eq!(
+            Some(key),
+            vault.unlock("correct horse"),
+            "Vault: Right Passphrase"
+        );
+        assert_eq!(
+            None,
+            vault.unlock("battery staple"),
+            "Vault: Wrong Passphrase"
+        );
+
+        let sealed = key.seal("{\"score\":2048}");
+        assert_ne!(sealed, key.seal("{\"score\":2048}"), "Seal: Fresh Nonce");
+        assert_eq!(
+            Some("{\"score\":2048}".to_string()),
+            key.open(&sealed),
+            "Seal: Round Trip"
+        );
+
+        let mut tampered = sealed.into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'0' { b'1' } else { b'0' };
+        assert_eq!(
+            None,
+            key.open(&String::from_utf8(tampered).unwrap()),
+            "Seal: Tampered"
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use seed::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+
+use crate::evaluate::Weights;
+use crate::game_state::{self, Action, FinalState, GameState, SpawnPolicy, Variant};
+use crate::mode::Mode;
+
+#[wasm_bindgen(typescript_custom_section)]
+const SEED_2048_OPTIONS: &str = r#"
+export interface Seed2048Options {
+    captureKeys?: boolean;
+    variant?: string | { Obstacles: number };
+    mode?: string;
+    theme?: string;
+    readOnly?: boolean;
+    classPrefix?: string;
+    aiWeights?: Seed2048Weights;
+    onScore?: (score: number) => void;
+    onGameOver?: (game: Seed2048GameOver) => void;
+}
+
+// What the computer judges boards by, as the tuner prints them.
+export interface Seed2048Weights {
+    empty?: number;
+    monotonicity?: number;
+    smoothness?: number;
+    cornerMax?: number;
+    merges?: number;
+}
+
+export interface Seed2048GameOver {
+    score: number;
+    maxTile: number;
+    moves: number;
+    won: boolean;
+    // Everything `verify_replay` needs to play the game again.
+    seed: string;
+    variant: string | { Obstacles: number };
+    spawn: string;
+    combo: boolean;
+    actions: unknown[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    // What a host page passes to `start_with_config`, read into `Options`.
+    #[wasm_bindgen(typescript_type = "Seed2048Options")]
+    pub type Seed2048Options;
+}
+
+// How a game is mounted, by `start` on the game's own page or by
+// `start_with_config` on a host page that may mount several.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    // Set apart every storage key and the archive, so games keep their own
+    // saves. Empty on the game's own page.
+    pub namespace: &'static str,
+    // The element mounted to by a host page.
+    pub root: Option<web_sys::Element>,
+    pub options: Options,
+}
+
+impl Config {
+    // Whether a key pressed on `target` is meant for this game. On a host
+    // page keys only go to the game focus is in, unless it captures them.
+    pub fn takes_keys(&self, target: Option<&web_sys::EventTarget>) -> bool {
+        let root = match &self.root {
+            Some(root) if !self.options.capture_keys => root,
+            _ => return true,
+        };
+        target
+            .and_then(|target| target.dyn_ref::<web_sys::Node>())
+            .is_some_and(|node| root.contains(Some(node)))
+    }
+}
+
+// `Seed2048Options` as the game uses them, everything left out keeps what
+// the game's own page does.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    // Takes arrow keys wherever focus is on the page, like the only game on it.
+    pub capture_keys: bool,
+    // Played from the start instead of what was played last. The variant
+    // also picks the board, and with it how many cells it has.
+    pub variant: Option<Variant>,
+    pub mode: Option<Mode>,
+    // Set on the mounted element as `theme-<name>`, for the host's stylesheet.
+    pub theme: Option<String>,
+    // Shows the game without taking moves.
+    pub read_only: bool,
+    // Put before the board's classes, `tile`, `tile-2`, `grid-cell` and so on,
+    // when the host's stylesheet draws the board instead of the game's own.
+    pub class_prefix: String,
+    // What the computer of races judges boards by, e.g. from the tuner.
+    pub ai_weights: Option<Weights>,
+    #[serde(skip)]
+    pub callbacks: Callbacks,
+}
+
+impl Options {
+    pub fn parse(options: &JsValue) -> Result<Options, String> {
+        if options.is_undefined() || options.is_null() {
+            return Ok(Options::default());
+        }
+        // Functions are left out of the JSON, they are picked up on their own.
+        let json = js_sys::JSON::stringify(options)
+            .ok()
+            .and_then(|json| json.as_string())
+            .ok_or("Options must be an object")?;
+        Ok(Options {
+            callbacks: Callbacks::read(options),
+            ..Options::from_json(&json)?
+        })
+    }
+
+    fn from_json(json: &str) -> Result<Options, String> {
+        let options: Options =
+            serde_json::from_str(json).map_err(|err| format!("Invalid options: {}", err))?;
+        if !options
+            .class_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err("The class prefix may only have letters, digits, - and _".to_string());
+        }
+        Ok(options)
+    }
+}
+
+// Functions of the host page told about the game.
+#[derive(Debug, Clone, Default)]
+pub struct Callbacks {
+    on_score: Option<js_sys::Function>,
+    on_game_over: Option<js_sys::Function>,
+}
+
+// What `onGameOver` is called with.
+#[derive(Serialize)]
+struct GameOver {
+    #[serde(flatten)]
+    final_state: FinalState,
+    won: bool,
+    #[serde(flatten)]
+    replay: Replay,
+}
+
+// A game as a leaderboard gets it, to be played again from its seed rather
+// than have its score taken on trust.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Replay {
+    // A string, JavaScript numbers can't hold every seed.
+    seed: String,
+    variant: Variant,
+    spawn: SpawnPolicy,
+    combo: bool,
+    actions: Vec<Action>,
+}
+
+impl Replay {
+    fn new(game_state: &GameState, variant: Variant) -> Replay {
+        Replay {
+            seed: game_state.seed().to_string(),
+            variant,
+            spawn: game_state.spawn_policy(),
+            combo: game_state.combo_scoring(),
+            actions: game_state.history().to_vec(),
+        }
+    }
+
+    fn verify(&self) -> Result<FinalState, String> {
+        let seed = self.seed.parse().map_err(|_| "Invalid seed".to_string())?;
+        game_state::verify_replay(self.variant, self.spawn, self.combo, seed, &self.actions)
+    }
+}
+
+// Plays the game `onGameOver` was called with again, see `verify_replay`.
+pub fn verify(game: &JsValue) -> Result<JsValue, String> {
+    let json = js_sys::JSON::stringify(game)
+        .ok()
+        .and_then(|json| json.as_string())
+        .ok_or("The game must be an object")?;
+    let replay: Replay =
+        serde_json::from_str(&json).map_err(|err| format!("Invalid game: {}", err))?;
+    let final_state = serde_json::to_string(&replay.verify()?).map_err(|err| err.to_string())?;
+    js_sys::JSON::parse(&final_state).map_err(|_| "Invalid result".to_string())
+}
+
+impl Callbacks {
+    fn read(options: &JsValue) -> Callbacks {
+        let function = |name: &str| {
+            js_sys::Reflect::get(options, &name.into())
+                .ok()?
+                .dyn_into::<js_sys::Function>()
+                .ok()
+        };
+        Callbacks {
+            on_score: function("onScore"),
+            on_game_over: function("onGameOver"),
+        }
+    }
+
+    // After every move that scored.
+    pub fn score(&self, score: usize) {
+        call(&self.on_score, &JsValue::from(score as f64));
+    }
+
+    // Once for every finished game.
+    pub fn game_over(&self, game_state: &GameState, variant: Variant) {
+        if self.on_game_over.is_none() {
+            return;
+        }
+        let game = GameOver {
+            final_state: game_state.final_state(),
+            won: game_state.is_won(),
+            replay: Replay::new(game_state, variant),
+        };
+        let game = serde_json::to_string(&game)
+            .ok()
+            .and_then(|json| js_sys::JSON::parse(&json).ok());
+        if let Some(game) = game {
+            call(&self.on_game_over, &game);
+        }
+    }
+}
+
+// A host's callback failing is the host's to fix, the game carries on.
+fn call(callback: &Option<js_sys::Function>, arg: &JsValue) {
+    if let Some(callback) = callback {
+        if let Err(err) = callback.call1(&JsValue::NULL, arg) {
+            log!("A callback of the host page failed:", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::embed::{Options, Replay};
+    use crate::evaluate::Weights;
+    use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+    use crate::mode::Mode;
+
+    #[test]
+    fn test_replay() {
+        let mut game_state = GameState::with_seed(Variant::Hex, SpawnPolicy::Classic, u64::MAX);
+        for direction in [Direction::Left, Direction::Right, Direction::UpLeft] {
+            game_state.move_tiles(direction);
+        }
+        let json = serde_json::to_string(&Replay::new(&game_state, Variant::Hex)).unwrap();
+        assert!(
+            json.contains("\"seed\":\"18446744073709551615\""),
+            "Replay: Seed As String"
+        );
+        let replay: Replay = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Ok(game_state.final_state()),
+            replay.verify(),
+            "Replay: Verified"
+        );
+    }
+
+    #[test]
+    fn test_options_from_json() {
+        // Capture keys, variant, mode, theme and read-only.
+        type Fields<'a> = (bool, Option<Variant>, Option<Mode>, Option<&'a str>, bool);
+
+        struct TestCase<'a> {
+            name: &'a str,
+            json: &'a str,
+            want: Option<Fields<'a>>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Options: Empty",
+                json: "{}",
+                want: Some((false, None, None, None, false)),
+            },
+            TestCase {
+                name: "Options: Everything",
+                json: "{\"captureKeys\":true,\"variant\":{\"Obstacles\":2},\"mode\":\"TimeAttack\",\"theme\":\"dark\",\"readOnly\":true}",
+                want: Some((
+                    true,
+                    Some(Variant::Obstacles(2)),
+                    Some(Mode::TimeAttack),
+                    Some("dark"),
+                    true,
+                )),
+            },
+            TestCase {
+                name: "Options: Unknown Ignored",
+                json: "{\"gridSize\":5,\"variant\":\"Hex\"}",
+                want: Some((false, Some(Variant::Hex), None, None, false)),
+            },
+            TestCase {
+                name: "Options: Unknown Mode",
+                json: "{\"mode\":\"Marathon\"}",
+                want: None,
+            },
+            TestCase {
+                name: "Options: Wrong Type",
+                json: "{\"captureKeys\":\"yes\"}",
+                want: None,
+            },
+        ];
+
+        for t in tests {
+            let options = Options::from_json(t.json).ok();
+            let got = options.as_ref().map(|options| {
+                (
+                    options.capture_keys,
+                    options.variant,
+                    options.mode,
+                    options.theme.as_deref(),
+                    options.read_only,
+                )
+            });
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_class_prefix() {
+        struct TestCase<'a> {
+            name: &'a str,
+            json: &'a str,
+            want: Option<&'a str>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Prefix: None",
+                json: "{}",
+                want: Some(""),
+            },
+            TestCase {
+                name: "Prefix: Set",
+                json: "{\"classPrefix\":\"my-2048_\"}",
+                want: Some("my-2048_"),
+            },
+            TestCase {
+                name: "Prefix: Two Classes",
+                json: "{\"classPrefix\":\"my 2048-\"}",
+                want: None,
+            },
+            TestCase {
+                name: "Prefix: Selector",
+                json: "{\"classPrefix\":\".my-\"}",
+                want: None,
+            },
+        ];
+
+        for t in tests {
+            let options = Options::from_json(t.json).ok();
+            let got = options
+                .as_ref()
+                .map(|options| options.class_prefix.as_str());
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_ai_weights() {
+        let options = Options::from_json("{\"aiWeights\":{\"cornerMax\":4.5}}").unwrap();
+        assert_eq!(
+            Some(Weights {
+                corner_max: 4.5,
+                ..Weights::default()
+            }),
+            options.ai_weights,
+            "AI Weights: Left Out Keep The Defaults"
+        );
+    }
+}
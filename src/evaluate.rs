@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{Direction, GameState};
+
+// What a board is judged by, each from the player's point of view and in
+// the exponents of the values, so a 2048 doesn't drown out everything else.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Features {
+    // Free cells leave room to move.
+    pub empty: f64,
+    // Zero for lines that only ever rise or fall, less the more they zigzag.
+    pub monotonicity: f64,
+    // Zero for equal neighbours, less the further apart their values are.
+    pub smoothness: f64,
+    // 1 with the best tile in a corner, where it's hardest to dislodge.
+    pub corner_max: f64,
+    // Equal neighbours are merges waiting to happen.
+    pub merges: f64,
+}
+
+// How much each feature counts, read from JSON with every field optional.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Weights {
+    pub empty: f64,
+    pub monotonicity: f64,
+    pub smoothness: f64,
+    pub corner_max: f64,
+    pub merges: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Weights {
+        Weights {
+            empty: 2.7,
+            monotonicity: 0.2,
+            smoothness: 0.1,
+            corner_max: 3.0,
+            merges: 1.0,
+        }
+    }
+}
+
+impl Weights {
+    pub fn apply(&self, features: &Features) -> f64 {
+        self.empty * features.empty
+            + self.monotonicity * features.monotonicity
+            + self.smoothness * features.smoothness
+            + self.corner_max * features.corner_max
+            + self.merges * features.merges
+    }
+}
+
+pub fn features(game_state: &GameState) -> Features {
+    let cells = game_state.cells();
+    let rank = |i: usize| {
+        cells[i]
+            .filter(|tile| tile.is_number())
+            .map(|tile| (tile.get_value() as f64).log2())
+    };
+
+    let mut monotonicity = 0.0;
+    let mut smoothness = 0.0;
+    let mut merges = 0;
+    // How many lines start on each cell, the most on the corners.
+    let mut starts = vec![0; cells.len()];
+
+    // Every line is seen once from each end, so everything is counted twice.
+    for direction in Direction::ALL {
+        let (mut rising, mut falling) = (0.0, 0.0);
+        for line in game_state.board().lines(direction) {
+            if let Some(&first) = line.first() {
+                starts[first] += 1;
+            }
+
+            // Tiles are neighbours across the empty cells between them.
+            let ranks: Vec<f64> = line.iter().filter_map(|&i| rank(i)).collect();
+            for pair in ranks.windows(2) {
+                let step = pair[1] - pair[0];
+                if step > 0.0 {
+                    rising += step;
+                } else {
+                    falling -= step;
+                }
+                smoothness -= step.abs();
+            }
+
+            merges += line
+                .windows(2)
+                .filter(|pair| match (cells[pair[0]], cells[pair[1]]) {
+                    (Some(a), Some(b)) => a.is_number() && a == b,
+                    _ => false,
+                })
+                .count();
+        }
+        monotonicity -= f64::min(rising, falling);
+    }
+
+    let corner = starts.iter().copied().max().unwrap_or(0);
+    let best = (0..cells.len())
+        .filter_map(|i| Some((i, rank(i)?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    let corner_max = match best {
+        Some((i, _)) if corner > 0 && starts[i] == corner => 1.0,
+        _ => 0.0,
+    };
+
+    Features {
+        empty: game_state.empty_count() as f64,
+        monotonicity: monotonicity / 2.0,
+        smoothness: smoothness / 2.0,
+        corner_max,
+        merges: merges as f64 / 2.0,
+    }
+}
+
+// Scores a board from the player's point of view, higher is better.
+pub fn evaluate(game_state: &GameState, weights: &Weights) -> f64 {
+    weights.apply(&features(game_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluate::{evaluate, features, Features, Weights};
+    use crate::game_state::GameState;
+
+    #[test]
+    fn test_features() {
+        struct TestCase<'a> {
+            name: &'a str,
+            values: [usize; 16],
+            want: Features,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Features: Empty",
+                values: [0; 16],
+                want: Features {
+                    empty: 16.0,
+                    ..Features::default()
+                },
+            },
+            TestCase {
+                name: "Features: Snake In The Corner",
+                values: [8, 4, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: Features {
+                    empty: 13.0,
+                    monotonicity: 0.0,
+                    smoothness: -2.0,
+                    corner_max: 1.0,
+                    merges: 0.0,
+                },
+            },
+            TestCase {
+                name: "Features: Zigzag In The Middle",
+                values: [0, 0, 0, 0, 2, 8, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: Features {
+                    empty: 12.0,
+                    monotonicity: -2.0,
+                    smoothness: -4.0,
+                    corner_max: 0.0,
+                    merges: 1.0,
+                },
+            },
+        ];
+
+        for t in tests {
+            let gs = GameState::from_values(&t.values);
+            assert_eq!(t.want, features(&gs), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let only_empty = Weights {
+            empty: 1.0,
+            monotonicity: 0.0,
+            smoothness: 0.0,
+            corner_max: 0.0,
+            merges: 0.0,
+        };
+        let gs = GameState::from_values(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(14.0, evaluate(&gs, &only_empty), "Evaluate: Weighted");
+
+        let weights: Weights = serde_json::from_str(r#"{"merges": 0}"#).unwrap();
+        assert_eq!(
+            Weights {
+                merges: 0.0,
+                ..Weights::default()
+            },
+            weights,
+            "Evaluate: Weights From JSON"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::game_state::{Direction, GameState};
+
+// Plays a board and moves made up from arbitrary bytes, checking the board
+// after every move, see `GameState::validate`. The bytes after the board are
+// moves, the high bit playing them the way the page does rather than as a
+// batch.
+pub fn check(bytes: &[u8]) -> Result<(), String> {
+    let (mut game_state, moves) = GameState::from_bytes_lossy(bytes);
+    game_state
+        .validate()
+        .map_err(|err| format!("Made up a broken board: {}\n{}", err, game_state))?;
+
+    for &byte in moves {
+        let direction = Direction::ALL[usize::from(byte & 0x7f) % Direction::ALL.len()];
+        if byte & 0x80 != 0 {
+            game_state.move_tiles(direction);
+            game_state.drain_events();
+        } else {
+            game_state.apply_moves(&[direction]);
+        }
+        game_state.validate().map_err(|err| {
+            format!(
+                "Moving {:?} broke the board: {}\n{}",
+                direction, err, game_state
+            )
+        })?;
+    }
+    Ok(())
+}
+
+// For the target in fuzz/, which only hears of panics.
+pub fn run(bytes: &[u8]) {
+    if let Err(err) = check(bytes) {
+        panic!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fuzz::check;
+
+    #[test]
+    fn test_check() {
+        struct TestCase<'a> {
+            name: &'a str,
+            bytes: &'a [u8],
+        }
+
+        let tests = [
+            TestCase {
+                name: "Fuzz: Nothing",
+                bytes: &[],
+            },
+            TestCase {
+                name: "Fuzz: Classic",
+                bytes: &[
+                    0, 7, 1, 1, 2, 0, 0, 0, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0x82, 1, 3,
+                ],
+            },
+            TestCase {
+                name: "Fuzz: Wrapping Bombs",
+                bytes: &[
+                    0x49, 1, 0xe1, 1, 1, 0xf0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x81, 2,
+                ],
+            },
+            TestCase {
+                name: "Fuzz: Triples With Gravity",
+                bytes: &[
+                    0x86, 3, 1, 1, 1, 2, 2, 2, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0x80, 2, 0x81, 3,
+                ],
+            },
+            TestCase {
+                name: "Fuzz: Triples Past The Largest Tile",
+                bytes: &[6, 0, 8, 8, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(Ok(()), check(t.bytes), "{}", t.name);
+        }
+    }
+}
@@ -1,14 +1,34 @@
-use rand::prelude::IteratorRandom;
+use rand::prelude::{IteratorRandom, SliceRandom};
 use rand::thread_rng;
-use rand::Rng;
+use rand::{Rng, RngCore};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ai;
+use crate::evaluate::Weights;
+use crate::rng::GameRng;
+use crate::topology::Board;
+use crate::transposition::TranspositionTable;
 
 #[derive(Debug, Copy, Clone, Eq, Deserialize, Serialize)]
 pub struct Tile {
-    value: usize,
+    // The value packed into a byte, see `to_exp`. Saved as the plain value.
+    #[serde(rename = "value", with = "tile_value")]
+    exp: u8,
     state: TileState,
     prev_pos: Option<usize>,
+    #[serde(default)]
+    kind: TileKind,
+    // Moves left until the tile thaws; frozen tiles neither move nor merge.
+    #[serde(default)]
+    frozen: usize,
+    // Stays with the tile while it slides, so it can be drawn by the same element.
+    #[serde(default)]
+    id: u32,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
@@ -18,342 +38,4183 @@ enum TileState {
     Merged,
 }
 
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+enum TileKind {
+    #[default]
+    Number,
+    // Never moves or merges; tiles slide up against it like a wall.
+    Obstacle,
+    // Slides like a number, goes off when merged against a number tile.
+    Bomb,
+    // Slides like a number, doubles the number tile it merges with.
+    Multiplier,
+}
+
+// Every rule set only ever multiplies values by 2 or 3, so a value is stored as
+// its exponents: the exponent of two in the low five bits and the exponent of
+// three in the high three bits. All bits set stands for 0, the value of
+// special tiles.
+const ZERO_EXP: u8 = u8::MAX;
+
+fn to_exp(value: usize) -> Option<u8> {
+    if value == 0 {
+        return Some(ZERO_EXP);
+    }
+
+    let twos = value.trailing_zeros();
+    let mut rest = value >> twos;
+    let mut threes = 0;
+    while rest.is_multiple_of(3) {
+        rest /= 3;
+        threes += 1;
+    }
+
+    (rest == 1 && twos < 31 && threes < 8).then_some((threes << 5 | twos) as u8)
+}
+
+fn from_exp(exp: u8) -> usize {
+    if exp == ZERO_EXP {
+        0
+    } else {
+        (1 << (exp & 0x1f)) * 3usize.pow(u32::from(exp >> 5))
+    }
+}
+
+// Reads and writes tile values the way they were saved before they were packed.
+mod tile_value {
+    use serde::de::Error;
+
+    use super::{from_exp, to_exp, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(exp: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        from_exp(*exp).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let value = usize::deserialize(deserializer)?;
+        to_exp(value).ok_or_else(|| D::Error::custom(format!("{} isn't a tile value", value)))
+    }
+}
+
+// Numbers the tiles without an id, e.g. from saves made before tiles had one,
+// after the others. Returns the highest id.
+fn number_tiles(grid: &mut [Option<Tile>]) -> u32 {
+    let mut id = grid.iter().flatten().map(|tile| tile.id).max().unwrap_or(0);
+    for tile in grid.iter_mut().flatten().filter(|tile| tile.id == 0) {
+        id += 1;
+        tile.id = id;
+    }
+    id
+}
+
+fn numbered_grid<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<Tile>>, D::Error> {
+    let mut grid = Vec::deserialize(deserializer)?;
+    number_tiles(&mut grid);
+    Ok(grid)
+}
+
 impl Tile {
     fn new(value: usize) -> Tile {
         Tile {
-            value,
+            exp: to_exp(value).expect("tile values are made of twos and threes"),
             state: TileState::New,
             prev_pos: None,
+            kind: TileKind::Number,
+            frozen: 0,
+            id: 0,
+        }
+    }
+
+    fn obstacle() -> Tile {
+        Tile {
+            kind: TileKind::Obstacle,
+            ..Tile::new(0)
+        }
+    }
+
+    fn bomb() -> Tile {
+        Tile {
+            kind: TileKind::Bomb,
+            ..Tile::new(0)
+        }
+    }
+
+    fn multiplier() -> Tile {
+        Tile {
+            kind: TileKind::Multiplier,
+            ..Tile::new(0)
         }
     }
 
     fn update(&mut self, value: usize, state: TileState) {
-        self.value = value;
+        self.exp = Tile::new(value).exp;
         self.state = state;
     }
 
     pub fn get_value(&self) -> usize {
-        self.value
+        from_exp(self.exp)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn is_obstacle(&self) -> bool {
+        self.kind == TileKind::Obstacle
+    }
+
+    pub fn is_bomb(&self) -> bool {
+        self.kind == TileKind::Bomb
+    }
+
+    pub fn is_multiplier(&self) -> bool {
+        self.kind == TileKind::Multiplier
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.kind == TileKind::Number
+    }
+
+    pub fn frozen_moves(&self) -> usize {
+        self.frozen
+    }
+
+    // Tiles that stay put and split their line like a wall.
+    fn is_fixed(&self) -> bool {
+        self.is_obstacle() || self.frozen > 0
+    }
+}
+
+impl PartialEq for Tile {
+    fn eq(&self, other: &Tile) -> bool {
+        self.exp == other.exp && self.kind == other.kind
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+    // Only used on hex boards.
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+    // Only used on cube boards, towards the first and the last layer.
+    Above,
+    Below,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 10] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+        Direction::Above,
+        Direction::Below,
+    ];
+
+    // Start index, step along a line and step to the next line on the 4x4 grid.
+    pub fn increment(self) -> Option<(i32, i32, i32)> {
+        match self {
+            Direction::Left => Some((0, 1, 0)),
+            Direction::Right => Some((15, -1, 0)),
+            Direction::Up => Some((0, 4, 1)),
+            Direction::Down => Some((15, -4, -1)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Variant {
+    #[default]
+    Classic,
+    // The given number of obstacles is placed at random when the game starts.
+    Obstacles(usize),
+    Threes,
+    Hex,
+    Cube,
+    Triples,
+    // Some spawned tiles are frozen for a few moves.
+    Frozen,
+    // Bombs spawn now and then and clear their lines when set off.
+    Bombs,
+    // x2 tiles spawn now and then and double the number merged into them.
+    Multipliers,
+    // Tiles leaving one edge come back in at the opposite one.
+    Torus,
+    // Tiles fall down after every horizontal move.
+    Gravity,
+}
+
+// Special tiles that can spawn instead of a plain number.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+enum SpecialTiles {
+    #[default]
+    None,
+    Frozen,
+    Bombs,
+    Multipliers,
+}
+
+// How the cells of a line are walked when its tiles slide.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+enum Traversal {
+    #[default]
+    Edges,
+    // Lines are rings. Tiles pack up behind the first gap from the leading
+    // edge backwards, so they can slide off one edge and merge across it.
+    Wrap,
+}
+
+impl Traversal {
+    fn walk<'a>(self, line: &'a [usize], grid: &[Option<Tile>]) -> Cow<'a, [usize]> {
+        match self {
+            Traversal::Edges => Cow::Borrowed(line),
+            Traversal::Wrap => {
+                let len = line.len();
+                // Full rings have no gap to pack behind and slide as usual.
+                let start = (0..len)
+                    .find(|&i| grid[line[(i + len - 1) % len]].is_none())
+                    .unwrap_or(0);
+
+                Cow::Owned(
+                    line[start..]
+                        .iter()
+                        .chain(&line[..start])
+                        .copied()
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+// Decides which tiles merge and what they merge into.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum MergeRule {
+    #[default]
+    Classic,
+    // 1 and 2 merge into 3, after that only equal tiles merge.
+    Threes,
+    // Exactly three equal tiles merge into one of triple value.
+    Triples,
+}
+
+impl MergeRule {
+    // How many tiles in a row merge at once.
+    fn arity(self) -> usize {
+        match self {
+            MergeRule::Triples => 3,
+            _ => 2,
+        }
+    }
+
+    // Merges `arity()` values in line order.
+    fn merge(self, values: &[usize]) -> Option<usize> {
+        match (self, values) {
+            (MergeRule::Classic, &[a, b]) if a == b => Some(a + b),
+            (MergeRule::Threes, &[1, 2] | &[2, 1]) => Some(3),
+            (MergeRule::Threes, &[a, b]) if a == b && a >= 3 => Some(a + b),
+            (MergeRule::Triples, &[a, b, c]) if a == b && b == c => Some(a * 3),
+            _ => None,
+        }
+    }
+
+    // Whether merging the tiles the rules spawn can make the value.
+    fn makes(self, value: usize) -> bool {
+        match self {
+            MergeRule::Classic => value >= 2 && value.is_power_of_two(),
+            MergeRule::Threes => {
+                value == 1 || value == 2 || value.is_multiple_of(3) && (value / 3).is_power_of_two()
+            }
+            MergeRule::Triples => {
+                let mut rest = value;
+                while rest > 1 && rest.is_multiple_of(3) {
+                    rest /= 3;
+                }
+                rest == 1
+            }
+        }
+    }
+
+    fn target(self) -> usize {
+        match self {
+            MergeRule::Classic => 2048,
+            MergeRule::Threes => 768,
+            MergeRule::Triples => 2187,
+        }
+    }
+}
+
+// The classic winning tile, for games and settings saved before it could be changed.
+pub fn default_target() -> Option<usize> {
+    Some(MergeRule::Classic.target())
+}
+
+// Decides which values new tiles get and how likely each one is.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum SpawnPolicy {
+    #[default]
+    Classic,
+    OnlyTwos,
+    TwoFourEight,
+    // The chance of a 4 grows from 10% to 50% as the score reaches 20000.
+    Curve,
+    Threes,
+    Triples,
+    // Every tile is placed where it hurts the player most.
+    Evil,
+}
+
+impl SpawnPolicy {
+    // Each value that can spawn with its probability at the given score.
+    pub fn odds(self, score: usize) -> Vec<(usize, f64)> {
+        match self {
+            SpawnPolicy::Classic => vec![(2, 0.9), (4, 0.1)],
+            SpawnPolicy::OnlyTwos => vec![(2, 1.0)],
+            SpawnPolicy::TwoFourEight => vec![(2, 0.8), (4, 0.15), (8, 0.05)],
+            SpawnPolicy::Curve => {
+                let four = 0.1 + 0.4 * (score as f64 / 20000.0).min(1.0);
+                vec![(2, 1.0 - four), (4, four)]
+            }
+            SpawnPolicy::Threes => vec![(1, 0.4), (2, 0.4), (3, 0.2)],
+            SpawnPolicy::Triples => vec![(1, 0.9), (3, 0.1)],
+            // Only the values matter here, the worst of them is always picked.
+            SpawnPolicy::Evil => SpawnPolicy::Classic.odds(score),
+        }
+    }
+
+    fn spawn_value(self, score: usize, rng: &mut impl Rng) -> usize {
+        let odds = self.odds(score);
+
+        let mut x = rng.gen::<f64>();
+        for &(value, chance) in &odds {
+            if x < chance {
+                return value;
+            }
+            x -= chance;
+        }
+
+        // Only reached through rounding errors.
+        odds.last().map_or(2, |&(value, _)| value)
+    }
+}
+
+// Consumables earned by merging big tiles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PowerUp {
+    Remove,
+    Swap,
+    Double,
+}
+
+impl PowerUp {
+    // How many tiles have to be picked to use it.
+    pub fn targets(self) -> usize {
+        match self {
+            PowerUp::Swap => 2,
+            _ => 1,
+        }
+    }
+
+    fn earned_by(value: usize) -> Option<PowerUp> {
+        match value {
+            128 => Some(PowerUp::Remove),
+            256 => Some(PowerUp::Swap),
+            512 => Some(PowerUp::Double),
+            _ => None,
+        }
+    }
+}
+
+// Everything that changed the board, in order, so a game can be replayed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Action {
+    Move(Direction),
+    PowerUp(PowerUp, Vec<usize>),
+    Shuffle,
+}
+
+// An action of the history and what it earned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    pub score_gained: usize,
+    // Power-ups and shuffles never merge anything.
+    pub merges: usize,
+    // The cell and value of the tile that landed after a move, if one did.
+    pub spawned: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderKind {
+    // Spawned after the last move.
+    New,
+    // Slid to its cell in the last move, or stayed where it was.
+    Moved,
+    Merged,
+    // One of the tiles a merged tile was made of, under the merged tile.
+    Under,
+}
+
+// A tile as it is drawn: at `pos`, coming from `prev`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RenderTile {
+    pub pos: usize,
+    pub prev: Option<usize>,
+    pub kind: RenderKind,
+    pub tile: Tile,
+}
+
+// A merge made by a move: the cell the merged tile ended up in and its value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub index: usize,
+    pub value: usize,
+}
+
+// What a single move did to the board.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveResult {
+    pub moved: bool,
+    // Only set for moves that changed the board.
+    pub direction: Option<Direction>,
+    // Includes the combo bonus and the points of any explosions.
+    pub score_gained: usize,
+    pub merges: Vec<Merge>,
+    // Cell and value of the tile spawned after the move.
+    pub spawned: Option<(usize, usize)>,
+}
+
+// What a batch of moves did to the board, see `GameState::apply_moves`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct BatchResult {
+    // The moves that changed the board, up to the first one that didn't.
+    pub applied: usize,
+    pub score_gained: usize,
+    pub merges: usize,
+    // Whether the batch reached the target.
+    pub won: bool,
+    // No direction moves anything anymore.
+    pub game_over: bool,
+}
+
+// Something that happened in a move, queued until the caller drains them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    TileSpawned { index: usize, value: usize },
+    TilesMerged { index: usize, value: usize },
+    ScoreChanged { score: usize },
+    GameWon,
+    // No direction moves anything anymore.
+    GameOver,
+}
+
+// Merges of a single move, scored once the move is done.
+#[derive(Default)]
+struct MoveTally {
+    merges: Vec<Merge>,
+    bombs: Vec<usize>,
+    // Whether the move keeps what drawing it needs, see `prepare_move`.
+    tracked: bool,
+}
+
+const SHUFFLES: usize = 2;
+
+// Chance of a spawned tile being frozen in frozen games, and for how many moves.
+const FROZEN_CHANCE: f64 = 0.1;
+const FROZEN_MOVES: usize = 3;
+
+// Chance of a bomb or x2 tile spawning instead of a number in their games.
+const BOMB_CHANCE: f64 = 0.05;
+const MULTIPLIER_CHANCE: f64 = 0.05;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameState {
+    #[serde(deserialize_with = "numbered_grid")]
+    grid: Vec<Option<Tile>>,
+    score: usize,
+    over: bool,
+    won: bool,
+    generate_tiles: bool,
+    #[serde(default)]
+    rules: MergeRule,
+    // The tile that wins the game, none to play on forever.
+    #[serde(default = "default_target")]
+    target: Option<usize>,
+    // Moves left before the game is over, none for no limit. Only moves that
+    // change the board use one up.
+    #[serde(default)]
+    moves_left: Option<usize>,
+    // The score that wins the game, none to only win by the target tile.
+    #[serde(default)]
+    score_target: Option<usize>,
+    #[serde(default)]
+    board: Board,
+    #[serde(default)]
+    spawn: SpawnPolicy,
+    #[serde(default)]
+    power_ups: Vec<PowerUp>,
+    #[serde(default)]
+    history: Vec<Action>,
+    // The game as it was before the first action of `history`.
+    #[serde(default)]
+    start: Option<Start>,
+    #[serde(default)]
+    shuffles_used: usize,
+    #[serde(default)]
+    special: SpecialTiles,
+    #[serde(default)]
+    traversal: Traversal,
+    #[serde(default)]
+    gravity: bool,
+    // Moves in a row that merged something; only scored with `combo_scoring`.
+    #[serde(default)]
+    combo: usize,
+    #[serde(default)]
+    combo_scoring: bool,
+    // Cells cleared by bombs in the last move, kept for the explosion animation.
+    #[serde(default)]
+    exploded: Vec<usize>,
+    // The tiles merged in the last move and the cell they merged in, kept to
+    // draw them sliding in under the merged tile.
+    #[serde(default)]
+    merged_from: Vec<(usize, Tile)>,
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    // The last id given to a tile.
+    #[serde(default)]
+    last_id: u32,
+    // Every random choice of the game comes from `rng`, which starts at `seed`.
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    rng: GameRng,
+}
+
+// What a replay needs to set the game back to before its first action; the
+// rest of the game state doesn't change while playing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Start {
+    grid: Vec<Option<Tile>>,
+    score: usize,
+    power_ups: Vec<PowerUp>,
+    shuffles_used: usize,
+    combo: usize,
+    rng: GameRng,
+    #[serde(default)]
+    moves_left: Option<usize>,
+}
+
+// Puts together a game state without going through a variant. Boards come in
+// fixed shapes, so the board is picked and a size only checked against it.
+#[derive(Debug, Clone)]
+pub struct GameStateBuilder {
+    board: Board,
+    size: Option<usize>,
+    seed: u64,
+    rules: MergeRule,
+    spawn: SpawnPolicy,
+    // None for the rules' own target.
+    target: Option<Option<usize>>,
+    tiles: Vec<(usize, usize)>,
+    generate_tiles: bool,
+}
+
+impl GameStateBuilder {
+    pub fn board(mut self, board: Board) -> GameStateBuilder {
+        self.board = board;
+        self
+    }
+
+    // Cells along each side, which has to be the board's.
+    pub fn size(mut self, size: usize) -> GameStateBuilder {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> GameStateBuilder {
+        self.seed = seed;
+        self
+    }
+
+    pub fn rules(mut self, rules: MergeRule) -> GameStateBuilder {
+        self.rules = rules;
+        self
+    }
+
+    pub fn spawn_policy(mut self, spawn: SpawnPolicy) -> GameStateBuilder {
+        self.spawn = spawn;
+        self
+    }
+
+    // None plays on without ever winning.
+    pub fn target(mut self, target: Option<usize>) -> GameStateBuilder {
+        self.target = Some(target);
+        self
+    }
+
+    // Cells and values of the starting tiles.
+    pub fn with_tiles(mut self, tiles: &[(usize, usize)]) -> GameStateBuilder {
+        self.tiles.extend_from_slice(tiles);
+        self
+    }
+
+    pub fn generate_tiles(mut self, generate_tiles: bool) -> GameStateBuilder {
+        self.generate_tiles = generate_tiles;
+        self
+    }
+
+    // No tiles are spawned here, callers add their own starting tiles. Fails
+    // for a size the board doesn't come in, tiles outside the board, and
+    // values and targets the rules can't make.
+    pub fn build(self) -> Result<GameState, String> {
+        let topology = self.board.topology();
+        if let Some(size) = self.size.filter(|&size| size != topology.size()) {
+            return Err(format!(
+                "{:?} boards have {} cells a side, not {}",
+                self.board,
+                topology.size(),
+                size
+            ));
+        }
+
+        let mut grid = vec![None; topology.cell_count()];
+        for &(index, value) in &self.tiles {
+            let cell = grid
+                .get_mut(index)
+                .ok_or_else(|| format!("Cell {} is outside the {:?} board", index, self.board))?;
+            if !self.rules.makes(value) || to_exp(value).is_none() {
+                return Err(format!("{:?} rules can't make {}", self.rules, value));
+            }
+            *cell = Some(Tile::new(value));
+        }
+        let target = self.target.unwrap_or(Some(self.rules.target()));
+        if let Some(target) = target.filter(|&target| !self.rules.makes(target)) {
+            return Err(format!(
+                "{:?} rules can't make the target {}",
+                self.rules, target
+            ));
+        }
+
+        let mut game_state = GameState::new(grid, self.generate_tiles);
+        game_state.board = self.board;
+        game_state.rules = self.rules;
+        game_state.seed = self.seed;
+        game_state.rng = GameRng::new(self.seed);
+        game_state.spawn = self.spawn;
+        game_state.target = target;
+        Ok(game_state)
+    }
+}
+
+impl GameState {
+    pub fn builder() -> GameStateBuilder {
+        GameStateBuilder {
+            board: Board::Square,
+            size: None,
+            seed: 0,
+            rules: MergeRule::Classic,
+            spawn: SpawnPolicy::Classic,
+            target: None,
+            tiles: Vec::new(),
+            generate_tiles: true,
+        }
+    }
+
+    fn new(mut grid: Vec<Option<Tile>>, generate_tiles: bool) -> GameState {
+        let last_id = number_tiles(&mut grid);
+        GameState {
+            grid,
+            score: 0,
+            over: false,
+            won: false,
+            generate_tiles,
+            rules: MergeRule::Classic,
+            target: default_target(),
+            moves_left: None,
+            score_target: None,
+            board: Board::Square,
+            spawn: SpawnPolicy::Classic,
+            power_ups: Vec::new(),
+            history: Vec::new(),
+            start: None,
+            shuffles_used: 0,
+            special: SpecialTiles::None,
+            traversal: Traversal::Edges,
+            gravity: false,
+            combo: 0,
+            combo_scoring: false,
+            exploded: Vec::new(),
+            merged_from: Vec::new(),
+            events: Vec::new(),
+            last_id,
+            seed: 0,
+            rng: GameRng::new(0),
+        }
+    }
+
+    // A classic board without spawns, with 0 for empty cells.
+    pub fn from_values(values: &[usize]) -> GameState {
+        let tiles: Vec<(usize, usize)> = values
+            .iter()
+            .enumerate()
+            .filter(|&(_, &value)| value != 0)
+            .map(|(i, &value)| (i, value))
+            .collect();
+
+        GameState::builder()
+            .with_tiles(&tiles)
+            .generate_tiles(false)
+            .build()
+            .expect("values of a square board")
+    }
+
+    // Makes up a game from arbitrary bytes, e.g. a fuzzer's, always one that
+    // passes `validate`. The first byte picks the board, rules and special
+    // tiles, the second the seed, then one byte per cell; returns what's
+    // left over. Missing bytes are zeros, empty cells.
+    pub fn from_bytes_lossy(bytes: &[u8]) -> (GameState, &[u8]) {
+        let byte = |i: usize| bytes.get(i).copied().unwrap_or(0);
+        let config = byte(0);
+        // The low six bits pick one of the combinations, the high two wrapping and gravity.
+        let choice = usize::from(config & 0x3f);
+        let board = [Board::Square, Board::Hex, Board::Cube][choice % 3];
+        let rules = [MergeRule::Classic, MergeRule::Threes, MergeRule::Triples][choice / 3 % 3];
+        // Special tiles only merge the way the classic rules expect.
+        let special = match choice / 9 % 4 {
+            1 if rules == MergeRule::Classic => SpecialTiles::Bombs,
+            2 if rules == MergeRule::Classic => SpecialTiles::Multipliers,
+            3 => SpecialTiles::Frozen,
+            _ => SpecialTiles::None,
+        };
+
+        let cell_count = board.topology().cell_count();
+        let grid = (0..cell_count)
+            .map(|i| {
+                let cell = byte(2 + i);
+                let n = u32::from(cell & 0x0f);
+                let value = match rules {
+                    _ if n == 0 => return None,
+                    MergeRule::Classic => 1 << n,
+                    MergeRule::Threes if n < 3 => n as usize,
+                    MergeRule::Threes => 3 << (n - 3),
+                    MergeRule::Triples => 3usize.pow(n.min(8) - 1),
+                };
+                Some(match cell >> 4 {
+                    0xf => Tile::obstacle(),
+                    0xe if special == SpecialTiles::Bombs => Tile::bomb(),
+                    0xd if special == SpecialTiles::Multipliers => Tile::multiplier(),
+                    _ => Tile::new(value),
+                })
+            })
+            .collect();
+
+        let seed = u64::from(byte(1));
+        let mut game_state = GameState::new(grid, true);
+        game_state.board = board;
+        game_state.rules = rules;
+        game_state.spawn = match rules {
+            MergeRule::Classic => SpawnPolicy::Classic,
+            MergeRule::Threes => SpawnPolicy::Threes,
+            MergeRule::Triples => SpawnPolicy::Triples,
+        };
+        game_state.special = special;
+        // Playing on past the target leaves the moves more to do.
+        game_state.target = None;
+        if config & 0x40 != 0 {
+            game_state.traversal = Traversal::Wrap;
+        }
+        game_state.gravity = config & 0x80 != 0;
+        game_state.seed = seed;
+        game_state.rng = GameRng::new(seed);
+
+        (game_state, bytes.get(2 + cell_count..).unwrap_or(&[]))
+    }
+
+    // Threes and Triples always spawn their own tiles, whatever `spawn` is.
+    pub fn with_variant(variant: Variant, spawn: SpawnPolicy) -> GameState {
+        GameState::with_seed(variant, spawn, thread_rng().gen())
+    }
+
+    // Games of the same variant and seed spawn the same tiles for the same moves.
+    pub fn with_seed(variant: Variant, spawn: SpawnPolicy, seed: u64) -> GameState {
+        let board = match variant {
+            Variant::Hex => Board::Hex,
+            Variant::Cube => Board::Cube,
+            _ => Board::Square,
+        };
+
+        let (rules, spawn) = match variant {
+            Variant::Threes => (MergeRule::Threes, SpawnPolicy::Threes),
+            Variant::Triples => (MergeRule::Triples, SpawnPolicy::Triples),
+            _ => (MergeRule::Classic, spawn),
+        };
+
+        let mut game_state = GameState::builder()
+            .board(board)
+            .seed(seed)
+            .rules(rules)
+            .spawn_policy(spawn)
+            .build()
+            .expect("rules make their own target");
+        if variant == Variant::Torus {
+            game_state.traversal = Traversal::Wrap;
+        }
+        game_state.gravity = variant == Variant::Gravity;
+
+        game_state.special = match variant {
+            Variant::Frozen => SpecialTiles::Frozen,
+            Variant::Bombs => SpecialTiles::Bombs,
+            Variant::Multipliers => SpecialTiles::Multipliers,
+            _ => SpecialTiles::None,
+        };
+
+        if let Variant::Obstacles(count) = variant {
+            for _ in 0..count {
+                game_state.add_obstacle();
+            }
+        }
+
+        for _ in 0..2 {
+            game_state.add_random_tile();
+        }
+        game_state
+    }
+
+    fn is_game_over(&mut self) -> bool {
+        self.over || self.won
+    }
+
+    // Returns the cell and value of the spawned tile, if any.
+    pub fn add_random_tile(&mut self) -> Option<(usize, usize)> {
+        let mut rng = self.rng;
+        let spawned = self.add_random_tile_with(&mut rng);
+        self.rng = rng;
+        spawned
     }
 
-    pub fn get_prev(&self) -> Option<usize> {
-        self.prev_pos
+    // Spawns a tile with the random choices drawn from `rng` instead of the
+    // game's own generator, e.g. a fixed sequence in tests.
+    pub fn add_random_tile_with(&mut self, rng: &mut impl Rng) -> Option<(usize, usize)> {
+        if !self.generate_tiles {
+            return None;
+        }
+
+        let spawn = match self.spawn {
+            SpawnPolicy::Evil => self.worst_spawn(),
+            _ => self.random_spawn(rng),
+        };
+
+        spawn.map(|(index, value)| {
+            let mut tile = Tile::new(value);
+            match self.special {
+                SpecialTiles::Frozen if rng.gen::<f64>() < FROZEN_CHANCE => {
+                    tile.frozen = FROZEN_MOVES;
+                }
+                SpecialTiles::Bombs if rng.gen::<f64>() < BOMB_CHANCE => {
+                    tile = Tile::bomb();
+                }
+                SpecialTiles::Multipliers if rng.gen::<f64>() < MULTIPLIER_CHANCE => {
+                    tile = Tile::multiplier();
+                }
+                _ => (),
+            }
+            tile.id = self.new_tile_id();
+            self.grid[index] = Some(tile);
+            (index, tile.get_value())
+        })
+    }
+
+    // Where the next tile could spawn if it spawned on the board as it is, as
+    // (cell, chance, expected value). Bombs and x2 tiles are left out.
+    pub fn spawn_odds(&self) -> Vec<(usize, f64, f64)> {
+        if !self.generate_tiles {
+            return Vec::new();
+        }
+        if self.spawn == SpawnPolicy::Evil {
+            return self
+                .worst_spawn()
+                .map(|(index, value)| (index, 1.0, value as f64))
+                .into_iter()
+                .collect();
+        }
+
+        let expected = self
+            .spawn
+            .odds(self.score)
+            .iter()
+            .map(|&(value, chance)| value as f64 * chance)
+            .sum();
+        let empty: Vec<usize> = self.empty_cells().collect();
+        let chance = 1.0 / empty.len() as f64;
+        empty
+            .into_iter()
+            .map(|index| (index, chance, expected))
+            .collect()
+    }
+
+    fn empty_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.is_none())
+            .map(|(i, _)| i)
+    }
+
+    // Cell and value of the next tile, drawn from the spawn policy.
+    fn random_spawn(&self, rng: &mut impl Rng) -> Option<(usize, usize)> {
+        let value = self.spawn.spawn_value(self.score, rng);
+        let empty: Vec<usize> = self.empty_cells().collect();
+
+        empty.choose(rng).map(|&i| (i, value))
+    }
+
+    // The tile a minimax search picks, looking `ai::EVIL_PLIES` moves past it.
+    // The search is small enough that a table of its own is cheap.
+    fn worst_spawn(&self) -> Option<(usize, usize)> {
+        let mut table = TranspositionTable::new(1 << 12);
+        ai::minimax_spawn(self, ai::EVIL_PLIES, &Weights::default(), &mut table)
+    }
+
+    fn add_obstacle(&mut self) {
+        let id = self.new_tile_id();
+        let grid_empty = self.grid.iter_mut().filter(|tile| tile.is_none());
+
+        if let Some(empty) = grid_empty.choose(&mut self.rng) {
+            *empty = Some(Tile {
+                id,
+                ..Tile::obstacle()
+            });
+        }
+    }
+
+    // Ids aren't reused, or a new tile could take over the element of one
+    // that was just merged away.
+    fn new_tile_id(&mut self) -> u32 {
+        let max = self.grid.iter().flatten().map(|tile| tile.id).max();
+        self.last_id = self.last_id.max(max.unwrap_or(0)) + 1;
+        self.last_id
+    }
+
+    // Whether moving in the direction would change the board, read off the
+    // board as it is.
+    pub fn can_move(&self, direction: Direction) -> bool {
+        if self.over || self.won {
+            return false;
+        }
+
+        let slides = self.board.lines(direction).iter().any(|line| {
+            let line = self.traversal.walk(line, &self.grid);
+            self.line_moves(&line, true)
+        });
+        // Gravity only pulls tiles down that rest on an empty cell.
+        slides
+            || self.gravity
+                && matches!(direction, Direction::Left | Direction::Right)
+                && self
+                    .board
+                    .lines(Direction::Down)
+                    .iter()
+                    .any(|line| self.line_moves(line, false))
+    }
+
+    // Whether sliding the line towards its first cell would move a tile or,
+    // with `merges`, merge any, the way `slide_and_merge_line` would.
+    fn line_moves(&self, line: &[usize], merges: bool) -> bool {
+        let arity = self.rules.arity();
+        // The segment the tiles slide in and the last two tiles packed in it.
+        let mut start = 0;
+        let mut packed = 0;
+        let mut recent = [None; 2];
+
+        for (i, &index) in line.iter().enumerate() {
+            let tile = match self.grid[index] {
+                Some(tile) => tile,
+                None => continue,
+            };
+            if tile.is_fixed() {
+                start = i + 1;
+                packed = 0;
+                recent = [None; 2];
+                continue;
+            }
+            if i != start + packed {
+                return true;
+            }
+
+            // Nothing merged before it, so the tile merges with the tiles
+            // packed right before it if any merge does.
+            if merges && packed + 1 >= arity {
+                let mut group = [tile; 3];
+                for (slot, placed) in group.iter_mut().zip(recent[3 - arity..].iter().flatten()) {
+                    *slot = *placed;
+                }
+                if self.merge_tiles(&group[..arity]).is_some() {
+                    return true;
+                }
+            }
+            recent = [recent[1], Some(tile)];
+            packed += 1;
+        }
+
+        false
+    }
+
+    // Where the tiles would land, without the tile spawned after the move.
+    // None if the move changes nothing.
+    pub fn preview_move(&self, direction: Direction) -> Option<GameState> {
+        let mut next = self.clone();
+        next.generate_tiles = false;
+        next.events.clear();
+        next.apply_move(direction, true).moved.then_some(next)
+    }
+
+    pub fn legal_moves(&self) -> impl Iterator<Item = Direction> + '_ {
+        Direction::ALL
+            .iter()
+            .copied()
+            .filter(move |&direction| self.can_move(direction))
+    }
+
+    // Clears what was kept of the last move and notes where every tile
+    // comes from. Untracked moves skip the latter, nothing draws them.
+    fn prepare_move(&mut self, tracked: bool) {
+        self.exploded.clear();
+        self.merged_from.clear();
+        if !tracked {
+            return;
+        }
+        for (i, cell) in self.grid.iter_mut().enumerate() {
+            if let Some(tile) = cell {
+                tile.state = TileState::New;
+                tile.prev_pos = Some(i);
+            }
+        }
+    }
+
+    // Moves the tiles and queues the events of the move, in place of those
+    // of the move before, so events nobody takes don't pile up.
+    pub fn move_tiles(&mut self, direction: Direction) -> MoveResult {
+        self.events.clear();
+        let was_won = self.won;
+        let result = self.apply_move(direction, true);
+        if !result.moved {
+            return result;
+        }
+
+        for merge in &result.merges {
+            self.events.push(GameEvent::TilesMerged {
+                index: merge.index,
+                value: merge.value,
+            });
+        }
+        if let Some((index, value)) = result.spawned {
+            self.events.push(GameEvent::TileSpawned { index, value });
+        }
+        if result.score_gained > 0 {
+            self.events
+                .push(GameEvent::ScoreChanged { score: self.score });
+        }
+        if self.won && !was_won {
+            self.events.push(GameEvent::GameWon);
+        } else if self.over || self.legal_moves().next().is_none() {
+            self.events.push(GameEvent::GameOver);
+        }
+
+        result
+    }
+
+    // Plays the moves one after the other, without queueing events or keeping
+    // what drawing them would need, for replays and the computer's games.
+    // Stops at the first move that doesn't change the board.
+    pub fn apply_moves(&mut self, directions: &[Direction]) -> BatchResult {
+        let was_won = self.won;
+        let mut batch = BatchResult::default();
+        for &direction in directions {
+            let result = self.apply_move(direction, false);
+            if !result.moved {
+                break;
+            }
+            batch.applied += 1;
+            batch.score_gained += result.score_gained;
+            batch.merges += result.merges.len();
+        }
+        // The tiles are drawn where they are, not sliding in from before the batch.
+        for tile in self.grid.iter_mut().flatten() {
+            tile.prev_pos = None;
+        }
+        batch.won = self.won && !was_won;
+        batch.game_over = self.over || self.legal_moves().next().is_none();
+        batch
+    }
+
+    // Takes the events of the last move, oldest first.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn apply_move(&mut self, direction: Direction, tracked: bool) -> MoveResult {
+        if self.is_game_over() {
+            return MoveResult::default();
+        }
+
+        self.mark_start();
+        self.prepare_move(tracked);
+        let score_before = self.score;
+
+        let mut moved = false;
+        let mut tally = MoveTally {
+            tracked,
+            ..MoveTally::default()
+        };
+        for line in self.board.lines(direction) {
+            let line = self.traversal.walk(line, &self.grid);
+            moved |= self.slide_and_merge_line(&line, &mut tally);
+        }
+
+        for &bomb in &tally.bombs {
+            self.explode(bomb);
+        }
+
+        if self.gravity && matches!(direction, Direction::Left | Direction::Right) {
+            moved |= self.settle();
+        }
+
+        if !moved {
+            return MoveResult::default();
+        }
+
+        let points: usize = tally.merges.iter().map(|merge| merge.value).sum();
+        self.combo = if tally.merges.is_empty() {
+            0
+        } else {
+            self.combo + 1
+        };
+        self.score += points * (100 + self.combo_bonus()) / 100;
+        if self.score_target.is_some_and(|target| self.score >= target) {
+            self.won = true;
+        }
+
+        self.history.push(Action::Move(direction));
+        if let Some(moves_left) = self.moves_left.as_mut() {
+            *moves_left = moves_left.saturating_sub(1);
+            self.over |= *moves_left == 0;
+        }
+        for tile in self.grid.iter_mut().flatten() {
+            tile.frozen = tile.frozen.saturating_sub(1);
+        }
+
+        let result = MoveResult {
+            moved,
+            direction: Some(direction),
+            score_gained: self.score - score_before,
+            merges: tally.merges,
+            spawned: self.add_random_tile(),
+        };
+        debug_assert_eq!(Ok(()), self.validate_board(), "Moving {:?}", direction);
+        result
+    }
+
+    // Checks what every game has to hold to, for fuzzers and property tests:
+    // the board, and that playing the history again ends on the score.
+    // Returns the first invariant broken.
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_board()?;
+
+        // Saves from before edits dropped the start may keep one without
+        // any history, which has nothing to replay.
+        if let Some(start) = self.start.as_ref().filter(|_| !self.history.is_empty()) {
+            let mut replayed = start.score;
+            self.replay(|frame| replayed = frame.score);
+            if replayed != self.score {
+                return Err(format!(
+                    "The score is {}, but playing the game again scores {}",
+                    self.score, replayed
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // What every board has to hold to after a move, checked in debug builds
+    // after each one too.
+    fn validate_board(&self) -> Result<(), String> {
+        let cell_count = self.board.topology().cell_count();
+        if self.grid.len() != cell_count {
+            return Err(format!(
+                "{} cells on a board of {}",
+                self.grid.len(),
+                cell_count
+            ));
+        }
+
+        let mut ids = BTreeSet::new();
+        for (i, tile) in self.grid.iter().enumerate() {
+            let tile = match tile {
+                Some(tile) => tile,
+                None => continue,
+            };
+            if tile.is_number() && !self.rules.makes(tile.get_value()) {
+                return Err(format!(
+                    "Cell {} holds {}, which the rules can't make",
+                    i,
+                    tile.get_value()
+                ));
+            }
+            if let Some(prev) = tile.prev_pos.filter(|&prev| prev >= cell_count) {
+                return Err(format!(
+                    "Cell {} comes from cell {}, off the board",
+                    i, prev
+                ));
+            }
+            if tile.id > self.last_id || !ids.insert(tile.id) {
+                return Err(format!("Cell {} reuses the tile id {}", i, tile.id));
+            }
+        }
+
+        // Every merge puts one group of tiles under a single merged tile,
+        // which doesn't merge again in the same move. Gravity may have moved
+        // the merged tiles on since.
+        if !self.gravity {
+            let cells: BTreeSet<usize> = self.merged_from.iter().map(|&(cell, _)| cell).collect();
+            for cell in cells {
+                let count = self.merged_from.iter().filter(|&&(c, _)| c == cell).count();
+                match self.grid.get(cell) {
+                    Some(Some(tile))
+                        if tile.state == TileState::Merged && count == self.rules.arity() => {}
+                    // The new tile may have spawned where the bomb went off.
+                    Some(_) if self.exploded.contains(&cell) => {}
+                    _ => return Err(format!("Cell {} has {} tiles merged under it", cell, count)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Slides and merges the tiles of one line towards its first cell, adding
+    // its merges and the bombs set off to `tally`.
+    fn slide_and_merge_line(&mut self, line: &[usize], tally: &mut MoveTally) -> bool {
+        let arity = self.rules.arity();
+        let mut moved = false;
+
+        // Tiles only merge within a segment of the line, which starts at the
+        // edge of the board or right after an obstacle.
+        let mut start = 0;
+        let mut next = 0;
+
+        for (i, &index) in line.iter().enumerate() {
+            let mut tile = match self.grid[index] {
+                Some(tile) => tile,
+                None => continue,
+            };
+            tile.state = TileState::Static;
+
+            if tile.is_fixed() {
+                self.grid[index] = Some(tile);
+                start = i + 1;
+                next = start;
+                continue;
+            }
+
+            self.grid[index] = None;
+
+            // The tile merges with the last tiles placed before it, as long
+            // as none of them has merged yet in this move.
+            if next - start + 1 >= arity {
+                let placed = &line[next + 1 - arity..next];
+                if self.merge_into(placed, tile, tally) {
+                    next = next + 1 - placed.len();
+                    moved = true;
+                    continue;
+                }
+            }
+
+            self.grid[line[next]] = Some(tile);
+            moved |= i != next;
+            next += 1;
+        }
+
+        moved
+    }
+
+    // Merges `tile` into the tiles placed on `placed`, the merged tile ending
+    // up on the first of them. Returns whether they merged.
+    fn merge_into(&mut self, placed: &[usize], tile: Tile, tally: &mut MoveTally) -> bool {
+        // No rule merges more than three tiles, so the group fits on the stack.
+        let mut group = [tile; 3];
+        let mut len = 0;
+        for placed_tile in placed.iter().filter_map(|&i| self.grid[i]) {
+            if placed_tile.state != TileState::Static {
+                return false;
+            }
+            group[len] = placed_tile;
+            len += 1;
+        }
+        if len != placed.len() {
+            return false;
+        }
+        group[len] = tile;
+        let group = &group[..=len];
+
+        let merged = match self.merge_tiles(group) {
+            Some(merged) => merged,
+            None => return false,
+        };
+
+        for &i in placed {
+            self.grid[i] = None;
+        }
+        let cell = placed[0];
+        if tally.tracked {
+            self.merged_from
+                .extend(group.iter().map(|&tile| (cell, tile)));
+        }
+        self.grid[cell] = Some(merged);
+
+        if merged.is_bomb() {
+            tally.bombs.push(cell);
+        } else {
+            tally.merges.push(Merge {
+                index: cell,
+                value: merged.get_value(),
+            });
+            self.power_ups
+                .extend(PowerUp::earned_by(merged.get_value()));
+            if self
+                .target
+                .is_some_and(|target| merged.get_value() >= target)
+            {
+                self.won = true;
+            }
+        }
+
+        true
+    }
+
+    // Lets every tile fall down as far as it can, without merging. Fixed
+    // tiles stay put and hold up the tiles above them.
+    fn settle(&mut self) -> bool {
+        let mut moved = false;
+
+        for line in self.board.lines(Direction::Down) {
+            let mut next = 0;
+            for (i, &index) in line.iter().enumerate() {
+                match self.grid[index] {
+                    Some(tile) if tile.is_fixed() => next = i + 1,
+                    Some(tile) => {
+                        if i != next {
+                            self.grid[line[next]] = Some(tile);
+                            self.grid[index] = None;
+                            moved = true;
+                        }
+                        next += 1;
+                    }
+                    None => (),
+                }
+            }
+        }
+
+        moved
+    }
+
+    // What neighbouring tiles merge into, if they merge at all. Special tiles
+    // only ever merge with a single number tile.
+    fn merge_tiles(&self, tiles: &[Tile]) -> Option<Tile> {
+        let values: Vec<usize> = tiles.iter().map(|tile| tile.get_value()).collect();
+        let sum: usize = values.iter().sum();
+
+        let (kind, value) = match tiles {
+            _ if tiles.iter().all(Tile::is_number) => {
+                (TileKind::Number, self.rules.merge(&values)?)
+            }
+            // The bomb swallows the number and carries its value into the explosion.
+            [a, b] if a.is_bomb() && b.is_number() || a.is_number() && b.is_bomb() => {
+                (TileKind::Bomb, sum)
+            }
+            // Multipliers are worth nothing themselves and double the number instead.
+            [a, b] if a.is_multiplier() && b.is_number() || a.is_number() && b.is_multiplier() => {
+                (TileKind::Number, sum * 2)
+            }
+            _ => return None,
+        };
+
+        // Past the largest value a tile holds, e.g. a fourth power of three
+        // in a game of Triples without a target, tiles stop merging.
+        Some(Tile {
+            exp: to_exp(value)?,
+            kind,
+            state: TileState::Merged,
+            ..tiles[0]
+        })
+    }
+
+    // Clears every cell on the lines through the bomb, except obstacles, and
+    // scores half of the values blown up.
+    fn explode(&mut self, bomb: usize) {
+        let mut cleared = 0;
+
+        for direction in Direction::ALL {
+            for line in self.board.lines(direction) {
+                if !line.contains(&bomb) {
+                    continue;
+                }
+
+                for &index in line {
+                    if self.grid[index].is_some_and(|tile| tile.is_obstacle()) {
+                        continue;
+                    }
+
+                    if let Some(tile) = self.grid[index].take() {
+                        cleared += tile.get_value();
+                    }
+                    if !self.exploded.contains(&index) {
+                        self.exploded.push(index);
+                    }
+                }
+            }
+        }
+
+        self.score += cleared / 2;
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    // Extra score in percent for the current run of moves with merges, 10%
+    // for every move after the first. Always 0 without combo scoring.
+    pub fn combo_bonus(&self) -> usize {
+        if self.combo_scoring {
+            self.combo.saturating_sub(1) * 10
+        } else {
+            0
+        }
+    }
+
+    pub fn combo_scoring(&self) -> bool {
+        self.combo_scoring
+    }
+
+    pub fn set_combo_scoring(&mut self, enabled: bool) {
+        self.combo_scoring = enabled;
+    }
+
+    pub fn spawn_policy(&self) -> SpawnPolicy {
+        self.spawn
+    }
+
+    pub fn exploded(&self) -> &[usize] {
+        &self.exploded
+    }
+
+    pub fn rules(&self) -> MergeRule {
+        self.rules
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Option<usize>) {
+        self.target = target;
+    }
+
+    pub fn set_score_target(&mut self, target: Option<usize>) {
+        self.score_target = target;
+    }
+
+    pub fn moves_left(&self) -> Option<usize> {
+        self.moves_left
+    }
+
+    // Ends the game once `limit` more moves changed the board, none to play
+    // on forever.
+    pub fn set_move_limit(&mut self, limit: Option<usize>) {
+        self.moves_left = limit;
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.traversal == Traversal::Wrap
+    }
+
+    pub fn board(&self) -> Board {
+        self.board
+    }
+
+    pub fn set_spawn(&mut self, spawn: SpawnPolicy) {
+        self.spawn = spawn;
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    // Places a tile for the board editor, 0 clears the cell. Edited boards
+    // start over as neither won nor lost.
+    pub fn set_value(&mut self, index: usize, value: usize) {
+        self.set_tile(index, (value != 0).then(|| Tile::new(value)));
+    }
+
+    pub fn set_tile(&mut self, index: usize, tile: Option<Tile>) {
+        let id = self.new_tile_id();
+        if let Some(cell) = self.grid.get_mut(index) {
+            *cell = tile.map(|tile| Tile { id, ..tile });
+        }
+        self.won = false;
+        self.over = false;
+        // The edited board is where replays start from now.
+        self.history.clear();
+        self.start = None;
+    }
+
+    pub fn shuffles_left(&self) -> usize {
+        SHUFFLES.saturating_sub(self.shuffles_used)
+    }
+
+    // Scatters the number tiles over all cells that aren't obstacles, keeping
+    // their values. Only allowed `SHUFFLES` times per game.
+    pub fn shuffle(&mut self) -> bool {
+        if self.shuffles_left() == 0 {
+            return false;
+        }
+
+        self.mark_start();
+        let mut cells = Vec::new();
+        let mut tiles = Vec::new();
+        for (i, cell) in self.grid.iter_mut().enumerate() {
+            match cell {
+                Some(tile) if tile.is_obstacle() => continue,
+                Some(tile) => tiles.push(*tile),
+                None => (),
+            }
+            *cell = None;
+            cells.push(i);
+        }
+
+        cells.shuffle(&mut self.rng);
+        for (i, mut tile) in cells.into_iter().zip(tiles) {
+            tile.state = TileState::New;
+            self.grid[i] = Some(tile);
+        }
+
+        self.shuffles_used += 1;
+        self.history.push(Action::Shuffle);
+        true
+    }
+
+    pub fn power_up_count(&self, power_up: PowerUp) -> usize {
+        self.power_ups.iter().filter(|&&p| p == power_up).count()
+    }
+
+    // Uses an owned power-up on the picked tiles. Returns false and leaves the
+    // board alone if it isn't owned or the targets aren't distinct number tiles.
+    pub fn use_power_up(&mut self, power_up: PowerUp, targets: &[usize]) -> bool {
+        let owned = self.power_ups.iter().position(|&p| p == power_up);
+        let tiles: Option<Vec<Tile>> = targets
+            .iter()
+            .map(|&i| self.grid.get(i).copied().flatten())
+            .map(|tile| tile.filter(|tile| tile.is_number()))
+            .collect();
+
+        let distinct = targets.first() != targets.get(1);
+        let (owned, tiles) = match (owned, tiles) {
+            (Some(owned), Some(tiles)) if targets.len() == power_up.targets() && distinct => {
+                (owned, tiles)
+            }
+            _ => return false,
+        };
+
+        self.mark_start();
+        match power_up {
+            PowerUp::Remove => self.grid[targets[0]] = None,
+            PowerUp::Swap => self.grid.swap(targets[0], targets[1]),
+            PowerUp::Double => {
+                let mut tile = tiles[0];
+                tile.update(tile.get_value() * 2, tile.state);
+                self.grid[targets[0]] = Some(tile);
+                // Doubling up to the target wins the game as a merge would.
+                if !self.won && self.target.is_some_and(|target| tile.get_value() >= target) {
+                    self.won = true;
+                    self.events.push(GameEvent::GameWon);
+                }
+            }
+        }
+
+        self.power_ups.remove(owned);
+        self.history
+            .push(Action::PowerUp(power_up, targets.to_vec()));
+        true
+    }
+
+    // Keeps the game as it is when nothing has been played yet, or since the
+    // board was last edited.
+    fn mark_start(&mut self) {
+        if !self.history.is_empty() {
+            return;
+        }
+
+        self.start = Some(Start {
+            grid: self.grid.clone(),
+            score: self.score,
+            power_ups: self.power_ups.clone(),
+            shuffles_used: self.shuffles_used,
+            combo: self.combo,
+            rng: self.rng,
+            moves_left: self.moves_left,
+        });
+    }
+
+    // Plays the game again from its start, calling `frame` with the board
+    // before the first action and after each one, holding the events of that
+    // action. Returns false, without calling `frame`, for games that have no
+    // start to go back to.
+    pub fn replay(&self, mut frame: impl FnMut(&GameState)) -> bool {
+        let start = match &self.start {
+            Some(start) if !self.history.is_empty() => start.clone(),
+            _ => return false,
+        };
+
+        let mut game_state = GameState {
+            grid: start.grid,
+            score: start.score,
+            over: false,
+            won: false,
+            // Games played on after winning would stop at the winning tile.
+            target: None,
+            moves_left: start.moves_left,
+            power_ups: start.power_ups,
+            history: Vec::new(),
+            start: None,
+            shuffles_used: start.shuffles_used,
+            combo: start.combo,
+            exploded: Vec::new(),
+            merged_from: Vec::new(),
+            events: Vec::new(),
+            rng: start.rng,
+            ..self.clone()
+        };
+
+        frame(&game_state);
+        for action in &self.history {
+            game_state.events.clear();
+            match action {
+                Action::Move(direction) => {
+                    game_state.move_tiles(*direction);
+                }
+                Action::PowerUp(power_up, targets) => {
+                    game_state.use_power_up(*power_up, targets);
+                }
+                Action::Shuffle => {
+                    game_state.shuffle();
+                }
+            }
+            frame(&game_state);
+        }
+
+        true
+    }
+
+    // The history with what each action earned, worked out by replaying it.
+    pub fn steps(&self) -> Vec<Step> {
+        let mut steps: Vec<Step> = Vec::new();
+        let mut score = None;
+        self.replay(|frame| {
+            if let Some(score) = score {
+                let action = self.history[steps.len()].clone();
+                let merges = match action {
+                    Action::Move(_) => frame
+                        .grid
+                        .iter()
+                        .flatten()
+                        .filter(|tile| tile.state == TileState::Merged)
+                        .count(),
+                    _ => 0,
+                };
+                let spawned = frame.events.iter().find_map(|event| match *event {
+                    GameEvent::TileSpawned { index, value } => Some((index, value)),
+                    _ => None,
+                });
+                steps.push(Step {
+                    action,
+                    score_gained: frame.score - score,
+                    merges,
+                    spawned,
+                });
+            }
+            score = Some(frame.score);
+        });
+        steps
+    }
+
+    pub fn history(&self) -> &[Action] {
+        &self.history
+    }
+
+    // Plays an action of a history as the player did. Returns whether it
+    // changed the board, which every action in a history did.
+    pub fn play(&mut self, action: &Action) -> bool {
+        match action {
+            Action::Move(direction) => self.move_tiles(*direction).moved,
+            Action::PowerUp(power_up, targets) => self.use_power_up(*power_up, targets),
+            Action::Shuffle => self.shuffle(),
+        }
+    }
+
+    pub fn final_state(&self) -> FinalState {
+        FinalState {
+            score: self.score,
+            max_tile: self.max_tile(),
+            moves: self.move_count(),
+        }
+    }
+
+    // Keeps the board but forgets how it came about, so the game takes far
+    // less space and can't be replayed anymore.
+    pub fn forget_history(&mut self) {
+        self.history.clear();
+        self.start = None;
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|action| matches!(action, Action::Move(_)))
+            .count()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Draws the random choices from here on from `seed` instead, e.g. for
+    // rollouts that mustn't know the spawns the game itself will see. The
+    // seed the game started from stays as it was.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+
+    pub fn max_tile(&self) -> usize {
+        self.grid
+            .iter()
+            .flatten()
+            .map(|tile| tile.get_value())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // A Zobrist hash of the tiles: every tile in every cell has a key of its
+    // own, the keys of the tiles on the board are xored together. Equal
+    // boards hash the same however the moves got there.
+    pub fn board_hash(&self) -> u64 {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tile)| Some((index, (*tile)?)))
+            .fold(0, |hash, (index, tile)| {
+                let piece =
+                    (tile.kind as u64) << 16 | (tile.frozen as u64) << 8 | u64::from(tile.exp);
+                hash ^ GameRng::new((index as u64) << 32 | piece).next_u64()
+            })
+    }
+
+    pub fn empty_count(&self) -> usize {
+        self.empty_cells().count()
+    }
+
+    // Only square boards are laid out in rows and columns.
+    pub fn tile_at(&self, row: usize, col: usize) -> Option<Tile> {
+        match self.board {
+            Board::Square if row < 4 && col < 4 => self.grid[row * 4 + col],
+            _ => None,
+        }
+    }
+
+    pub fn cells(&self) -> &[Option<Tile>] {
+        &self.grid
+    }
+
+    // The tiles to draw, starting with those merged away in the last move so
+    // the merged tiles pop up on top of them.
+    pub fn get_tiles(&self) -> impl Iterator<Item = RenderTile> + '_ {
+        let under = self.merged_from.iter().map(|&(pos, tile)| RenderTile {
+            pos,
+            prev: tile.prev_pos,
+            kind: RenderKind::Under,
+            tile,
+        });
+
+        let tiles = self.grid.iter().enumerate().filter_map(|(pos, cell)| {
+            cell.map(|tile| RenderTile {
+                pos,
+                prev: tile.prev_pos,
+                kind: match tile.state {
+                    TileState::New => RenderKind::New,
+                    TileState::Static => RenderKind::Moved,
+                    TileState::Merged => RenderKind::Merged,
+                },
+                tile,
+            })
+        });
+
+        under.chain(tiles)
+    }
+}
+
+// What a game came to, as `verify_replay` works it out. Host pages read it,
+// so the names are the ones of JavaScript.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalState {
+    pub score: usize,
+    pub max_tile: usize,
+    pub moves: usize,
+}
+
+// Plays a game again from its seed, so its score can be checked against the
+// actions that made it rather than taken on trust. Fails on the first action
+// that couldn't have been played.
+pub fn verify_replay(
+    variant: Variant,
+    spawn: SpawnPolicy,
+    combo: bool,
+    seed: u64,
+    actions: &[Action],
+) -> Result<FinalState, String> {
+    let mut game_state = GameState::with_seed(variant, spawn, seed);
+    game_state.set_combo_scoring(combo);
+    let mut i = 0;
+    while i < actions.len() {
+        // Moves in a row go as one batch.
+        let moves: Vec<Direction> = actions[i..]
+            .iter()
+            .map_while(|action| match action {
+                Action::Move(direction) => Some(*direction),
+                _ => None,
+            })
+            .collect();
+        let played = if moves.is_empty() {
+            usize::from(game_state.play(&actions[i]))
+        } else {
+            game_state.apply_moves(&moves).applied
+        };
+        if played < moves.len().max(1) {
+            return Err(format!(
+                "Action {} can't be played on its board.",
+                i + played + 1
+            ));
+        }
+        i += played;
+    }
+    Ok(game_state.final_state())
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::with_variant(Variant::Classic, SpawnPolicy::Classic)
+    }
+}
+
+// Text notation of a single cell: "." for empty, X, B and M for obstacles,
+// bombs and x2 tiles, otherwise the value.
+fn cell_notation(cell: Option<Tile>) -> String {
+    match cell {
+        None => ".".to_string(),
+        Some(tile) if tile.is_obstacle() => "X".to_string(),
+        Some(tile) if tile.is_bomb() => "B".to_string(),
+        Some(tile) if tile.is_multiplier() => "M".to_string(),
+        Some(tile) => tile.get_value().to_string(),
+    }
+}
+
+// One row per line, with the values lined up, e.g.
+//    2    .    .   16
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .grid
+            .iter()
+            .map(|&cell| cell_notation(cell).len())
+            .max()
+            .unwrap_or(1);
+
+        let rows: Vec<String> = self
+            .board
+            .topology()
+            .lines(Direction::Left)
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|&i| format!("{:>width$}", cell_notation(self.grid[i]), width = width))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBoardError {
+    // Neither a cell notation nor a tile value.
+    Cell(String),
+    // No board has this many cells.
+    CellCount(usize),
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBoardError::Cell(cell) => write!(f, "{} is not a tile", cell),
+            ParseBoardError::CellCount(count) => write!(f, "no board has {} cells", count),
+        }
+    }
+}
+
+// Reads cells in the order they are stored, separated by spaces, new lines or
+// "/", so both "2 0 0 16 / 0 4 ..." and the output of `Display` parse. The board
+// is picked by the number of cells and, like puzzles, spawns no tiles. Boards
+// are classic, so only powers of two are tiles.
+impl FromStr for GameState {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .filter(|cell| !cell.is_empty())
+            .map(|cell| match cell {
+                "." | "0" => Ok(None),
+                "X" => Ok(Some(Tile::obstacle())),
+                "B" => Ok(Some(Tile::bomb())),
+                "M" => Ok(Some(Tile::multiplier())),
+                _ => cell
+                    .parse()
+                    .ok()
+                    .filter(|&value| MergeRule::Classic.makes(value) && to_exp(value).is_some())
+                    .map(|value| Some(Tile::new(value)))
+                    .ok_or_else(|| ParseBoardError::Cell(cell.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let board = [Board::Square, Board::Hex, Board::Cube]
+            .iter()
+            .copied()
+            .find(|board| board.topology().cell_count() == cells.len())
+            .ok_or(ParseBoardError::CellCount(cells.len()))?;
+
+        let mut game_state = GameState::builder()
+            .board(board)
+            .generate_tiles(false)
+            .build()
+            .expect("no tiles to reject");
+        game_state.grid = cells;
+        game_state.last_id = number_tiles(&mut game_state.grid);
+        Ok(game_state)
+    }
+}
+
+impl PartialEq for GameState {
+    fn eq(&self, other: &GameState) -> bool {
+        self.grid == other.grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{
+        default_target, from_exp, to_exp, verify_replay, Action, Direction, GameEvent, GameState,
+        Merge, MergeRule, MoveResult, ParseBoardError, PowerUp, RenderKind, SpawnPolicy, Tile,
+        Traversal, Variant,
+    };
+    use crate::rng::GameRng;
+    use crate::topology::Board;
+    use rand::rngs::mock::StepRng;
+    use rand::Rng;
+
+    const X: usize = usize::MAX;
+    const B: usize = usize::MAX - 1;
+    const M: usize = usize::MAX - 2;
+
+    fn to_grid(from: &[usize]) -> Vec<Option<Tile>> {
+        let mut to = vec![None; from.len()];
+        for i in 0..from.len() {
+            if from[i] == X {
+                to[i] = Some(Tile::obstacle());
+            } else if from[i] == B {
+                to[i] = Some(Tile::bomb());
+            } else if from[i] == M {
+                to[i] = Some(Tile::multiplier());
+            } else if from[i] != 0 {
+                to[i] = Some(Tile::new(from[i]));
+            }
+        }
+        to
+    }
+
+    fn from_grid(from: &[Option<Tile>]) -> Vec<usize> {
+        let mut to = vec![0; from.len()];
+        for i in 0..from.len() {
+            if let Some(tile) = from[i] {
+                to[i] = if tile.is_obstacle() {
+                    X
+                } else if tile.is_bomb() {
+                    B
+                } else if tile.is_multiplier() {
+                    M
+                } else {
+                    tile.get_value()
+                };
+            }
+        }
+        to
+    }
+
+    #[test]
+    fn test_basic() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Basic: Left",
+                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
+                want: [2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Basic: Right",
+                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
+                want: [0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2],
+                moves: vec![Direction::Right],
+            },
+            TestCase {
+                name: "Basic: Up",
+                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
+                want: [2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Up],
+            },
+            TestCase {
+                name: "Basic: Down",
+                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2],
+                moves: vec![Direction::Down],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_preview_move() {
+        struct TestCase<'a> {
+            name: &'a str,
+            direction: Direction,
+            want: Option<[usize; 16]>,
+        }
+
+        let curr = [2, 2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8];
+        let tests = [
+            TestCase {
+                name: "Preview: Left",
+                direction: Direction::Left,
+                want: Some([4, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0]),
+            },
+            TestCase {
+                name: "Preview: Right",
+                direction: Direction::Right,
+                want: Some([0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 8]),
+            },
+        ];
+
+        for t in tests {
+            let mut gs = GameState::new(to_grid(&curr), true);
+            let got = gs
+                .preview_move(t.direction)
+                .map(|next| from_grid(&next.grid));
+
+            assert_eq!(t.want.map(|want| want.to_vec()), got, "{}", t.name);
+            assert_eq!(curr.to_vec(), from_grid(&gs.grid), "{}: Unchanged", t.name);
+            assert!(gs.drain_events().is_empty(), "{}: No Events", t.name);
+        }
+
+        let stuck = GameState::new(
+            to_grid(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]),
+            true,
+        );
+        assert!(
+            stuck.preview_move(Direction::Up).is_none(),
+            "Preview: Stuck"
+        );
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let moves = [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ];
+        for variant in [Variant::Classic, Variant::Bombs, Variant::Gravity] {
+            let mut one_by_one = GameState::with_seed(variant, SpawnPolicy::Classic, 3);
+            let mut batched = one_by_one.clone();
+            let mut score_gained = 0;
+            let mut applied = 0;
+            for &direction in &moves {
+                let result = one_by_one.move_tiles(direction);
+                score_gained += result.score_gained;
+                applied += usize::from(result.moved);
+                if !result.moved {
+                    break;
+                }
+            }
+
+            let batch = batched.apply_moves(&moves);
+            assert_eq!(
+                (applied, score_gained),
+                (batch.applied, batch.score_gained),
+                "Batch {:?}: Result",
+                variant
+            );
+            assert_eq!(
+                one_by_one.to_string(),
+                batched.to_string(),
+                "Batch {:?}: Board",
+                variant
+            );
+            assert_eq!(
+                one_by_one.history(),
+                batched.history(),
+                "Batch {:?}: History",
+                variant
+            );
+            assert!(
+                batched.drain_events().is_empty(),
+                "Batch {:?}: No Events",
+                variant
+            );
+        }
+
+        let mut stuck = GameState::new(
+            to_grid(&[2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2]),
+            true,
+        );
+        let batch = stuck.apply_moves(&moves);
+        assert_eq!((0, true), (batch.applied, batch.game_over), "Batch: Stuck");
+    }
+
+    #[test]
+    fn test_validate() {
+        struct TestCase<'a> {
+            name: &'a str,
+            break_board: fn(&mut GameState),
+            want_ok: bool,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Validate: After A Merge",
+                break_board: |_| {},
+                want_ok: true,
+            },
+            TestCase {
+                name: "Validate: Value The Rules Can't Make",
+                break_board: |gs| gs.grid[5] = Some(Tile::new(6)),
+                want_ok: false,
+            },
+            TestCase {
+                name: "Validate: Tile Twice",
+                break_board: |gs| gs.grid[5] = gs.grid[0],
+                want_ok: false,
+            },
+            TestCase {
+                name: "Validate: From Off The Board",
+                break_board: |gs| {
+                    if let Some(tile) = gs.grid[0].as_mut() {
+                        tile.prev_pos = Some(16);
+                    }
+                },
+                want_ok: false,
+            },
+            TestCase {
+                name: "Validate: Merged Twice",
+                break_board: |gs| {
+                    let under = gs.merged_from.clone();
+                    gs.merged_from.extend(under);
+                },
+                want_ok: false,
+            },
+            TestCase {
+                name: "Validate: Score Went Down",
+                break_board: |gs| {
+                    if let Some(start) = gs.start.as_mut() {
+                        start.score = gs.score + 4;
+                    }
+                },
+                want_ok: false,
+            },
+            TestCase {
+                name: "Validate: Edited After Scoring",
+                break_board: |gs| gs.set_value(5, 2),
+                want_ok: true,
+            },
+            TestCase {
+                name: "Validate: Start Without History",
+                break_board: |gs| gs.history.clear(),
+                want_ok: true,
+            },
+            TestCase {
+                name: "Validate: Score Not Played",
+                break_board: |gs| gs.score += 4,
+                want_ok: false,
+            },
+        ];
+
+        for t in tests {
+            let mut gs = GameState::new(
+                to_grid(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+                false,
+            );
+            gs.move_tiles(Direction::Left);
+            (t.break_board)(&mut gs);
+            assert_eq!(t.want_ok, gs.validate().is_ok(), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Merge: Basic Merge",
+                curr: [2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Merge: Not Merge",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
+                want: [2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Up],
+            },
+            TestCase {
+                name: "Merge: Merge Twice",
+                curr: [2, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left, Direction::Right],
+            },
+            TestCase {
+                name: "Merge: Merge Twos",
+                curr: [2, 2, 2, 2, 0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 0, 2],
+                want: [4, 4, 0, 0, 4, 2, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Merge: Not Across Rows Left",
+                curr: [2, 4, 8, 16, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [2, 4, 8, 16, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Merge: Not Across Rows Right",
+                curr: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16, 16, 2, 4, 8],
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16, 16, 2, 4, 8],
+                moves: vec![Direction::Right],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_obstacles() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Obstacles: Block Sliding",
+                curr: [0, X, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [0, X, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Obstacles: Block Merging",
+                curr: [2, X, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [2, X, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Obstacles: Merge Behind Obstacle",
+                curr: [0, 0, 0, 0, X, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0],
+                want: [0, 0, 0, 0, X, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Up],
+            },
+            TestCase {
+                name: "Obstacles: Never Move",
+                curr: [X, 0, 0, 0, 0, 0, 0, 0, 0, 0, X, 0, 0, 0, 0, 0],
+                want: [X, 0, 0, 0, 0, 0, 0, 0, 0, 0, X, 0, 0, 0, 0, 0],
+                moves: vec![
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Up,
+                ],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_threes() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
+            want_score: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Threes: One And Two",
+                curr: [1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 6,
+            },
+            TestCase {
+                name: "Threes: Equal Ones And Twos",
+                curr: [1, 1, 0, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [1, 1, 0, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+            TestCase {
+                name: "Threes: Equal Threes",
+                curr: [3, 3, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [6, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 6,
+            },
+            TestCase {
+                name: "Threes: Unequal",
+                curr: [3, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [3, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.rules = MergeRule::Threes;
+
+            gs.move_tiles(Direction::Left);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_triples() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
+            want_score: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Triples: Three Equal",
+                curr: [3, 0, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 9,
+            },
+            TestCase {
+                name: "Triples: Pairs Don't Merge",
+                curr: [1, 1, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [1, 1, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+            TestCase {
+                name: "Triples: Four Equal",
+                curr: [1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [3, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 3,
+            },
+            TestCase {
+                name: "Triples: After A Different Tile",
+                curr: [9, 3, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [9, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 9,
+            },
+            TestCase {
+                name: "Triples: Split By An Obstacle",
+                curr: [3, X, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [3, X, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.rules = MergeRule::Triples;
+
+            gs.move_tiles(Direction::Left);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_wrap() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            want: [usize; 16],
+            want_score: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Wrap: Gap Behind The Edge",
+                curr: [0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 4,
+            },
+            TestCase {
+                name: "Wrap: Merge Across The Edge",
+                curr: [2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 4,
+            },
+            TestCase {
+                name: "Wrap: Merge Across The Right Edge",
+                curr: [2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Right,
+                want: [0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 4,
+            },
+            TestCase {
+                name: "Wrap: Slide Off The Top",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0],
+                direction: Direction::Up,
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0],
+                want_score: 0,
+            },
+            TestCase {
+                name: "Wrap: Full Line",
+                curr: [2, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [2, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.traversal = Traversal::Wrap;
+
+            gs.move_tiles(t.direction);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_gravity() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            want: [usize; 16],
+        }
+
+        let tests = [
+            TestCase {
+                name: "Gravity: Falls Without Merging",
+                curr: [0, 0, 0, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0],
+                direction: Direction::Left,
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0],
+            },
+            TestCase {
+                name: "Gravity: Only After Horizontal Moves",
+                curr: [0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Up,
+                want: [0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+            TestCase {
+                name: "Gravity: Obstacles Hold Tiles Up",
+                curr: [0, 2, 0, 0, X, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [2, 0, 0, 0, X, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+            TestCase {
+                name: "Gravity: Settles Without A Slide",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.gravity = true;
+
+            gs.move_tiles(t.direction);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_exp() {
+        struct TestCase<'a> {
+            name: &'a str,
+            value: usize,
+            want_packed: bool,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Exp: Zero",
+                value: 0,
+                want_packed: true,
+            },
+            TestCase {
+                name: "Exp: Power Of Two",
+                value: 131072,
+                want_packed: true,
+            },
+            TestCase {
+                name: "Exp: Threes",
+                value: 768,
+                want_packed: true,
+            },
+            TestCase {
+                name: "Exp: Triples",
+                value: 2187,
+                want_packed: true,
+            },
+            TestCase {
+                name: "Exp: Doubled Triple",
+                value: 18,
+                want_packed: true,
+            },
+            TestCase {
+                name: "Exp: Not A Tile Value",
+                value: 10,
+                want_packed: false,
+            },
+        ];
+
+        for t in tests {
+            let got = to_exp(t.value).map(from_exp);
+            let want = t.want_packed.then_some(t.value);
+
+            assert_eq!(want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 19],
+            want: [usize; 19],
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Hex: Down Right",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4],
+                moves: vec![Direction::DownRight],
+            },
+            TestCase {
+                name: "Hex: Right",
+                curr: [2, 0, 0, 2, 2, 2, 0, 4, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
+                want: [0, 0, 2, 0, 0, 2, 4, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Right],
+            },
+            TestCase {
+                name: "Hex: Up Does Nothing",
+                curr: [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Up, Direction::Down],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.board = Board::Hex;
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_cube() {
+        struct TestCase<'a> {
+            name: &'a str,
+            // (index, value) pairs on the 64-cell board.
+            curr: Vec<(usize, usize)>,
+            want: Vec<(usize, usize)>,
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Cube: Above",
+                curr: vec![(5, 2), (37, 2), (58, 4)],
+                want: vec![(5, 4), (10, 4)],
+                moves: vec![Direction::Above],
+            },
+            TestCase {
+                name: "Cube: Below",
+                curr: vec![(0, 2), (16, 2), (32, 2)],
+                want: vec![(32, 2), (48, 4)],
+                moves: vec![Direction::Below],
+            },
+            TestCase {
+                name: "Cube: Left Stays In Layer",
+                curr: vec![(19, 2), (35, 2)],
+                want: vec![(16, 2), (32, 2)],
+                moves: vec![Direction::Left],
+            },
+        ];
+
+        for t in tests {
+            let mut curr = vec![0; 64];
+            let mut want = vec![0; 64];
+            for &(i, value) in &t.curr {
+                curr[i] = value;
+            }
+            for &(i, value) in &t.want {
+                want[i] = value;
+            }
+
+            let mut gs = GameState::new(to_grid(&curr), false);
+            gs.board = Board::Cube;
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(want, from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_frozen() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            frozen: Vec<(usize, usize)>,
+            want: [usize; 16],
+            moves: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Frozen: Blocks Like An Obstacle",
+                curr: [0, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                frozen: vec![(2, 3)],
+                want: [2, 0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left],
+            },
+            TestCase {
+                name: "Frozen: Thaws",
+                curr: [0, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2],
+                frozen: vec![(2, 2)],
+                want: [4, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                moves: vec![Direction::Left, Direction::Up, Direction::Left],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            for (i, frozen) in t.frozen {
+                if let Some(tile) = gs.grid[i].as_mut() {
+                    tile.frozen = frozen;
+                }
+            }
+
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_bombs() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            want: [usize; 16],
+            want_score: usize,
+            want_exploded: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Bombs: Number Into Bomb",
+                curr: [B, 2, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0],
+                direction: Direction::Left,
+                want: [0; 16],
+                want_score: 7,
+                want_exploded: 7,
+            },
+            TestCase {
+                name: "Bombs: Bomb Into Number",
+                curr: [2, 8, 0, 0, B, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Up,
+                want: [0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 5,
+                want_exploded: 7,
+            },
+            TestCase {
+                name: "Bombs: Never Merge With Each Other",
+                curr: [B, B, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [B, B, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+                want_exploded: 0,
+            },
+            TestCase {
+                name: "Bombs: Obstacles Survive",
+                curr: [B, 2, 0, 0, X, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [0, 0, 0, 0, X, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 1,
+                want_exploded: 6,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.move_tiles(t.direction);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+            assert_eq!(t.want_exploded, gs.exploded().len(), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_multipliers() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            want: [usize; 16],
+            want_score: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Multipliers: Number Into Multiplier",
+                curr: [M, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 8,
+            },
+            TestCase {
+                name: "Multipliers: Multiplier Into Number",
+                curr: [0, 16, 0, M, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 32,
+            },
+            TestCase {
+                name: "Multipliers: Merge Once Per Move",
+                curr: [M, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [4, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 4,
+            },
+            TestCase {
+                name: "Multipliers: Never Merge With Special Tiles",
+                curr: [M, M, B, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [M, M, B, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_score: 0,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.move_tiles(t.direction);
+
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_combo() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            combo_scoring: bool,
+            moves: Vec<Direction>,
+            want_score: usize,
+            want_bonus: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Combo: Escalates",
+                curr: [4, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo_scoring: true,
+                moves: vec![Direction::Left, Direction::Left, Direction::Left],
+                // 8, then 16 * 1.1 and 32 * 1.2, rounded down.
+                want_score: 8 + 17 + 38,
+                want_bonus: 20,
+            },
+            TestCase {
+                name: "Combo: Resets Without Merges",
+                curr: [16, 16, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo_scoring: true,
+                moves: vec![Direction::Left, Direction::Right, Direction::Up],
+                want_score: 32 + 64,
+                want_bonus: 0,
+            },
+            TestCase {
+                name: "Combo: Off",
+                curr: [4, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo_scoring: false,
+                moves: vec![Direction::Left, Direction::Left, Direction::Left],
+                want_score: 8 + 16 + 32,
+                want_bonus: 0,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.set_combo_scoring(t.combo_scoring);
+            for d in &t.moves {
+                gs.move_tiles(*d);
+            }
+
+            assert_eq!(t.want_score, gs.score, "{}", t.name);
+            assert_eq!(t.want_bonus, gs.combo_bonus(), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        struct TestCase<'a> {
+            name: &'a str,
+            board: Board,
+            size: Option<usize>,
+            rules: MergeRule,
+            tiles: Vec<(usize, usize)>,
+            target: Option<Option<usize>>,
+            generate_tiles: bool,
+            // Cells and tiles, or the error.
+            want: Result<(usize, usize), &'a str>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Builder: Defaults",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![],
+                target: None,
+                generate_tiles: true,
+                want: Ok((16, 0)),
+            },
+            TestCase {
+                name: "Builder: Tiles",
+                board: Board::Square,
+                size: Some(4),
+                rules: MergeRule::Classic,
+                tiles: vec![(0, 2), (5, 4), (15, 8)],
+                target: Some(Some(4096)),
+                generate_tiles: false,
+                want: Ok((16, 3)),
+            },
+            TestCase {
+                name: "Builder: No Target",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![],
+                target: Some(None),
+                generate_tiles: true,
+                want: Ok((16, 0)),
+            },
+            TestCase {
+                name: "Builder: Hex",
+                board: Board::Hex,
+                size: Some(3),
+                rules: MergeRule::Classic,
+                tiles: vec![(18, 2)],
+                target: None,
+                generate_tiles: true,
+                want: Ok((19, 1)),
+            },
+            TestCase {
+                name: "Builder: Wrong Size",
+                board: Board::Square,
+                size: Some(5),
+                rules: MergeRule::Classic,
+                tiles: vec![],
+                target: None,
+                generate_tiles: true,
+                want: Err("Square boards have 4 cells a side, not 5"),
+            },
+            TestCase {
+                name: "Builder: Outside The Board",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![(0, 2), (16, 8)],
+                target: None,
+                generate_tiles: true,
+                want: Err("Cell 16 is outside the Square board"),
+            },
+            TestCase {
+                name: "Builder: Not A Tile Value",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![(0, 5)],
+                target: None,
+                generate_tiles: true,
+                want: Err("Classic rules can't make 5"),
+            },
+            TestCase {
+                name: "Builder: Zero",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![(0, 0)],
+                target: None,
+                generate_tiles: true,
+                want: Err("Classic rules can't make 0"),
+            },
+            TestCase {
+                name: "Builder: Three On A Classic Board",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![(0, 3)],
+                target: None,
+                generate_tiles: true,
+                want: Err("Classic rules can't make 3"),
+            },
+            TestCase {
+                name: "Builder: Threes",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Threes,
+                tiles: vec![(0, 1), (1, 2), (2, 6)],
+                target: None,
+                generate_tiles: false,
+                want: Ok((16, 3)),
+            },
+            TestCase {
+                name: "Builder: Target Out Of Reach",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Classic,
+                tiles: vec![],
+                target: Some(Some(0)),
+                generate_tiles: true,
+                want: Err("Classic rules can't make the target 0"),
+            },
+            TestCase {
+                name: "Builder: Classic Target For Threes",
+                board: Board::Square,
+                size: None,
+                rules: MergeRule::Threes,
+                tiles: vec![],
+                target: Some(Some(2048)),
+                generate_tiles: true,
+                want: Err("Threes rules can't make the target 2048"),
+            },
+        ];
+
+        for t in tests {
+            let mut builder = GameState::builder()
+                .board(t.board)
+                .rules(t.rules)
+                .with_tiles(&t.tiles)
+                .generate_tiles(t.generate_tiles);
+            if let Some(size) = t.size {
+                builder = builder.size(size);
+            }
+            if let Some(target) = t.target {
+                builder = builder.target(target);
+            }
+            let gs = match (builder.build(), t.want) {
+                (Ok(gs), Ok((want_cells, want_tiles))) => {
+                    assert_eq!(want_cells, gs.grid.len(), "{}", t.name);
+                    assert_eq!(want_tiles, gs.grid.iter().flatten().count(), "{}", t.name);
+                    gs
+                }
+                (got, want) => {
+                    assert_eq!(
+                        want.map_err(String::from),
+                        got.map(|_| (0, 0)),
+                        "{}",
+                        t.name
+                    );
+                    continue;
+                }
+            };
+            assert_eq!(
+                t.target.unwrap_or(Some(t.rules.target())),
+                gs.target,
+                "{}",
+                t.name
+            );
+            assert_eq!(t.rules, gs.rules, "{}", t.name);
+            assert_eq!(t.generate_tiles, gs.generate_tiles, "{}", t.name);
+        }
+
+        // The same seed spawns the same tiles.
+        let spawns: Vec<_> = (0..2)
+            .map(|_| {
+                let mut gs = GameState::builder()
+                    .seed(7)
+                    .spawn_policy(SpawnPolicy::TwoFourEight)
+                    .build()
+                    .unwrap();
+                gs.add_random_tile()
+            })
+            .collect();
+        assert_eq!(spawns[0], spawns[1], "Builder: Seed");
+    }
+
+    #[test]
+    fn test_notation() {
+        struct TestCase<'a> {
+            name: &'a str,
+            text: &'a str,
+            want: Result<Vec<usize>, ParseBoardError>,
+            want_text: &'a str,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Notation: Slashes",
+                text: "2 0 0 16 / 0 4 0 0 / 0 0 0 0 / 128 0 0 2",
+                want: Ok(vec![2, 0, 0, 16, 0, 4, 0, 0, 0, 0, 0, 0, 128, 0, 0, 2]),
+                want_text: "  2   .   .  16\n  .   4   .   .\n  .   .   .   .\n128   .   .   2",
+            },
+            TestCase {
+                name: "Notation: Special Tiles",
+                text: "X . . .\n. B . .\n. . M .\n. . . 2",
+                want: Ok(vec![X, 0, 0, 0, 0, B, 0, 0, 0, 0, M, 0, 0, 0, 0, 2]),
+                want_text: "X . . .\n. B . .\n. . M .\n. . . 2",
+            },
+            TestCase {
+                name: "Notation: Hex",
+                text: "2 . . / . . . . / . . 4 . . / . . . . / . . 8",
+                want: Ok(vec![
+                    2, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 8,
+                ]),
+                want_text: "2 . .\n. . . .\n. . 4 . .\n. . . .\n. . 8",
+            },
+            TestCase {
+                name: "Notation: Not A Tile",
+                text: "2 0 0 5 / 0 0 0 0 / 0 0 0 0 / 0 0 0 0",
+                want: Err(ParseBoardError::Cell("5".to_string())),
+                want_text: "",
+            },
+            TestCase {
+                name: "Notation: Not A Classic Tile",
+                text: "3 . . . / . . . . / . . . . / . . . 6",
+                want: Err(ParseBoardError::Cell("3".to_string())),
+                want_text: "",
+            },
+            TestCase {
+                name: "Notation: Cell Count",
+                text: "2 0 0 / 0 0 0",
+                want: Err(ParseBoardError::CellCount(6)),
+                want_text: "",
+            },
+        ];
+
+        for t in tests {
+            let got = t.text.parse::<GameState>();
+
+            assert_eq!(
+                t.want,
+                got.clone().map(|gs| from_grid(&gs.grid)),
+                "{}",
+                t.name
+            );
+            if let Ok(gs) = got {
+                assert_eq!(t.want_text, gs.to_string(), "{}", t.name);
+                assert_eq!(Ok(gs.clone()), gs.to_string().parse(), "{}", t.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_ids() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            // Ids after the move, 0 for empty cells; tiles are numbered in order.
+            want: [u32; 16],
+        }
+
+        let tests = [
+            TestCase {
+                name: "Tile Ids: Slide",
+                curr: [0, 0, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: [1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+            TestCase {
+                name: "Tile Ids: Merge Keeps The First",
+                curr: [2, 2, 0, 0, 0, 0, 0, 0, 4, 0, 0, 4, 0, 0, 0, 0],
+                direction: Direction::Right,
+                want: [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0],
+            },
+        ];
+
+        for t in tests {
+            let mut gs = GameState::new(to_grid(&t.curr), false);
+
+            gs.move_tiles(t.direction);
+
+            let got: Vec<u32> = gs
+                .grid
+                .iter()
+                .map(|cell| cell.map_or(0, |tile| tile.id()))
+                .collect();
+            assert_eq!(t.want.to_vec(), got, "{}", t.name);
+        }
+
+        // The merged-away tile had the highest id, new tiles still get a fresh one.
+        let mut gs = GameState::new(
+            to_grid(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            true,
+        );
+        let spawned = gs
+            .move_tiles(Direction::Left)
+            .spawned
+            .map(|(index, _)| index);
+        assert_eq!(
+            Some(3),
+            spawned
+                .and_then(|index| gs.grid[index])
+                .map(|tile| tile.id()),
+            "Tile Ids: Not Reused"
+        );
+    }
+
+    #[test]
+    fn test_get_tiles() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            direction: Direction,
+            // Cell, previous cell, kind and value of every tile drawn.
+            want: Vec<(usize, Option<usize>, RenderKind, usize)>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Get Tiles: Slide",
+                curr: [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: vec![(0, Some(3), RenderKind::Moved, 2)],
+            },
+            TestCase {
+                name: "Get Tiles: Merge",
+                curr: [0, 2, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: vec![
+                    (0, Some(1), RenderKind::Under, 2),
+                    (0, Some(3), RenderKind::Under, 2),
+                    (0, Some(1), RenderKind::Merged, 4),
+                ],
+            },
+            TestCase {
+                name: "Get Tiles: x2 Merge",
+                curr: [8, M, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                direction: Direction::Left,
+                want: vec![
+                    (0, Some(0), RenderKind::Under, 8),
+                    (0, Some(1), RenderKind::Under, 0),
+                    (0, Some(0), RenderKind::Merged, 16),
+                ],
+            },
+        ];
+
+        for t in tests {
+            let mut gs = GameState::new(to_grid(&t.curr), false);
+
+            gs.move_tiles(t.direction);
+
+            let got: Vec<_> = gs
+                .get_tiles()
+                .map(|render| {
+                    (
+                        render.pos,
+                        render.prev,
+                        render.kind,
+                        render.tile.get_value(),
+                    )
+                })
+                .collect();
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want_empty: usize,
+            want_max: usize,
+            // Row, column and the value expected there.
+            want_at: Vec<(usize, usize, Option<usize>)>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Accessors: Empty Board",
+                curr: [0; 16],
+                want_empty: 16,
+                want_max: 0,
+                want_at: vec![(0, 0, None), (3, 3, None)],
+            },
+            TestCase {
+                name: "Accessors: Rows And Columns",
+                curr: [2, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 64, 0, 0],
+                want_empty: 13,
+                want_max: 64,
+                want_at: vec![(0, 0, Some(2)), (1, 3, Some(8)), (3, 1, Some(64))],
+            },
+            TestCase {
+                name: "Accessors: Outside The Board",
+                curr: [2; 16],
+                want_empty: 0,
+                want_max: 2,
+                want_at: vec![(4, 0, None), (0, 4, None)],
+            },
+        ];
+
+        for t in tests {
+            let gs = GameState::new(to_grid(&t.curr), false);
+
+            assert_eq!(t.want_empty, gs.empty_count(), "{}", t.name);
+            assert_eq!(t.want_max, gs.max_tile(), "{}", t.name);
+            for (row, col, want) in t.want_at {
+                let got = gs.tile_at(row, col).map(|tile| tile.get_value());
+                assert_eq!(want, got, "{}", t.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: Vec<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Legal Moves: Corner Tile",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want: vec![Direction::Right, Direction::Down],
+            },
+            TestCase {
+                name: "Legal Moves: Merge Only",
+                curr: [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 8, 8],
+                want: vec![Direction::Left, Direction::Right],
+            },
+            TestCase {
+                name: "Legal Moves: Stuck",
+                curr: [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2],
+                want: vec![],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let gs = GameState::new(curr.clone(), true);
+
+            let got: Vec<Direction> = gs.legal_moves().collect();
+
+            assert_eq!(t.want, got, "{}", t.name);
+            assert_eq!(curr, gs.grid, "{}", t.name);
+        }
+
+        // Reading the board agrees with trying the move, whatever the rules,
+        // special tiles, traversal and gravity.
+        let mut rng = GameRng::new(3);
+        for _ in 0..2000 {
+            let bytes: Vec<u8> = (0..66).map(|_| rng.gen()).collect();
+            let (gs, _) = GameState::from_bytes_lossy(&bytes);
+            for direction in Direction::ALL {
+                assert_eq!(
+                    gs.preview_move(direction).is_some(),
+                    gs.can_move(direction),
+                    "Legal Moves: {:?} on\n{}",
+                    direction,
+                    gs
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            generate_tiles: bool,
+            direction: Direction,
+            want: Vec<GameEvent>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Events: Merge",
+                curr: [2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                generate_tiles: false,
+                direction: Direction::Left,
+                want: vec![
+                    GameEvent::TilesMerged { index: 0, value: 4 },
+                    GameEvent::ScoreChanged { score: 4 },
+                ],
+            },
+            TestCase {
+                name: "Events: Won",
+                curr: [1024, 1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                generate_tiles: false,
+                direction: Direction::Right,
+                want: vec![
+                    GameEvent::TilesMerged {
+                        index: 3,
+                        value: 2048,
+                    },
+                    GameEvent::ScoreChanged { score: 2048 },
+                    GameEvent::GameWon,
+                ],
+            },
+            TestCase {
+                name: "Events: Over",
+                curr: [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 0, 4, 2, 4],
+                generate_tiles: true,
+                direction: Direction::Left,
+                want: vec![
+                    GameEvent::TileSpawned {
+                        index: 15,
+                        value: 2,
+                    },
+                    GameEvent::GameOver,
+                ],
+            },
+            TestCase {
+                name: "Events: No Move",
+                curr: [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                generate_tiles: false,
+                direction: Direction::Left,
+                want: vec![],
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, t.generate_tiles);
+            gs.spawn = SpawnPolicy::OnlyTwos;
+
+            gs.move_tiles(t.direction);
+
+            assert_eq!(t.want, gs.drain_events(), "{}", t.name);
+            assert_eq!(Vec::<GameEvent>::new(), gs.drain_events(), "{}", t.name);
+        }
+
+        // Events nobody takes are dropped with the next move, and previews
+        // start without any.
+        let mut gs = GameState::new(
+            to_grid(&[2, 2, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 8, 0, 0, 0]),
+            false,
+        );
+        gs.move_tiles(Direction::Left);
+        let preview = gs.preview_move(Direction::Right).unwrap();
+        assert_eq!(
+            Vec::<GameEvent>::new(),
+            preview.clone().drain_events(),
+            "Events: Preview"
+        );
+        gs.move_tiles(Direction::Up);
+        assert_eq!(
+            vec![
+                GameEvent::TilesMerged {
+                    index: 4,
+                    value: 16
+                },
+                GameEvent::ScoreChanged { score: 20 },
+            ],
+            gs.drain_events(),
+            "Events: Only The Last Move"
+        );
+    }
+
+    #[test]
+    fn test_move_result() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            combo: usize,
+            direction: Direction,
+            want: MoveResult,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Move Result: Merges",
+                curr: [2, 2, 4, 4, 0, 0, 0, 0, 8, 0, 8, 0, 0, 0, 0, 0],
+                combo: 0,
+                direction: Direction::Left,
+                want: MoveResult {
+                    moved: true,
+                    direction: Some(Direction::Left),
+                    score_gained: 4 + 8 + 16,
+                    merges: vec![
+                        Merge { index: 0, value: 4 },
+                        Merge { index: 1, value: 8 },
+                        Merge {
+                            index: 8,
+                            value: 16,
+                        },
+                    ],
+                    spawned: None,
+                },
+            },
+            TestCase {
+                name: "Move Result: Combo Bonus",
+                curr: [0, 0, 32, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo: 1,
+                direction: Direction::Right,
+                want: MoveResult {
+                    moved: true,
+                    direction: Some(Direction::Right),
+                    score_gained: 70,
+                    merges: vec![Merge {
+                        index: 3,
+                        value: 64,
+                    }],
+                    spawned: None,
+                },
+            },
+            TestCase {
+                name: "Move Result: Slide Only",
+                curr: [0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo: 0,
+                direction: Direction::Left,
+                want: MoveResult {
+                    moved: true,
+                    direction: Some(Direction::Left),
+                    ..MoveResult::default()
+                },
+            },
+            TestCase {
+                name: "Move Result: No Move",
+                curr: [2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                combo: 0,
+                direction: Direction::Left,
+                want: MoveResult::default(),
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.set_combo_scoring(true);
+            gs.combo = t.combo;
+
+            assert_eq!(t.want, gs.move_tiles(t.direction), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_target() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            target: Option<usize>,
+            want_won: bool,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Target: Classic",
+                curr: [1024, 1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                target: default_target(),
+                want_won: true,
+            },
+            TestCase {
+                name: "Target: Quick Game",
+                curr: [512, 512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                target: Some(1024),
+                want_won: true,
+            },
+            TestCase {
+                name: "Target: Marathon",
+                curr: [1024, 1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                target: Some(8192),
+                want_won: false,
+            },
+            TestCase {
+                name: "Target: None",
+                curr: [4096, 4096, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                target: None,
+                want_won: false,
+            },
+        ];
+
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.set_target(t.target);
+            gs.move_tiles(Direction::Left);
+
+            assert_eq!(t.want_won, gs.is_won(), "{}", t.name);
+        }
     }
 
-    pub fn get_state(&self) -> String {
-        match self.state {
-            TileState::New => String::from(" tile-new"),
-            TileState::Merged => String::from(" tile-merged"),
-            TileState::Static => String::new(),
+    #[test]
+    fn test_score_target() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            score_target: Option<usize>,
+            want_won: bool,
         }
-    }
-}
 
-impl PartialEq for Tile {
-    fn eq(&self, other: &Tile) -> bool {
-        self.value == other.value
-    }
-}
+        let tests = [
+            TestCase {
+                name: "Score Target: Reached",
+                curr: [8, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                score_target: Some(16),
+                want_won: true,
+            },
+            TestCase {
+                name: "Score Target: Passed",
+                curr: [64, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                score_target: Some(100),
+                want_won: true,
+            },
+            TestCase {
+                name: "Score Target: Below",
+                curr: [8, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                score_target: Some(20),
+                want_won: false,
+            },
+            TestCase {
+                name: "Score Target: None",
+                curr: [512, 512, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                score_target: None,
+                want_won: false,
+            },
+        ];
 
-#[derive(Debug, Copy, Clone)]
-pub enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, false);
+            gs.set_target(None);
+            gs.set_score_target(t.score_target);
+            gs.move_tiles(Direction::Left);
 
-impl Direction {
-    fn increment(self) -> (i32, i32, i32) {
-        match self {
-            Direction::Left => (0, 1, 0),
-            Direction::Right => (15, -1, 0),
-            Direction::Up => (0, 4, 1),
-            Direction::Down => (15, -4, -1),
+            assert_eq!(t.want_won, gs.is_won(), "{}", t.name);
+            assert_eq!(
+                t.want_won,
+                gs.drain_events().contains(&GameEvent::GameWon),
+                "{}: Event",
+                t.name
+            );
         }
     }
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct GameState {
-    grid: [Option<Tile>; 16],
-    score: usize,
-    over: bool,
-    won: bool,
-    generate_tiles: bool,
-}
 
-impl GameState {
-    fn new(grid: [Option<Tile>; 16], generate_tiles: bool) -> GameState {
-        GameState {
-            grid,
-            score: 0,
-            over: false,
-            won: false,
-            generate_tiles: generate_tiles,
+    #[test]
+    fn test_move_limit() {
+        struct TestCase<'a> {
+            name: &'a str,
+            limit: Option<usize>,
+            moves: Vec<Direction>,
+            want_moves_left: Option<usize>,
+            want_over: bool,
+            want_move_count: usize,
         }
-    }
 
-    fn is_game_over(&mut self) -> bool {
-        self.over || self.won
-    }
+        // The lone tile goes back and forth, every move changes the board.
+        let back_and_forth = |moves| {
+            [Direction::Right, Direction::Left]
+                .iter()
+                .copied()
+                .cycle()
+                .take(moves)
+                .collect()
+        };
+        let tests = [
+            TestCase {
+                name: "Move Limit: Budget Left",
+                limit: Some(3),
+                moves: back_and_forth(2),
+                want_moves_left: Some(1),
+                want_over: false,
+                want_move_count: 2,
+            },
+            TestCase {
+                name: "Move Limit: Budget Runs Out",
+                limit: Some(3),
+                moves: back_and_forth(3),
+                want_moves_left: Some(0),
+                want_over: true,
+                want_move_count: 3,
+            },
+            TestCase {
+                name: "Move Limit: Moves Past The Limit",
+                limit: Some(3),
+                moves: back_and_forth(5),
+                want_moves_left: Some(0),
+                want_over: true,
+                want_move_count: 3,
+            },
+            TestCase {
+                name: "Move Limit: Moves That Don't Count",
+                limit: Some(3),
+                moves: vec![Direction::Left, Direction::Up, Direction::Right],
+                want_moves_left: Some(2),
+                want_over: false,
+                want_move_count: 1,
+            },
+            TestCase {
+                name: "Move Limit: None",
+                limit: None,
+                moves: back_and_forth(5),
+                want_moves_left: None,
+                want_over: false,
+                want_move_count: 5,
+            },
+        ];
 
-    pub fn add_random_tile(&mut self) {
-        if !self.generate_tiles {
-            return;
+        for t in tests {
+            let curr = to_grid(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            let mut gs = GameState::new(curr, false);
+            gs.set_move_limit(t.limit);
+            let mut events = Vec::new();
+            for &direction in &t.moves {
+                gs.move_tiles(direction);
+                events.extend(gs.drain_events());
+            }
+
+            assert_eq!(t.want_moves_left, gs.moves_left(), "{}", t.name);
+            assert_eq!(t.want_move_count, gs.move_count(), "{}", t.name);
+            assert_eq!(
+                t.want_over,
+                events.contains(&GameEvent::GameOver),
+                "{}: Game Over",
+                t.name
+            );
+            assert_eq!(
+                !t.want_over,
+                gs.move_tiles(Direction::Down).moved,
+                "{}: Finished",
+                t.name
+            );
         }
+    }
 
-        let mut rng = thread_rng();
+    #[test]
+    fn test_variant() {
+        let gs = GameState::with_variant(Variant::Obstacles(3), SpawnPolicy::Classic);
+        let obstacles = gs.grid.iter().flatten().filter(|t| t.is_obstacle()).count();
 
-        let grid_empty = self.grid.iter_mut().filter(|tile| tile.is_none());
+        assert_eq!(3, obstacles, "Variant: Obstacles");
+        assert_eq!(5, gs.grid.iter().flatten().count(), "Variant: Obstacles");
 
-        if let Some(empty) = grid_empty.choose(&mut rng) {
-            let value = match rng.gen::<f64>() {
-                x if x > 0.9 => 4,
-                _ => 2,
-            };
+        let gs = GameState::with_variant(Variant::Threes, SpawnPolicy::TwoFourEight);
+        assert_eq!(SpawnPolicy::Threes, gs.spawn, "Variant: Threes");
 
-            *empty = Some(Tile::new(value));
+        let mut a = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 7);
+        let mut b = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 7);
+        for direction in [Direction::Left, Direction::Up, Direction::Right] {
+            a.move_tiles(direction);
+            b.move_tiles(direction);
         }
+        assert_eq!(a.grid, b.grid, "Variant: Same Seed, Same Spawns");
     }
 
-    fn prepare_move(&mut self) {
-        for i in 0..16 {
-            self.grid
-                .get_mut(i)
-                .and_then(|tile| tile.as_mut())
-                .map(|tile| {
-                    tile.state = TileState::New;
-                    tile.prev_pos = Some(i);
-                });
+    #[test]
+    fn test_verify_replay() {
+        let mut game_state = GameState::with_seed(Variant::Classic, SpawnPolicy::Classic, 42);
+        game_state.set_combo_scoring(true);
+        for direction in [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+        ] {
+            game_state.move_tiles(direction);
         }
+        let mut actions = game_state.history().to_vec();
+        let verified = verify_replay(Variant::Classic, SpawnPolicy::Classic, true, 42, &actions);
+        assert_eq!(Ok(game_state.final_state()), verified, "Verify: Same Game");
+
+        // No tile high enough for a power-up was ever reached.
+        actions.push(Action::PowerUp(PowerUp::Remove, vec![0]));
+        let unearned = verify_replay(Variant::Classic, SpawnPolicy::Classic, true, 42, &actions);
+        assert!(unearned.is_err(), "Verify: Power-Up Never Earned");
     }
 
-    pub fn move_tiles(&mut self, direction: Direction) {
-        if self.is_game_over() {
-            return;
+    #[test]
+    fn test_spawn_odds() {
+        struct TestCase<'a> {
+            name: &'a str,
+            spawn: SpawnPolicy,
+            score: usize,
+            want_four: f64,
         }
 
-        self.prepare_move();
-
-        let mut moved = false;
-        let mut index = direction.increment().0;
-
-        for _ in 0..4 {
-            let mut next = index;
-
-            for _ in 0..4 {
-                if let Some(mut curr_tile) = self.grid[index as usize] {
-                    let mut moved_tile = false;
-                    let prev = next - direction.increment().1;
-
-                    if prev >= 0 && prev < 16 {
-                        if let Some(mut merge_tile) = self.grid[prev as usize] {
-                            if merge_tile.state != TileState::Merged && merge_tile == curr_tile {
-                                merge_tile.update(merge_tile.value * 2, TileState::Merged);
-
-                                self.grid[prev as usize] = Some(merge_tile);
-                                self.grid[index as usize] = None;
-                                moved_tile = true;
-
-                                self.score += merge_tile.value;
-                                if merge_tile.value == 2048 {
-                                    self.won = true;
-                                }
-                            }
-                        }
-                    }
-
-                    if !moved_tile {
-                        if index == next {
-                            curr_tile.update(curr_tile.value, TileState::Static);
-                            self.grid[index as usize] = Some(curr_tile);
+        let tests = [
+            TestCase {
+                name: "Spawn: Classic",
+                spawn: SpawnPolicy::Classic,
+                score: 0,
+                want_four: 0.1,
+            },
+            TestCase {
+                name: "Spawn: Only Twos",
+                spawn: SpawnPolicy::OnlyTwos,
+                score: 0,
+                want_four: 0.0,
+            },
+            TestCase {
+                name: "Spawn: 2/4/8",
+                spawn: SpawnPolicy::TwoFourEight,
+                score: 0,
+                want_four: 0.15,
+            },
+            TestCase {
+                name: "Spawn: Curve Start",
+                spawn: SpawnPolicy::Curve,
+                score: 0,
+                want_four: 0.1,
+            },
+            TestCase {
+                name: "Spawn: Curve Halfway",
+                spawn: SpawnPolicy::Curve,
+                score: 10000,
+                want_four: 0.3,
+            },
+            TestCase {
+                name: "Spawn: Curve End",
+                spawn: SpawnPolicy::Curve,
+                score: 50000,
+                want_four: 0.5,
+            },
+        ];
 
-                            next += direction.increment().1;
-                        } else {
-                            curr_tile.update(curr_tile.value, TileState::Static);
+        for t in tests {
+            let odds = t.spawn.odds(t.score);
+            let total: f64 = odds.iter().map(|(_, chance)| chance).sum();
+            let four = odds
+                .iter()
+                .find(|(value, _)| *value == 4)
+                .map_or(0.0, |(_, chance)| *chance);
 
-                            self.grid[next as usize] = Some(curr_tile);
-                            self.grid[index as usize] = None;
-                            moved_tile = true;
+            assert!((total - 1.0).abs() < 1e-9, "{}", t.name);
+            assert!((t.want_four - four).abs() < 1e-9, "{}", t.name);
+        }
+    }
 
-                            next += direction.increment().1;
-                        }
-                    }
+    #[test]
+    fn test_injected_rng() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            variant: Variant,
+            want: [usize; 16],
+        }
 
-                    moved |= moved_tile;
-                }
+        let tests = [
+            TestCase {
+                name: "Injected RNG: First Cell",
+                curr: [2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                variant: Variant::Classic,
+                want: [2, 4, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+            TestCase {
+                name: "Injected RNG: Bomb",
+                curr: [0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                variant: Variant::Bombs,
+                want: [B, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            },
+        ];
 
-                index += direction.increment().1;
-            }
+        for t in tests {
+            let mut gs = GameState::with_variant(t.variant, SpawnPolicy::Classic);
+            gs.grid = to_grid(&t.curr);
+            let rng = gs.rng;
 
-            index = (index + direction.increment().2 + 16) % 16;
-        }
+            // Every draw is 0: the likeliest value in the first empty cell.
+            gs.add_random_tile_with(&mut StepRng::new(0, 0));
 
-        if moved {
-            self.add_random_tile();
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(rng, gs.rng, "{}", t.name);
         }
     }
 
-    pub fn get_tiles(&self) -> impl Iterator<Item = (usize, Tile)> + '_ {
-        self.grid
-            .iter()
-            .enumerate()
-            .filter_map(|(i, t)| match t {
-                None => None,
-                Some(tile) => Some((i, *tile)),
-            })
-            .flat_map(|(i, tile)| match tile.state {
-                TileState::Merged => vec![
-                    (
-                        i,
-                        Tile {
-                            value: tile.value / 2,
-                            state: TileState::Static,
-                            prev_pos: tile.prev_pos,
-                        },
-                    ),
-                    (i, tile),
-                ],
-                _ => vec![(i, tile)],
-            })
-    }
-}
-
-impl Default for GameState {
-    fn default() -> Self {
-        let mut game_state = GameState::new([None; 16], true);
-        for _ in 0..2 {
-            game_state.add_random_tile();
+    #[test]
+    fn test_evil_spawn() {
+        struct TestCase<'a> {
+            name: &'a str,
+            curr: [usize; 16],
+            want: [usize; 16],
         }
-        game_state
-    }
-}
 
-impl PartialEq for GameState {
-    fn eq(&self, other: &GameState) -> bool {
-        self.grid == other.grid
-    }
-}
+        let tests = [
+            TestCase {
+                name: "Evil: Ends The Game",
+                curr: [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 0],
+                want: [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 2, 4, 2],
+            },
+            TestCase {
+                name: "Evil: Blocks The Merge",
+                curr: [2, 0, 2, 4, 4, 8, 16, 32, 8, 16, 32, 64, 16, 32, 64, 128],
+                want: [2, 4, 2, 4, 4, 8, 16, 32, 8, 16, 32, 64, 16, 32, 64, 128],
+            },
+        ];
 
-#[cfg(test)]
-mod tests {
-    use crate::game_state::{Direction, GameState, Tile};
+        for t in tests {
+            let curr = to_grid(&t.curr);
+            let mut gs = GameState::new(curr, true);
+            gs.spawn = SpawnPolicy::Evil;
 
-    fn to_grid(from: [usize; 16]) -> [Option<Tile>; 16] {
-        let mut to = [None; 16];
-        for i in 0..from.len() {
-            if from[i] != 0 {
-                to[i].insert(Tile::new(from[i]));
-            }
-        }
-        to
-    }
+            gs.add_random_tile();
 
-    fn from_grid(from: [Option<Tile>; 16]) -> [usize; 16] {
-        let mut to = [0; 16];
-        for i in 0..from.len() {
-            if let Some(tile) = from[i] {
-                to[i] = tile.value;
-            }
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
         }
-        to
     }
 
     #[test]
-    fn test_basic() {
+    fn test_spawn_cells() {
         struct TestCase<'a> {
             name: &'a str,
-            curr: [usize; 16],
-            want: [usize; 16],
-            moves: Vec<Direction>,
+            spawn: SpawnPolicy,
+            generate_tiles: bool,
+            want: Vec<(usize, f64, f64)>,
         }
 
+        let curr = [2, 4, 2, 4, 4, 2, 4, 2, 2, 4, 2, 4, 4, 0, 4, 0];
         let tests = [
             TestCase {
-                name: "Basic: Left",
-                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
-                want: [2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0],
-                moves: vec![Direction::Left],
+                name: "Spawn Cells: Classic",
+                spawn: SpawnPolicy::Classic,
+                generate_tiles: true,
+                want: vec![(13, 0.5, 2.2), (15, 0.5, 2.2)],
             },
             TestCase {
-                name: "Basic: Right",
-                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
-                want: [0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2],
-                moves: vec![Direction::Right],
+                name: "Spawn Cells: Only Twos",
+                spawn: SpawnPolicy::OnlyTwos,
+                generate_tiles: true,
+                want: vec![(13, 0.5, 2.0), (15, 0.5, 2.0)],
             },
             TestCase {
-                name: "Basic: Up",
-                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
-                want: [2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                moves: vec![Direction::Up],
+                name: "Spawn Cells: Evil",
+                spawn: SpawnPolicy::Evil,
+                generate_tiles: true,
+                want: vec![(13, 1.0, 2.0)],
             },
             TestCase {
-                name: "Basic: Down",
-                curr: [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2],
-                want: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2],
-                moves: vec![Direction::Down],
+                name: "Spawn Cells: No Spawns",
+                spawn: SpawnPolicy::Classic,
+                generate_tiles: false,
+                want: vec![],
             },
         ];
 
         for t in tests {
-            let curr = to_grid(t.curr);
-            let mut gs = GameState::new(curr, false);
+            let mut gs = GameState::new(to_grid(&curr), t.generate_tiles);
+            gs.spawn = t.spawn;
+            let got = gs.spawn_odds();
 
-            for d in &t.moves {
-                gs.move_tiles(*d);
+            assert_eq!(t.want.len(), got.len(), "{}", t.name);
+            for (want, got) in t.want.iter().zip(&got) {
+                assert_eq!(want.0, got.0, "{}", t.name);
+                assert!((want.1 - got.1).abs() < 1e-9, "{}", t.name);
+                assert!((want.2 - got.2).abs() < 1e-9, "{}", t.name);
             }
-
-            assert_eq!(t.want, from_grid(gs.grid), "{}", t.name);
         }
     }
 
     #[test]
-    fn test_merge() {
+    fn test_power_ups() {
         struct TestCase<'a> {
             name: &'a str,
             curr: [usize; 16],
+            power_up: PowerUp,
+            targets: Vec<usize>,
             want: [usize; 16],
-            moves: Vec<Direction>,
+            want_used: bool,
+            want_won: bool,
         }
 
         let tests = [
             TestCase {
-                name: "Merge: Basic Merge",
-                curr: [2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                want: [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                moves: vec![Direction::Left],
+                name: "Power-Up: Remove",
+                curr: [64, 64, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Remove,
+                targets: vec![1],
+                want: [128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: true,
+                want_won: false,
             },
             TestCase {
-                name: "Merge: Not Merge",
-                curr: [2, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0],
-                want: [2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                moves: vec![Direction::Up],
+                name: "Power-Up: Not Owned",
+                curr: [64, 64, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Swap,
+                targets: vec![0, 1],
+                want: [128, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: false,
+                want_won: false,
             },
             TestCase {
-                name: "Merge: Merge Twice",
-                curr: [2, 2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                want: [0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                moves: vec![Direction::Left, Direction::Right],
+                name: "Power-Up: Swap",
+                curr: [128, 128, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Swap,
+                targets: vec![1, 4],
+                want: [256, 4, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: true,
+                want_won: false,
             },
             TestCase {
-                name: "Merge: Merge Twos",
-                curr: [2, 2, 2, 2, 0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 0, 2],
-                want: [4, 4, 0, 0, 4, 2, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0],
-                moves: vec![Direction::Left],
+                name: "Power-Up: Swap Needs Two Tiles",
+                curr: [128, 128, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Swap,
+                targets: vec![1, 5],
+                want: [256, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: false,
+                want_won: false,
+            },
+            TestCase {
+                name: "Power-Up: Double",
+                curr: [256, 256, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Double,
+                targets: vec![1],
+                want: [512, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: true,
+                want_won: false,
+            },
+            TestCase {
+                name: "Power-Up: Double To The Target",
+                curr: [256, 256, 1024, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                power_up: PowerUp::Double,
+                targets: vec![1],
+                want: [512, 2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                want_used: true,
+                want_won: true,
             },
         ];
 
         for t in tests {
-            let curr = to_grid(t.curr);
+            let curr = to_grid(&t.curr);
             let mut gs = GameState::new(curr, false);
+            gs.move_tiles(Direction::Left);
+            gs.drain_events();
 
-            for d in &t.moves {
-                gs.move_tiles(*d);
-            }
+            let used = gs.use_power_up(t.power_up, &t.targets);
+
+            assert_eq!(t.want_used, used, "{}", t.name);
+            assert_eq!(t.want.to_vec(), from_grid(&gs.grid), "{}", t.name);
+            assert_eq!(t.want_won, gs.is_won(), "{}: Won", t.name);
+            assert_eq!(
+                t.want_won,
+                gs.drain_events().contains(&GameEvent::GameWon),
+                "{}: Won Event",
+                t.name
+            );
+            assert_eq!(
+                t.want_used,
+                gs.history.last() == Some(&Action::PowerUp(t.power_up, t.targets)),
+                "{}",
+                t.name
+            );
+        }
+    }
 
-            assert_eq!(t.want, from_grid(gs.grid), "{}", t.name);
+    #[test]
+    fn test_shuffle() {
+        let curr = to_grid(&[2, 4, 8, 0, X, 0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 2]);
+        let mut gs = GameState::new(curr, false);
+
+        for want in [true, true, false] {
+            let before = from_grid(&gs.grid);
+            assert_eq!(want, gs.shuffle(), "Shuffle: Uses");
+
+            let mut after = from_grid(&gs.grid);
+            let mut before_sorted = before.clone();
+            after.sort_unstable();
+            before_sorted.sort_unstable();
+
+            assert_eq!(before_sorted, after, "Shuffle: Same Values");
+            assert_eq!(X, from_grid(&gs.grid)[4], "Shuffle: Obstacle Stays");
+        }
+        assert_eq!(0, gs.shuffles_left(), "Shuffle: None Left");
+
+        // The same seed shuffles the same way.
+        let mut a = GameState::new(
+            to_grid(&[2, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            false,
+        );
+        let mut b = a.clone();
+        a.shuffle();
+        b.shuffle();
+        assert_eq!(from_grid(&a.grid), from_grid(&b.grid), "Shuffle: Seeded");
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut gs = GameState::with_variant(Variant::Classic, SpawnPolicy::Classic);
+        let mut frames = Vec::new();
+        assert!(!gs.replay(|_| ()), "Replay: Nothing Played");
+
+        for &direction in Direction::ALL.iter().cycle().take(40) {
+            gs.move_tiles(direction);
         }
+        gs.shuffle();
+        for &direction in Direction::ALL.iter().take(4) {
+            gs.move_tiles(direction);
+        }
+
+        assert!(
+            gs.replay(|frame| frames.push((from_grid(&frame.grid), frame.score))),
+            "Replay: Played"
+        );
+        assert_eq!(gs.history.len() + 1, frames.len(), "Replay: Frames");
+        assert_eq!(
+            Some(&(from_grid(&gs.grid), gs.score)),
+            frames.last(),
+            "Replay: Same Ending"
+        );
+
+        let steps = gs.steps();
+        assert_eq!(
+            gs.history,
+            steps
+                .iter()
+                .map(|step| step.action.clone())
+                .collect::<Vec<_>>(),
+            "Replay: Steps"
+        );
+        assert_eq!(
+            gs.score,
+            steps.iter().map(|step| step.score_gained).sum::<usize>(),
+            "Replay: Steps Score"
+        );
+        assert!(
+            steps
+                .iter()
+                .all(|step| step.score_gained == 0 || step.merges > 0),
+            "Replay: Steps Merges"
+        );
+        assert!(
+            steps
+                .iter()
+                .all(|step| step.spawned.is_some() == matches!(step.action, Action::Move(_))),
+            "Replay: Steps Spawns"
+        );
+
+        // Editing the board starts the history over.
+        gs.set_value(0, 2);
+        assert!(!gs.replay(|_| ()), "Replay: Edited");
     }
 
     #[test]
@@ -362,6 +4223,7 @@ mod tests {
             name: &'a str,
             curr: [usize; 16],
             want: usize,
+            want_moved: bool,
             moves: Vec<Direction>,
         }
 
@@ -370,24 +4232,32 @@ mod tests {
                 name: "Random Tile: Valid Move",
                 curr: [0, 0, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0],
                 want: 3,
+                want_moved: true,
                 moves: vec![Direction::Left],
             },
             TestCase {
                 name: "Random Tile: Invalid Move",
                 curr: [0, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0],
                 want: 2,
+                want_moved: false,
                 moves: vec![Direction::Left],
             },
         ];
 
         for t in tests {
-            let curr = to_grid(t.curr);
+            let curr = to_grid(&t.curr);
             let mut gs = GameState::new(curr, true);
 
+            let mut moved = false;
             for d in &t.moves {
-                gs.move_tiles(*d);
+                let result = gs.move_tiles(*d);
+                if let Some((index, value)) = result.spawned {
+                    assert_eq!(Some(value), gs.grid[index].map(|tile| tile.get_value()));
+                }
+                moved |= result.moved;
             }
 
+            assert_eq!(t.want_moved, moved, "{}", t.name);
             assert_eq!(
                 t.want,
                 gs.grid.iter().filter(|x| x.is_some()).count(),
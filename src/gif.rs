@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+// Palette indices are a byte, so codes start one bit wider.
+const MIN_CODE_SIZE: u8 = 8;
+const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+const END_CODE: u16 = CLEAR_CODE + 1;
+// GIF codes are at most 12 bits long.
+const MAX_CODES: u16 = 1 << 12;
+
+// An animated GIF that loops forever. Every frame brings its own palette of
+// its 256 most common colors, the others are drawn in the closest of those.
+pub struct Gif {
+    width: u16,
+    height: u16,
+    bytes: Vec<u8>,
+}
+
+impl Gif {
+    pub fn new(width: u16, height: u16) -> Gif {
+        let mut bytes = b"GIF89a".to_vec();
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        // No global palette, background color and aspect ratio.
+        bytes.extend_from_slice(&[0, 0, 0]);
+        // Netscape's extension, looping forever.
+        bytes.extend_from_slice(&[0x21, 0xff, 11]);
+        bytes.extend_from_slice(b"NETSCAPE2.0");
+        bytes.extend_from_slice(&[3, 1, 0, 0, 0]);
+
+        Gif {
+            width,
+            height,
+            bytes,
+        }
+    }
+
+    // Adds a frame of RGBA pixels, shown for `delay` hundredths of a second.
+    // Transparent pixels end up white.
+    pub fn add_frame(&mut self, rgba: &[u8], delay: u16) {
+        let (palette, indices) = quantize(rgba);
+
+        self.bytes.extend_from_slice(&[0x21, 0xf9, 4, 0]);
+        self.bytes.extend_from_slice(&delay.to_le_bytes());
+        self.bytes.extend_from_slice(&[0, 0]);
+
+        self.bytes.extend_from_slice(&[0x2c, 0, 0, 0, 0]);
+        self.bytes.extend_from_slice(&self.width.to_le_bytes());
+        self.bytes.extend_from_slice(&self.height.to_le_bytes());
+        // A local palette of 256 colors.
+        self.bytes.push(0x87);
+        for i in 0..256 {
+            let color = palette.get(i).copied().unwrap_or_default();
+            self.bytes.extend_from_slice(&color);
+        }
+
+        self.bytes.push(MIN_CODE_SIZE);
+        for block in lzw(&indices).chunks(255) {
+            self.bytes.push(block.len() as u8);
+            self.bytes.extend_from_slice(block);
+        }
+        self.bytes.push(0);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.push(0x3b);
+        self.bytes
+    }
+}
+
+fn on_white(pixel: &[u8]) -> [u8; 3] {
+    let alpha = pixel[3] as u32;
+    let blend = |channel: u8| ((channel as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+    [blend(pixel[0]), blend(pixel[1]), blend(pixel[2])]
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> i32 {
+    a.iter()
+        .zip(&b)
+        .map(|(&a, &b)| (a as i32 - b as i32).pow(2))
+        .sum()
+}
+
+// Picks the palette of a frame and the palette index of each pixel.
+fn quantize(rgba: &[u8]) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let pixels: Vec<[u8; 3]> = rgba.chunks_exact(4).map(on_white).collect();
+
+    let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+    for &pixel in &pixels {
+        *counts.entry(pixel).or_insert(0) += 1;
+    }
+    let mut colors: Vec<([u8; 3], usize)> = counts.into_iter().collect();
+    colors.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let palette: Vec<[u8; 3]> = colors.iter().take(256).map(|&(color, _)| color).collect();
+
+    let mut index: HashMap<[u8; 3], u8> = palette
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| (color, i as u8))
+        .collect();
+    let indices = pixels
+        .iter()
+        .map(|&pixel| {
+            *index.entry(pixel).or_insert_with(|| {
+                (0..palette.len())
+                    .min_by_key(|&i| distance(palette[i], pixel))
+                    .unwrap_or(0) as u8
+            })
+        })
+        .collect();
+
+    (palette, indices)
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.bits |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+// Compresses palette indices the way GIF decoders expect: codes grow a bit
+// once the decoder's table needs it, and the table starts over when full.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = END_CODE + 1;
+    let mut size = MIN_CODE_SIZE as u32 + 1;
+    writer.write(CLEAR_CODE, size);
+
+    let mut indices = indices.iter().copied();
+    let mut prefix = match indices.next() {
+        Some(index) => index as u16,
+        None => {
+            writer.write(END_CODE, size);
+            return writer.finish();
+        }
+    };
+
+    for index in indices {
+        if let Some(&code) = codes.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix, size);
+        if next == MAX_CODES {
+            writer.write(CLEAR_CODE, size);
+            codes.clear();
+            next = END_CODE + 1;
+            size = MIN_CODE_SIZE as u32 + 1;
+        } else {
+            codes.insert((prefix, index), next);
+            next += 1;
+            // The decoder adds its entries one code later.
+            if next - 1 == 1 << size {
+                size += 1;
+            }
+        }
+        prefix = index as u16;
+    }
+
+    writer.write(prefix, size);
+    if next == 1 << size && size < 12 {
+        size += 1;
+    }
+    writer.write(END_CODE, size);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gif::{lzw, quantize, Gif};
+
+    // A plain GIF decoder, to check the encoder against.
+    fn unlzw(bytes: &[u8]) -> Vec<u8> {
+        let mut position = 0;
+        let mut read = |size: usize| {
+            let mut code = 0;
+            for bit in 0..size {
+                let byte = bytes[(position + bit) / 8];
+                code |= (((byte >> ((position + bit) % 8)) & 1) as usize) << bit;
+            }
+            position += size;
+            code
+        };
+
+        let mut table: Vec<Vec<u8>> = (0..258).map(|i| vec![i as u8]).collect();
+        let mut size = 9;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            let code = read(size);
+            if code == 256 {
+                table.truncate(258);
+                size = 9;
+                previous = None;
+                continue;
+            }
+            if code == 257 {
+                return out;
+            }
+
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("Unknown first code {}", code),
+            };
+            if let Some(previous) = previous {
+                if table.len() < 4096 {
+                    table.push([previous, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+
+            out.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        struct TestCase<'a> {
+            name: &'a str,
+            indices: Vec<u8>,
+        }
+
+        let mut noise = 12345u32;
+        let tests = [
+            TestCase {
+                name: "LZW: Empty",
+                indices: vec![],
+            },
+            TestCase {
+                name: "LZW: Single",
+                indices: vec![7],
+            },
+            TestCase {
+                name: "LZW: Runs",
+                indices: [vec![0; 3000], vec![255; 3000], vec![3; 10]].concat(),
+            },
+            TestCase {
+                name: "LZW: Table Fills Up",
+                indices: (0..20_000)
+                    .map(|_| {
+                        noise = noise.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        (noise >> 16) as u8
+                    })
+                    .collect(),
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.indices, unlzw(&lzw(&t.indices)), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_gif() {
+        let red = [255, 0, 0, 255];
+        let clear = [0, 0, 0, 0];
+        let almost_red = [250, 0, 0, 255];
+        let pixels = [red, clear, almost_red, red].concat();
+
+        let (palette, indices) = quantize(&pixels);
+        assert_eq!(vec![[255, 0, 0], [250, 0, 0], [255, 255, 255]], palette);
+        assert_eq!(vec![0, 2, 1, 0], indices);
+
+        let mut gif = Gif::new(2, 2);
+        gif.add_frame(&pixels, 50);
+        let bytes = gif.finish();
+        assert_eq!(b"GIF89a", &bytes[..6]);
+        assert_eq!(Some(&0x3b), bytes.last());
+    }
+}
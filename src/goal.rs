@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::game_state::GameState;
+
+// Something to reach on the board, checked after every move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Goal {
+    Tile(usize),
+    Score(usize),
+}
+
+impl Goal {
+    pub fn is_reached(self, game_state: &GameState) -> bool {
+        match self {
+            Goal::Tile(value) => game_state.max_tile() >= value,
+            Goal::Score(score) => game_state.score() >= score,
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Goal::Tile(value) => write!(f, "Make a {}", value),
+            Goal::Score(score) => write!(f, "Score {}", score),
+        }
+    }
+}
+
+// The goals of the ladder mode, one after the other on the same board.
+pub const LADDER: [Goal; 7] = [
+    Goal::Tile(128),
+    Goal::Tile(512),
+    Goal::Score(10_000),
+    Goal::Tile(2048),
+    Goal::Score(50_000),
+    Goal::Tile(4096),
+    Goal::Score(100_000),
+];
+
+// Index of the first goal from `current` on that isn't reached yet, so a
+// single move can climb several rungs at once.
+pub fn climb(goals: &[Goal], current: usize, game_state: &GameState) -> usize {
+    current
+        + goals
+            .iter()
+            .skip(current)
+            .take_while(|goal| goal.is_reached(game_state))
+            .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::goal::{climb, Goal};
+
+    #[test]
+    fn test_climb() {
+        struct TestCase<'a> {
+            name: &'a str,
+            grid: [usize; 16],
+            current: usize,
+            want: usize,
+        }
+
+        let goals = [Goal::Tile(8), Goal::Tile(16), Goal::Tile(64)];
+
+        let tests = [
+            TestCase {
+                name: "Climb: Nothing Reached",
+                grid: [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                current: 0,
+                want: 0,
+            },
+            TestCase {
+                name: "Climb: One Rung",
+                grid: [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                current: 0,
+                want: 1,
+            },
+            TestCase {
+                name: "Climb: Several Rungs",
+                grid: [32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                current: 0,
+                want: 2,
+            },
+            TestCase {
+                name: "Climb: Top",
+                grid: [64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                current: 2,
+                want: 3,
+            },
+        ];
+
+        for t in tests {
+            let gs = GameState::from_values(&t.grid);
+
+            assert_eq!(t.want, climb(&goals, t.current, &gs), "{}", t.name);
+        }
+    }
+}
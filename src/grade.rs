@@ -0,0 +1,217 @@
+use crate::ai::{hint_move, minimax_value, HINT_PLIES};
+use crate::evaluate::{evaluate, Weights};
+use crate::game_state::{Direction, GameState, SpawnPolicy};
+use crate::transposition::TranspositionTable;
+
+// How much worse than after the best move the board may evaluate and the
+// move still count as good, or only as inaccurate. A free cell is worth 2.7
+// with the default weights.
+const GOOD_LOSS: f64 = 1.0;
+const INACCURACY_LOSS: f64 = 3.0;
+// Each this much of loss takes a move's accuracy down to about a third.
+const ACCURACY_SCALE: f64 = 3.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Grade {
+    Best,
+    Good,
+    Inaccuracy,
+    Blunder,
+}
+
+impl Grade {
+    pub fn label(self) -> &'static str {
+        match self {
+            Grade::Best => "Best",
+            Grade::Good => "Good",
+            Grade::Inaccuracy => "Inaccuracy",
+            Grade::Blunder => "Blunder",
+        }
+    }
+
+    pub fn class(self) -> &'static str {
+        match self {
+            Grade::Best => "grade-best",
+            Grade::Good => "grade-good",
+            Grade::Inaccuracy => "grade-inaccuracy",
+            Grade::Blunder => "grade-blunder",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GradedMove {
+    pub grade: Grade,
+    // How much better the board would evaluate after the computer's move.
+    pub loss: f64,
+    // The computer's move, see `ai::hint_move`.
+    pub best: Direction,
+}
+
+// Grades the move against the one the computer would make on the board,
+// both judged before the next tile lands, on evil boards against the worst
+// tiles to come. None for moves that don't change the board and for the
+// only move there is, there was nothing to choose.
+pub fn grade_move(
+    game_state: &GameState,
+    direction: Direction,
+    weights: &Weights,
+) -> Option<GradedMove> {
+    game_state.legal_moves().nth(1)?;
+    let mut table = TranspositionTable::new(1 << 12);
+    let mut judge = |next: &GameState| match game_state.spawn_policy() {
+        SpawnPolicy::Evil => minimax_value(next, HINT_PLIES, weights, &mut table),
+        _ => evaluate(next, weights),
+    };
+    let value = judge(&game_state.preview_move(direction)?);
+    let best = hint_move(game_state, weights)?;
+    let best_value = judge(&game_state.preview_move(best)?);
+
+    let loss = (best_value - value).max(0.0);
+    let grade = if direction == best || loss == 0.0 {
+        Grade::Best
+    } else if loss <= GOOD_LOSS {
+        Grade::Good
+    } else if loss <= INACCURACY_LOSS {
+        Grade::Inaccuracy
+    } else {
+        Grade::Blunder
+    };
+    Some(GradedMove { grade, loss, best })
+}
+
+// The graded moves of a game so far.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Accuracy {
+    // The grade of the latest move, None if it had none.
+    pub last: Option<GradedMove>,
+    moves: usize,
+    credit: f64,
+}
+
+impl Accuracy {
+    pub fn record(&mut self, graded: Option<GradedMove>) {
+        self.last = graded;
+        if let Some(graded) = graded {
+            self.moves += 1;
+            self.credit += (-graded.loss / ACCURACY_SCALE).exp();
+        }
+    }
+
+    // From 0 to 100, the best move each time scoring 100. None until a move
+    // was graded.
+    pub fn percent(&self) -> Option<f64> {
+        (self.moves > 0).then(|| self.credit / self.moves as f64 * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluate::Weights;
+    use crate::game_state::{Direction, GameState, SpawnPolicy};
+    use crate::grade::{grade_move, Accuracy, Grade, GradedMove};
+
+    #[test]
+    fn test_grade_move() {
+        struct TestCase<'a> {
+            name: &'a str,
+            values: &'a [usize],
+            direction: Direction,
+            spawn: SpawnPolicy,
+            want: Option<Grade>,
+        }
+
+        let pair = &[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        // The big tiles are in the corner, pushing down drags them out.
+        let corner = &[64, 32, 16, 8, 32, 16, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        // Pushing right looks best before the next tile lands, not with the
+        // worst tiles to come.
+        let cornered = &[4, 8, 4, 8, 32, 2, 16, 0, 16, 16, 8, 4, 16, 16, 32, 0];
+        let tests = [
+            TestCase {
+                name: "Grade Move: Merge",
+                values: pair,
+                direction: Direction::Left,
+                spawn: SpawnPolicy::Classic,
+                want: Some(Grade::Best),
+            },
+            TestCase {
+                name: "Grade Move: Tied With The Best",
+                values: pair,
+                direction: Direction::Right,
+                spawn: SpawnPolicy::Classic,
+                want: Some(Grade::Best),
+            },
+            TestCase {
+                name: "Grade Move: Out Of The Corner",
+                values: corner,
+                direction: Direction::Down,
+                spawn: SpawnPolicy::Classic,
+                want: Some(Grade::Blunder),
+            },
+            TestCase {
+                name: "Grade Move: Doesn't Move",
+                values: pair,
+                direction: Direction::Up,
+                spawn: SpawnPolicy::Classic,
+                want: None,
+            },
+            TestCase {
+                name: "Grade Move: Forced",
+                values: &[0, 2, 4, 2, 0, 4, 2, 4, 0, 2, 4, 2, 0, 4, 2, 4],
+                direction: Direction::Left,
+                spawn: SpawnPolicy::Classic,
+                want: None,
+            },
+            TestCase {
+                name: "Grade Move: Greedy",
+                values: cornered,
+                direction: Direction::Right,
+                spawn: SpawnPolicy::Classic,
+                want: Some(Grade::Best),
+            },
+            TestCase {
+                name: "Grade Move: Against The Evil Spawner",
+                values: cornered,
+                direction: Direction::Right,
+                spawn: SpawnPolicy::Evil,
+                want: Some(Grade::Blunder),
+            },
+        ];
+
+        for t in tests {
+            let mut game_state = GameState::from_values(t.values);
+            game_state.set_spawn(t.spawn);
+            let got = grade_move(&game_state, t.direction, &Weights::default());
+            assert_eq!(t.want, got.map(|graded| graded.grade), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let mut accuracy = Accuracy::default();
+        assert_eq!(None, accuracy.percent(), "Accuracy: No Moves");
+
+        let best = GradedMove {
+            grade: Grade::Best,
+            loss: 0.0,
+            best: Direction::Left,
+        };
+        accuracy.record(Some(best));
+        accuracy.record(None);
+        assert_eq!(Some(100.0), accuracy.percent(), "Accuracy: Best");
+        assert_eq!(None, accuracy.last, "Accuracy: Forced Move");
+
+        accuracy.record(Some(GradedMove {
+            grade: Grade::Blunder,
+            loss: 100.0,
+            best: Direction::Up,
+        }));
+        assert!(
+            accuracy
+                .percent()
+                .is_some_and(|percent| percent > 49.0 && percent < 51.0),
+            "Accuracy: Blunder"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+// Two players taking turns on one board.
+pub const PLAYERS: usize = 2;
+
+// Whose move it is and what each player's moves scored.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct HotSeat {
+    scores: [usize; PLAYERS],
+    turn: usize,
+}
+
+impl HotSeat {
+    // Credits the player whose move it was and passes the turn on.
+    pub fn play(&mut self, score_gained: usize) {
+        self.scores[self.turn] += score_gained;
+        self.turn = (self.turn + 1) % PLAYERS;
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn scores(&self) -> [usize; PLAYERS] {
+        self.scores
+    }
+
+    // The player ahead, none on a draw.
+    pub fn leader(&self) -> Option<usize> {
+        let best = self.scores.iter().max()?;
+        let mut leaders = (0..PLAYERS).filter(|&player| self.scores[player] == *best);
+        match (leaders.next(), leaders.next()) {
+            (Some(player), None) => Some(player),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hot_seat::HotSeat;
+
+    #[test]
+    fn test_play() {
+        struct TestCase<'a> {
+            name: &'a str,
+            gains: &'a [usize],
+            want_scores: [usize; 2],
+            want_turn: usize,
+            want_leader: Option<usize>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Hot Seat: Start",
+                gains: &[],
+                want_scores: [0, 0],
+                want_turn: 0,
+                want_leader: None,
+            },
+            TestCase {
+                name: "Hot Seat: Second Ahead",
+                gains: &[4, 8, 0],
+                want_scores: [4, 8],
+                want_turn: 1,
+                want_leader: Some(1),
+            },
+            TestCase {
+                name: "Hot Seat: Draw",
+                gains: &[4, 0, 0, 4],
+                want_scores: [4, 4],
+                want_turn: 0,
+                want_leader: None,
+            },
+        ];
+
+        for t in tests {
+            let mut hot_seat = HotSeat::default();
+            for &gain in t.gains {
+                hot_seat.play(gain);
+            }
+            assert_eq!(t.want_scores, hot_seat.scores(), "{}: Scores", t.name);
+            assert_eq!(t.want_turn, hot_seat.turn(), "{}: Turn", t.name);
+            assert_eq!(t.want_leader, hot_seat.leader(), "{}: Leader", t.name);
+        }
+    }
+}
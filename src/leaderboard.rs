@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Entry {
+    pub score: usize,
+    pub best_tile: usize,
+}
+
+// The best results of one mode, highest score first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    const SIZE: usize = 10;
+
+    pub fn record(&mut self, entry: Entry) {
+        // Later results go after earlier ones with the same score.
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(Leaderboard::SIZE);
+    }
+
+    // Runs of `other` this one already has are only counted once.
+    pub fn merge(&self, other: &Leaderboard) -> Leaderboard {
+        let mut merged = self.clone();
+        for &entry in &other.entries {
+            if !self.entries.contains(&entry) {
+                merged.record(entry);
+            }
+        }
+        merged
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leaderboard::{Entry, Leaderboard};
+
+    #[test]
+    fn test_record() {
+        struct TestCase<'a> {
+            name: &'a str,
+            scores: Vec<usize>,
+            want: Vec<usize>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Record: Sorted",
+                scores: vec![100, 300, 200],
+                want: vec![300, 200, 100],
+            },
+            TestCase {
+                name: "Record: Keeps Ten",
+                scores: (1..=12).collect(),
+                want: (3..=12).rev().collect(),
+            },
+        ];
+
+        for t in tests {
+            let mut leaderboard = Leaderboard::default();
+            for score in t.scores {
+                leaderboard.record(Entry {
+                    score,
+                    best_tile: 2,
+                });
+            }
+
+            let got: Vec<usize> = leaderboard.entries().iter().map(|e| e.score).collect();
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+}
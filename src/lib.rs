@@ -1,23 +1,573 @@
 #![allow(clippy::wildcard_imports)]
 
-use game_state::GameState;
+use ai::{Brain, Outlook, Projection, Projector, Race};
+use analysis::Analysis;
+use archive::ArchivedGame;
+use backup::Backup;
+use broadcast::{Broadcast, Spectator};
+use challenge::Challenge;
+use crypt::Vault;
+use embed::{Config, Options, Seed2048Options};
+use game_state::{Direction, GameState, MergeRule, MoveResult, PowerUp, Variant};
+use goal::{Goal, LADDER};
+use grade::Accuracy;
+use hot_seat::HotSeat;
+use leaderboard::Leaderboard;
+use mode::{Mode, MOVE_LIMIT, SCORE_TARGET, TIME_ATTACK_SECONDS};
+use multiplayer::Versus;
+use ntuple::NTupleEvaluator;
+use puzzle::{Puzzle, PUZZLES};
+use route::Page;
 use seed::{prelude::*, *};
+use settings::Settings;
+use splits::Splits;
+use stats::Stats;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::rc::Rc;
+use storage::{Storage, StorageError};
+use sync::{SyncConfig, SyncStatus};
+use tilt::Tilt;
+use toast::{Severity, Toasts};
+use tournament::Tournament;
 
 const STORAGE_KEY: &str = "game_state";
-const LEFT_KEY: &str = "ArrowLeft";
-const RIGHT_KEY: &str = "ArrowRight";
-const UP_KEY: &str = "ArrowUp";
-const DOWN_KEY: &str = "ArrowDown";
+const STATS_KEY: &str = "stats";
+const DEVICE_KEY: &str = "device";
+const SYNC_KEY: &str = "sync";
+const SETTINGS_KEY: &str = "settings";
+const SOLVED_PUZZLES_KEY: &str = "solved_puzzles";
+const ACHIEVEMENTS_KEY: &str = "achievements";
+const SPLITS_KEY: &str = "splits";
+const PLAYER_NAME_KEY: &str = "player_name";
+const TOURNAMENT_KEY: &str = "tournament";
+const VERSUS_SERVER_KEY: &str = "versus_server";
+const GAMES_KEY: &str = "games";
+const UPDATED_AT_KEY: &str = "updated_at";
+const VARIANT_KEY: &str = "variant";
+const SAVE_DELAY_MS: u32 = 250;
+// How long a finished game stays before the next starts by itself.
+const AUTO_RESTART_MS: u32 = 2000;
 
+const VARIANTS: [(Variant, &str); 13] = [
+    (Variant::Classic, "Classic"),
+    (Variant::Obstacles(1), "1 Obstacle"),
+    (Variant::Obstacles(2), "2 Obstacles"),
+    (Variant::Obstacles(3), "3 Obstacles"),
+    (Variant::Threes, "Threes"),
+    (Variant::Triples, "Triples"),
+    (Variant::Hex, "Hex"),
+    (Variant::Cube, "3D"),
+    (Variant::Frozen, "Frozen Tiles"),
+    (Variant::Bombs, "Bombs"),
+    (Variant::Multipliers, "x2 Tiles"),
+    (Variant::Torus, "Wrap-Around"),
+    (Variant::Gravity, "Gravity"),
+];
+
+const MODES: [(Mode, &str); 8] = [
+    (Mode::Endless, "Endless"),
+    (Mode::TimeAttack, "Time Attack"),
+    (Mode::MoveLimit, "200 Moves"),
+    (Mode::Puzzle, "Puzzles"),
+    (Mode::Editor, "Editor"),
+    (Mode::ScoreTarget, "20,000 Points"),
+    (Mode::Ladder, "Goal Ladder"),
+    (Mode::HotSeat, "Two Players"),
+];
+
+mod achievements;
+mod ai;
+mod analysis;
+mod archive;
+mod backup;
+pub mod bench;
+mod broadcast;
+mod challenge;
+mod crypt;
+mod embed;
+mod evaluate;
+pub mod fuzz;
 mod game_state;
+mod gif;
+mod goal;
+mod grade;
+mod hot_seat;
+mod leaderboard;
+mod locale;
+mod luck;
+mod mode;
+mod multiplayer;
+mod ntuple;
+mod page;
+mod puzzle;
+mod pwa;
+mod qr;
+mod quota;
+mod records;
+mod rng;
+mod route;
+mod screenshot;
+mod settings;
+mod share;
+pub mod simulate;
+mod skin;
+mod splits;
+mod stats;
+mod storage;
+mod svg;
+mod sync;
+mod tilt;
+mod toast;
+mod topology;
+mod tournament;
+mod transposition;
+pub mod tune;
+mod tutorial;
+
+// Every variant keeps its own saved game, so switching back and forth resumes it.
+fn storage_key(variant: Variant) -> String {
+    match variant {
+        Variant::Classic => STORAGE_KEY.to_string(),
+        Variant::Obstacles(count) => format!("{}_obstacles_{}", STORAGE_KEY, count),
+        Variant::Threes => format!("{}_threes", STORAGE_KEY),
+        Variant::Hex => format!("{}_hex", STORAGE_KEY),
+        Variant::Cube => format!("{}_cube", STORAGE_KEY),
+        Variant::Triples => format!("{}_triples", STORAGE_KEY),
+        Variant::Frozen => format!("{}_frozen", STORAGE_KEY),
+        Variant::Bombs => format!("{}_bombs", STORAGE_KEY),
+        Variant::Multipliers => format!("{}_multipliers", STORAGE_KEY),
+        Variant::Torus => format!("{}_torus", STORAGE_KEY),
+        Variant::Gravity => format!("{}_gravity", STORAGE_KEY),
+    }
+}
+
+// Every mode with an end ranks its runs on a leaderboard of its own.
+fn leaderboard_key(mode: Mode) -> Option<&'static str> {
+    match mode {
+        Mode::Endless
+        | Mode::Puzzle
+        | Mode::Editor
+        | Mode::ScoreTarget
+        | Mode::Ladder
+        | Mode::HotSeat => None,
+        Mode::TimeAttack => Some("leaderboard_time_attack"),
+        Mode::MoveLimit => Some("leaderboard_move_limit"),
+    }
+}
+
+fn load_leaderboard(storage: &Storage, mode: Mode) -> Leaderboard {
+    leaderboard_key(mode)
+        .and_then(|key| storage.get(key))
+        .unwrap_or_default()
+}
+
+fn load_game(storage: &Storage, variant: Variant, settings: Settings) -> GameState {
+    storage
+        .get(&storage_key(variant))
+        .unwrap_or_else(|| new_game(variant, settings))
+}
+
+// A fresh game of the variant with the player's settings applied.
+fn new_game(variant: Variant, settings: Settings) -> GameState {
+    apply_settings(GameState::with_variant(variant, settings.spawn), settings)
+}
+
+fn apply_settings(mut game_state: GameState, settings: Settings) -> GameState {
+    game_state.set_combo_scoring(settings.combo);
+    // Threes and Triples keep their own target, their tiles never hit a power of two.
+    if game_state.rules() == MergeRule::Classic {
+        game_state.set_target(settings.target);
+    }
+    game_state
+}
 
 // ------ ------
 //     Model
 // ------ ------
 
+// A board from the history shown in place of the game. It only lasts until
+// the game changes, which plays or starts over the history.
+struct Preview {
+    step: usize,
+    game_state: GameState,
+    // The archived game the board is from, by when it ended, none for the
+    // current game.
+    archived: Option<f64>,
+    seed: u64,
+    actions: usize,
+}
+
+impl Preview {
+    fn is_current(&self, game_state: &GameState) -> bool {
+        self.seed == game_state.seed() && self.actions == game_state.history().len()
+    }
+}
+
 // `Model` describes our app state.
 pub struct Model {
     game_state: game_state::GameState,
+    variant: Variant,
+    mode: Mode,
+    time_left: u32,
+    moves_left: u32,
+    timer: Option<StreamHandle>,
+    // When the current run started and, once over, when it was finished.
+    started_at: f64,
+    finished_at: Option<f64>,
+    // When the clock of a timed run was stopped, moves wait until it runs again.
+    paused_at: Option<f64>,
+    // The milestones of the game and the best of every game, saved. The best
+    // as they were when the game started are what it is compared against.
+    splits: Splits,
+    best_splits: Splits,
+    pace: Splits,
+    // How precarious the board is, for the board it was worked out for.
+    outlook: Option<(u64, Outlook)>,
+    // How the player's moves compare to the computer's, see `grade.rs`.
+    accuracy: Accuracy,
+    // The final scores the game was headed for on the board last projected,
+    // and how much the moves before it moved the average.
+    projection: Option<(u64, Projection)>,
+    projection_change: Option<f64>,
+    // The games being played for the projection of a board, a slice at a time.
+    projector: Option<(u64, Projector)>,
+    // What the computer made of the game once it was lost or ended.
+    analysis: Option<Analysis>,
+    // The current goal of the ladder and the one just reached, if any.
+    rung: usize,
+    // Whose move it is in the two player mode, and what each one scored.
+    hot_seat: HotSeat,
+    celebration: Option<Goal>,
+    // What the last move did, announced to screen readers.
+    last_move: MoveResult,
+    // Moves pressed while the tiles were sliding, played one per slide.
+    queued_moves: VecDeque<Direction>,
+    sliding: bool,
+    // When each direction was last taken from the keyboard, to throttle held keys.
+    key_times: Vec<(Direction, f64)>,
+    // Where the mouse or finger went down on the board.
+    drag_start: Option<(i32, i32)>,
+    // When the last wheel event came in.
+    wheeled_at: f64,
+    // Orientation events come in while this is kept; tilts are measured
+    // against the first one.
+    tilt: Option<StreamHandle>,
+    tilt_rest: Option<Tilt>,
+    tilted_at: f64,
+    tilt_error: Option<String>,
+    // The direction highlighted by scanning. Every step bumps the generation,
+    // so only the dwell timeout of the latest step plays it.
+    scan: Option<Direction>,
+    scan_generation: u32,
+    game_frame: ElRef<web_sys::HtmlElement>,
+    fullscreen_enabled: bool,
+    fullscreen: bool,
+    // The system asks for more contrast, see `Settings::contrast_suggested`.
+    prefers_contrast: bool,
+    // The page reads right to left. The board doesn't, its directions stay
+    // the ones on screen.
+    rtl: bool,
+    // How much the board is scaled up to fill the screen.
+    fullscreen_scale: f64,
+    toasts: Toasts,
+    // The QR code of the page shown over it, as an SVG image.
+    qr_code: Option<String>,
+    history_open: bool,
+    preview: Option<Preview>,
+    // The move whose outcome is drawn over the board, while its button is
+    // hovered or Shift is held with its key.
+    ghost: Option<Direction>,
+    // Finished games with their moves, loaded in the background.
+    archive: Vec<ArchivedGame>,
+    archive_open: bool,
+    archive_filter: archive::Filter,
+    // The archived game whose moves are listed, by when it ended.
+    archived_game: Option<f64>,
+    leaderboard: Leaderboard,
+    puzzle: usize,
+    // Names rather than indices, so adding puzzles keeps the progress.
+    solved_puzzles: BTreeSet<String>,
+    // When each achievement was unlocked, by name like the puzzles.
+    achievements: BTreeMap<String, f64>,
+    achievements_open: bool,
+    editor_value: usize,
+    power_up: Option<PowerUp>,
+    targets: Vec<usize>,
+    stats: Stats,
+    games: Vec<records::GameRecord>,
+    settings: Settings,
+    sync: SyncConfig,
+    sync_status: SyncStatus,
+    updated_at: f64,
+    storage: Storage,
+    storage_error: Option<StorageError>,
+    // A backup picked for import, until the player chooses how to combine it.
+    backup_import: Option<Backup>,
+    // How full storage is, checked after saves and archive writes.
+    usage: quota::Usage,
+    // Encrypted saves waiting for their passphrase, asked for over the page.
+    vault: Option<Vault>,
+    // What is typed into a passphrase field, never saved.
+    passphrase: String,
+    passphrase_error: Option<String>,
+    // Shown over the game to first-time players.
+    tutorial: Option<tutorial::Tutorial>,
+    // The challenge the current game was started from, by link.
+    challenge: Option<Challenge>,
+    // Put on the challenges sent, saved.
+    player_name: String,
+    // A knockout played in turns on this device, saved between turns.
+    tournament: Option<Tournament>,
+    // Whether the game on the board is a turn of the tournament, rather than
+    // waiting for the device to be passed on.
+    tournament_turn: bool,
+    // The form for a new tournament, names one per line.
+    tournament_names: String,
+    tournament_mode: Mode,
+    // A room of the versus game, joined over a WebSocket.
+    versus: Option<Versus>,
+    // Where the rooms are, saved, and the room to join.
+    versus_server: String,
+    versus_room: String,
+    // The computer racing this game on a copy of the board, moving every
+    // `ai_pace` milliseconds while `ai_timer` runs.
+    ai_race: Option<Race>,
+    ai_timer: Option<StreamHandle>,
+    ai_pace: u32,
+    ai_brain: Brain,
+    ai_network: Option<Rc<NTupleEvaluator>>,
+    // This game going out to a channel on the versus server.
+    broadcast: Option<Broadcast>,
+    // Someone else's game watched in place of this one, from a link.
+    spectator: Option<Spectator>,
+    // A save is scheduled, see `SAVE_DELAY_MS`.
+    save_pending: bool,
+    page: Page,
+    config: Config,
+}
+
+impl Model {
+    fn snapshot(&self) -> sync::Snapshot {
+        sync::Snapshot {
+            updated_at: self.updated_at,
+            variant: self.variant,
+            game_state: self.game_state.clone(),
+            stats: self.stats.clone(),
+            settings: self.settings,
+        }
+    }
+
+    // Everything kept about the player, the current game included.
+    fn backup(&self) -> Backup {
+        Backup {
+            version: backup::VERSION,
+            created_at: js_sys::Date::now(),
+            settings: self.settings,
+            stats: self.stats.clone(),
+            games: self.games.clone(),
+            solved_puzzles: self.solved_puzzles.clone(),
+            achievements: self.achievements.clone(),
+            leaderboards: MODES
+                .iter()
+                .filter(|&&(mode, _)| leaderboard_key(mode).is_some())
+                .map(|&(mode, _)| {
+                    let leaderboard = if mode == self.mode {
+                        self.leaderboard.clone()
+                    } else {
+                        load_leaderboard(&self.storage, mode)
+                    };
+                    (mode, leaderboard)
+                })
+                .collect(),
+            saves: VARIANTS
+                .iter()
+                .filter_map(|&(variant, _)| {
+                    let game_state = if variant == self.variant && self.mode == Mode::Endless {
+                        Some(self.game_state.clone())
+                    } else {
+                        self.storage.get(&storage_key(variant))
+                    };
+                    game_state.map(|game_state| (variant, game_state))
+                })
+                .collect(),
+            archive: self.archive.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: sync::Snapshot) {
+        self.updated_at = snapshot.updated_at;
+        self.variant = snapshot.variant;
+        self.game_state = snapshot.game_state;
+        self.stats.adopt(snapshot.stats);
+        self.settings = snapshot.settings;
+    }
+
+    fn archived_game(&self) -> Option<&ArchivedGame> {
+        self.archived_game.and_then(|ended_at| {
+            self.archive
+                .iter()
+                .find(|game| game.record.ended_at == ended_at)
+        })
+    }
+
+    fn preview(&self) -> Option<&Preview> {
+        self.preview
+            .as_ref()
+            .filter(|preview| preview.is_current(&self.game_state))
+    }
+
+    // The board on screen, which is the game unless a move of it is previewed.
+    fn shown_game_state(&self) -> &GameState {
+        self.preview()
+            .map_or(&self.game_state, |preview| &preview.game_state)
+    }
+
+    // Only endless classic games started on this visit, other variants and
+    // modes reach the milestones at a pace of their own.
+    fn times_splits(&self) -> bool {
+        self.variant == Variant::Classic
+            && self.mode == Mode::Endless
+            && self.started_at > 0.0
+            && self.keeps_records()
+    }
+
+    // Puzzles would start over the same puzzle, the editor never ends.
+    fn restarts_itself(&self) -> bool {
+        self.settings.auto_restart
+            && !matches!(self.mode, Mode::Puzzle | Mode::Editor)
+            && !self.plays_others()
+    }
+
+    // The opponent's clock wouldn't stop.
+    fn can_pause(&self) -> bool {
+        self.mode.is_timed() && !self.is_finished() && !self.races()
+    }
+
+    fn is_finished(&self) -> bool {
+        // A decided race ends the game, whatever is left on the board.
+        if self.ai_race.as_ref().is_some_and(Race::is_over) {
+            return true;
+        }
+        // A board without any legal move ends every mode but the editor.
+        if self.mode != Mode::Editor && self.game_state.legal_moves().next().is_none() {
+            return true;
+        }
+
+        match self.mode {
+            Mode::Endless | Mode::Editor | Mode::HotSeat => false,
+            Mode::TimeAttack => self.time_left == 0,
+            Mode::MoveLimit => self.game_state.moves_left() == Some(0),
+            Mode::Puzzle => self.moves_left == 0 || self.puzzle().is_solved(&self.game_state),
+            Mode::ScoreTarget => self.game_state.is_won(),
+            Mode::Ladder => self.rung == LADDER.len(),
+        }
+    }
+
+    fn puzzle(&self) -> &'static Puzzle {
+        &PUZZLES[self.puzzle]
+    }
+
+    fn allows(&self, direction: Direction) -> bool {
+        // Directions the board doesn't have, e.g. diagonals on a square board.
+        if self.game_state.board().lines(direction).is_empty() {
+            return false;
+        }
+
+        match self.mode {
+            Mode::Editor => false,
+            Mode::Puzzle => self.puzzle().is_allowed(direction),
+            _ => true,
+        }
+    }
+
+    // Incognito games and tournament turns are left out of every record.
+    fn keeps_records(&self) -> bool {
+        !self.storage.is_incognito() && self.tournament.is_none()
+    }
+
+    // Between the turns of a tournament, the board is the last player's.
+    fn awaits_turn(&self) -> bool {
+        self.tournament.is_some() && !self.tournament_turn
+    }
+
+    // Playing against someone online, whose copy of the board only follows moves.
+    fn races(&self) -> bool {
+        self.versus
+            .as_ref()
+            .is_some_and(|versus| versus.opponent.is_some())
+    }
+
+    // Tournaments and races start their games themselves.
+    fn plays_others(&self) -> bool {
+        self.tournament.is_some() || self.races() || self.ai_race.is_some()
+    }
+
+    fn record_run(&mut self) {
+        if !self.keeps_records() {
+            return;
+        }
+        self.leaderboard.record(leaderboard::Entry {
+            score: self.game_state.score(),
+            best_tile: self.game_state.max_tile(),
+        });
+    }
+
+    // Edited boards aren't games of their own.
+    fn record_game(&mut self, orders: &mut impl Orders<Msg>) {
+        if self.mode == Mode::Editor {
+            return;
+        }
+        self.config
+            .options
+            .callbacks
+            .game_over(&self.game_state, self.variant);
+        // Turns only count in the tournament, the device goes to the next player.
+        if self.tournament_turn {
+            self.tournament_turn = false;
+            if let Some(tournament) = self.tournament.as_mut() {
+                tournament.record(self.game_state.score());
+            }
+            return;
+        }
+        if self.restarts_itself() {
+            let note = format!(
+                "{} points, best tile {}. Starting a new game…",
+                self.game_state.score(),
+                self.game_state.max_tile()
+            );
+            show_toast(self, orders, note, Severity::Info);
+            let seed = self.game_state.seed();
+            orders.perform_cmd(cmds::timeout(AUTO_RESTART_MS, move || {
+                Msg::Game(page::game::Msg::AutoRestarted(seed))
+            }));
+        }
+        if !self.keeps_records() {
+            return;
+        }
+
+        let now = js_sys::Date::now();
+        let record = records::GameRecord {
+            ended_at: now,
+            seed: self.game_state.seed(),
+            variant: self.variant,
+            mode: self.mode,
+            score: self.game_state.score(),
+            max_tile: self.game_state.max_tile(),
+            moves: self.game_state.move_count(),
+            // Games loaded from an earlier visit were started at no known time.
+            duration: (self.started_at > 0.0).then_some(now - self.started_at),
+        };
+        records::record(&mut self.games, record);
+
+        let game = ArchivedGame::new(record, &self.game_state);
+        self.archive.push(game.clone());
+        let storage = self.storage;
+        orders.perform_cmd(async move {
+            Msg::Archive(page::archive::Msg::Archived(
+                archive::add(game, storage).await,
+            ))
+        });
+    }
 }
 
 // ------ ------
@@ -25,13 +575,232 @@ pub struct Model {
 // ------ ------
 
 // `init` describes what should happen when your app started.
-fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+fn init(url: Url, config: Config, orders: &mut impl Orders<Msg>) -> Model {
     orders.stream(streams::window_event(Ev::KeyDown, |ev| {
-        Msg::Move(ev.unchecked_into())
+        Msg::Game(page::game::Msg::Move(ev.unchecked_into()))
+    }));
+    orders.stream(streams::window_event(Ev::KeyUp, |ev| {
+        let ev: web_sys::KeyboardEvent = ev.unchecked_into();
+        (ev.key() == "Shift").then_some(Msg::Game(page::game::Msg::GhostHidden))
+    }));
+    // Drags may end anywhere on the page.
+    orders.stream(streams::window_event(Ev::PointerUp, |ev| {
+        let ev: web_sys::PointerEvent = ev.unchecked_into();
+        Msg::Game(page::game::Msg::DragEnded(ev.client_x(), ev.client_y()))
+    }));
+    // Escape leaves fullscreen without asking, so its state is only ever read back.
+    orders.stream(streams::document_event(
+        Ev::from("fullscreenchange"),
+        |_| Msg::Game(page::game::Msg::ViewportChanged),
+    ));
+    orders.stream(streams::window_event(Ev::Resize, |_| {
+        Msg::Game(page::game::Msg::ViewportChanged)
+    }));
+    // Saves still waiting for their delay are written before the page goes away.
+    orders.stream(streams::window_event(Ev::BeforeUnload, |_| Msg::SaveDue));
+    orders.stream(streams::document_event(Ev::VisibilityChange, |_| {
+        Msg::Game(page::game::Msg::VisibilityChanged)
     }));
+    // The address of a host page is the host's, games mounted there stay on
+    // one page.
+    let page = if config.root.is_none() {
+        orders.subscribe(Msg::UrlChanged);
+        Page::from_url(&url)
+    } else {
+        Page::Game
+    };
+
+    let (storage, vault) = Storage::open(config.namespace);
+    let mut model = Model {
+        game_state: new_game(Variant::default(), Settings::default()),
+        variant: Variant::default(),
+        mode: Mode::Endless,
+        time_left: 0,
+        moves_left: 0,
+        timer: None,
+        started_at: 0.0,
+        finished_at: None,
+        paused_at: None,
+        splits: Splits::default(),
+        best_splits: Splits::default(),
+        pace: Splits::default(),
+        outlook: None,
+        accuracy: Accuracy::default(),
+        projection: None,
+        projection_change: None,
+        projector: None,
+        analysis: None,
+        rung: 0,
+        hot_seat: HotSeat::default(),
+        celebration: None,
+        last_move: MoveResult::default(),
+        queued_moves: VecDeque::new(),
+        sliding: false,
+        key_times: Vec::new(),
+        drag_start: None,
+        wheeled_at: 0.0,
+        tilt: None,
+        tilt_rest: None,
+        tilted_at: 0.0,
+        tilt_error: None,
+        scan: None,
+        scan_generation: 0,
+        game_frame: ElRef::default(),
+        fullscreen_enabled: document().fullscreen_enabled(),
+        prefers_contrast: matches!(
+            window().match_media("(prefers-contrast: more)"),
+            Ok(Some(query)) if query.matches()
+        ),
+        rtl: locale::is_rtl_at(config.root.as_ref()),
+        fullscreen: false,
+        fullscreen_scale: 1.0,
+        toasts: Toasts::default(),
+        qr_code: None,
+        history_open: false,
+        preview: None,
+        ghost: None,
+        archive: Vec::new(),
+        archive_open: false,
+        archive_filter: archive::Filter::default(),
+        archived_game: None,
+        leaderboard: Leaderboard::default(),
+        puzzle: 0,
+        solved_puzzles: BTreeSet::new(),
+        achievements: BTreeMap::new(),
+        achievements_open: false,
+        editor_value: 2,
+        power_up: None,
+        targets: Vec::new(),
+        stats: Stats::default(),
+        games: Vec::new(),
+        settings: Settings::default(),
+        sync: SyncConfig::default(),
+        sync_status: SyncStatus::Idle,
+        updated_at: 0.0,
+        storage,
+        storage_error: None,
+        backup_import: None,
+        usage: quota::Usage::default(),
+        vault,
+        passphrase: String::new(),
+        passphrase_error: None,
+        tutorial: None,
+        challenge: None,
+        player_name: String::new(),
+        tournament: None,
+        tournament_turn: false,
+        tournament_names: String::new(),
+        tournament_mode: Mode::MoveLimit,
+        versus: None,
+        versus_server: String::new(),
+        versus_room: String::new(),
+        ai_race: None,
+        ai_timer: None,
+        ai_pace: page::game::AI_PACES[1].0,
+        ai_brain: Brain::Greedy,
+        ai_network: None,
+        broadcast: None,
+        spectator: None,
+        save_pending: false,
+        page: Page::Game,
+        config,
+    };
+    open_page(&mut model, page);
+    model.usage.local = model.storage.usage();
+    orders.perform_cmd(async { Msg::QuotaEstimated(quota::estimate().await) });
+    // Encrypted saves are loaded once unlocked, a fresh game is played until then.
+    if model.vault.is_none() {
+        load_saves(&mut model, orders);
+    }
+    // A host page may start on its own variant and mode, picked as the player would.
+    let options = &model.config.options;
+    if let Some(index) = options
+        .variant
+        .and_then(|variant| VARIANTS.iter().position(|&(v, _)| v == variant))
+    {
+        orders.send_msg(Msg::Game(page::game::Msg::VariantChanged(
+            index.to_string(),
+        )));
+    }
+    if let Some(index) = options
+        .mode
+        .and_then(|mode| MODES.iter().position(|&(m, _)| m == mode))
+    {
+        orders.send_msg(Msg::Game(page::game::Msg::ModeChanged(index.to_string())));
+    }
+    // Taken off the address once started, so reloading doesn't start it over.
+    let search = url.search();
+    let challenge = Challenge::from_query(|key| search.get(key)?.first().cloned());
+    if let Some(challenge) = challenge.filter(|_| model.config.root.is_none()) {
+        url.clone()
+            .set_search(UrlSearch::default())
+            .go_and_replace();
+        page::game::start_challenge(&mut model, orders, challenge);
+    }
+    // Kept in the address, so reloading goes on watching.
+    let watch = search.get("watch").and_then(|watch| watch.first());
+    let server = search.get("server").and_then(|server| server.first());
+    if let (Some(channel), Some(server)) = (watch, server) {
+        if model.config.root.is_none() {
+            page::game::spectate(&mut model, orders, server, channel.clone());
+        }
+    }
+    model
+}
+
+// Reads back everything saved, replacing the game and progress in the model.
+fn load_saves(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let mut storage = model.storage;
+    model.sync = storage.get(SYNC_KEY).unwrap_or_default();
+    if model.sync.is_configured() {
+        orders.send_msg(Msg::Settings(page::settings::Msg::Sync));
+    }
+
+    model.variant = storage.get(VARIANT_KEY).unwrap_or_default();
+    model.settings = storage.get(SETTINGS_KEY).unwrap_or_default();
+    if model.settings.tilt {
+        orders.perform_cmd(async {
+            Msg::Settings(page::settings::Msg::TiltPermitted(
+                tilt::request_permission().await,
+            ))
+        });
+    }
+    orders.perform_cmd(async move {
+        Msg::Archive(page::archive::Msg::ArchiveLoaded(
+            archive::load(storage).await,
+        ))
+    });
+
+    model.game_state = load_game(&storage, model.variant, model.settings);
+    model.leaderboard = load_leaderboard(&storage, model.mode);
+    model.solved_puzzles = storage.get(SOLVED_PUZZLES_KEY).unwrap_or_default();
+    model.stats = storage.get(STATS_KEY).unwrap_or_default();
+    // Stats count for this device under an id of its own, made up the first
+    // time.
+    let device = storage.get(DEVICE_KEY).unwrap_or_else(|| {
+        let device = format!("{:016x}", rand::random::<u64>());
+        storage.insert(DEVICE_KEY, &device).ok();
+        device
+    });
+    model.stats.set_device(&device);
+    model.achievements = storage.get(ACHIEVEMENTS_KEY).unwrap_or_default();
+    model.best_splits = storage.get(SPLITS_KEY).unwrap_or_default();
+    model.player_name = storage.get(PLAYER_NAME_KEY).unwrap_or_default();
+    // A turn cut short by leaving is played again.
+    model.tournament = storage.get(TOURNAMENT_KEY).unwrap_or_default();
+    model.versus_server = storage.get(VERSUS_SERVER_KEY).unwrap_or_default();
+    if let Some(tournament) = &model.tournament {
+        model.variant = tournament.variant();
+        model.mode = tournament.mode();
+    }
+    // Reached before achievements were kept, unlocked quietly.
+    achievements::unlock(&mut model.achievements, &model.stats, js_sys::Date::now());
+    model.games = storage.get(GAMES_KEY).unwrap_or_default();
+    model.updated_at = storage.get(UPDATED_AT_KEY).unwrap_or_default();
 
-    Model {
-        game_state: LocalStorage::get(STORAGE_KEY).unwrap_or_default(),
+    // Nothing saved yet, so a first visit.
+    if model.updated_at == 0.0 && !model.settings.tutorial_done && !model.config.options.read_only {
+        model.tutorial = Some(tutorial::Tutorial::new());
     }
 }
 
@@ -41,29 +810,261 @@ fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
 
 // `Msg` describes the different events you can modify state with.
 enum Msg {
-    Move(web_sys::KeyboardEvent),
-    NewGame,
+    UrlChanged(subs::UrlChanged),
+    Game(page::game::Msg),
+    Archive(page::archive::Msg),
+    Stats(page::stats::Msg),
+    Settings(page::settings::Msg),
+    Achievements(page::achievements::Msg),
+    Tutorial(tutorial::Msg),
+    QuotaEstimated(Option<(f64, f64)>),
+    Copied(Result<(), String>),
+    Shared(Result<share::Shared, String>),
+    ToastDismissed(u32),
+    HighContrastSuggestion(bool),
+    StorageErrorDismissed,
+    StorageRetried,
+    SaveDue,
 }
 
 // `update` describes how to handle each `Msg`.
-fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::Move(ev) => {
-            ev.prevent_default();
-
-            match ev.key().as_str() {
-                LEFT_KEY | "a" => model.game_state.move_tiles(game_state::Direction::Left),
-                RIGHT_KEY | "d" => model.game_state.move_tiles(game_state::Direction::Right),
-                UP_KEY | "w" => model.game_state.move_tiles(game_state::Direction::Up),
-                DOWN_KEY | "s" => model.game_state.move_tiles(game_state::Direction::Down),
-                _ => (),
+        Msg::UrlChanged(subs::UrlChanged(url)) => {
+            open_page(model, Page::from_url(&url));
+            return;
+        }
+        Msg::Game(msg) => {
+            if !page::game::update(msg, model, orders) {
+                return;
+            }
+        }
+        Msg::Archive(msg) => {
+            if !page::archive::update(msg, model, orders) {
+                return;
+            }
+        }
+        Msg::Stats(msg) => {
+            page::stats::update(msg, model, orders);
+            return;
+        }
+        Msg::Settings(msg) => {
+            if !page::settings::update(msg, model, orders) {
+                return;
+            }
+        }
+        Msg::Achievements(msg) => {
+            page::achievements::update(msg, model);
+            return;
+        }
+        Msg::Tutorial(msg) => {
+            let done = model
+                .tutorial
+                .as_mut()
+                .is_some_and(|tutorial| tutorial.update(msg));
+            if !done {
+                return;
+            }
+            model.tutorial = None;
+            model.settings.tutorial_done = true;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::QuotaEstimated(site) => {
+            model.usage.site = site;
+            return;
+        }
+        Msg::Copied(result) => {
+            match result {
+                Ok(()) => show_toast(
+                    model,
+                    orders,
+                    "Copied to the clipboard.".to_string(),
+                    Severity::Success,
+                ),
+                Err(err) => show_toast(model, orders, err, Severity::Error),
+            }
+            return;
+        }
+        Msg::Shared(result) => {
+            let (note, severity) = match result {
+                Ok(share::Shared::Sheet) => return,
+                Ok(share::Shared::Clipboard) => {
+                    ("Copied to the clipboard.".to_string(), Severity::Success)
+                }
+                Err(err) => (err, Severity::Error),
             };
+            show_toast(model, orders, note, severity);
+            return;
+        }
+        Msg::ToastDismissed(id) => {
+            model.toasts.dismiss(id);
+            return;
         }
-        Msg::NewGame => {
-            model.game_state = GameState::default();
+        // Taken or turned down, the suggestion isn't made again.
+        Msg::HighContrastSuggestion(accepted) => {
+            if accepted {
+                model.settings.theme = skin::Theme::HighContrast;
+            }
+            model.settings.contrast_suggested = true;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::StorageErrorDismissed => {
+            model.storage_error = None;
+        }
+        // Saved again once the next save is due.
+        Msg::StorageRetried => {
+            model.storage_error = None;
+            model.storage.retry();
+        }
+        Msg::SaveDue => {
+            model.save_pending = false;
+            save(model);
+            return;
         }
     }
-    LocalStorage::insert(STORAGE_KEY, &model.game_state).expect("save game state to LocalStorage");
+
+    // Whatever changed the game goes out to its spectators.
+    if let Some(broadcast) = model.broadcast.as_mut() {
+        broadcast.follow(&model.game_state);
+    }
+
+    // Writes are synchronous, so changes coming in quick succession (e.g. a
+    // held arrow key) are saved together once things calm down.
+    if !model.save_pending {
+        model.save_pending = true;
+        orders.perform_cmd(cmds::timeout(SAVE_DELAY_MS, || Msg::SaveDue));
+    }
+}
+
+// A failed write keeps the game going and shows a warning until it is dismissed.
+fn save(model: &mut Model) {
+    let mut storage = model.storage;
+    let result = (|| {
+        if model.mode == Mode::Endless {
+            storage.insert(&storage_key(model.variant), &model.game_state)?;
+        }
+        storage.insert(VARIANT_KEY, &model.variant)?;
+        storage.insert(STATS_KEY, &model.stats)?;
+        storage.insert(GAMES_KEY, &model.games)?;
+        storage.insert(SOLVED_PUZZLES_KEY, &model.solved_puzzles)?;
+        storage.insert(ACHIEVEMENTS_KEY, &model.achievements)?;
+        storage.insert(SPLITS_KEY, &model.best_splits)?;
+        storage.insert(PLAYER_NAME_KEY, &model.player_name)?;
+        storage.insert(TOURNAMENT_KEY, &model.tournament)?;
+        storage.insert(VERSUS_SERVER_KEY, &model.versus_server)?;
+        if let Some(key) = leaderboard_key(model.mode) {
+            storage.insert(key, &model.leaderboard)?;
+        }
+        storage.insert(SETTINGS_KEY, &model.settings)?;
+        storage.insert(SYNC_KEY, &model.sync)?;
+        storage.insert(UPDATED_AT_KEY, &model.updated_at)
+    })();
+
+    model.storage = storage;
+    if let Err(err) = result {
+        model.storage_error = Some(err);
+    }
+    model.usage.local = model.storage.usage();
+}
+
+// Stores the archived games in the model in place of those stored.
+fn rewrite_archive(model: &Model, orders: &mut impl Orders<Msg>) {
+    let archive = model.archive.clone();
+    let storage = model.storage;
+    orders.perform_cmd(async move {
+        Msg::Archive(page::archive::Msg::Archived(
+            archive::save_all(archive, true, storage).await,
+        ))
+    });
+}
+
+fn show_toast(model: &mut Model, orders: &mut impl Orders<Msg>, note: String, severity: Severity) {
+    let toast = model.toasts.push(note, severity);
+    let id = toast.id;
+    orders.perform_cmd(cmds::timeout(toast.duration, move || {
+        Msg::ToastDismissed(id)
+    }));
+}
+
+// A replay opens its game as if picked in the archive, leaving it goes back
+// to the current board.
+fn open_page(model: &mut Model, page: Page) {
+    model.archived_game = match page {
+        Page::Replay(ended_at) => Some(ended_at),
+        _ => None,
+    };
+    model.preview = None;
+    model.page = page;
+}
+
+// Replaces the current game with a fresh one, resetting the clock or move budget.
+fn start_game(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    start_game_with(model, orders, new_game(model.variant, model.settings));
+}
+
+// A link to this page with the query, e.g. for a challenge.
+fn page_link(query: Vec<(&str, String)>) -> String {
+    let query = query.into_iter().map(|(key, value)| (key, vec![value]));
+    let location = window().location();
+    format!(
+        "{}{}?{}",
+        location.origin().unwrap_or_default(),
+        location.pathname().unwrap_or_default(),
+        UrlSearch::new(query)
+    )
+}
+
+fn start_game_with(model: &mut Model, orders: &mut impl Orders<Msg>, game_state: GameState) {
+    model.game_state = game_state;
+    model.challenge = None;
+    if model.keeps_records() {
+        model.stats.record_new_game();
+        page::achievements::unlock(model, orders);
+    }
+    model.updated_at = js_sys::Date::now();
+
+    model.started_at = js_sys::Date::now();
+    model.finished_at = None;
+    model.paused_at = None;
+    model.splits = Splits::default();
+    model.pace = model.best_splits;
+    model.rung = 0;
+    model.hot_seat = HotSeat::default();
+    model.celebration = None;
+    model.last_move = MoveResult::default();
+    model.accuracy = Accuracy::default();
+    model.analysis = None;
+    model.projection = None;
+    model.projection_change = None;
+    model.projector = None;
+    model.queued_moves.clear();
+
+    model.timer = None;
+    if model.mode == Mode::TimeAttack {
+        model.time_left = TIME_ATTACK_SECONDS;
+        model.timer = Some(
+            orders.stream_with_handle(streams::interval(1000, || Msg::Game(page::game::Msg::Tick))),
+        );
+    }
+    if model.mode == Mode::MoveLimit {
+        model.game_state.set_move_limit(Some(MOVE_LIMIT));
+    }
+    if model.mode == Mode::ScoreTarget {
+        model.game_state.set_score_target(Some(SCORE_TARGET));
+    }
+
+    // Puzzles ignore the variant and start from their own position.
+    if model.mode == Mode::Puzzle {
+        model.game_state = model.puzzle().game_state();
+        model.moves_left = model.puzzle().moves;
+    }
+
+    // Only the goals count, a winning tile would end the run early.
+    if matches!(model.mode, Mode::ScoreTarget | Mode::Ladder) {
+        model.game_state.set_target(None);
+    }
+    page::game::refresh_outlook(model);
+    page::game::schedule_projection(model, orders);
 }
 
 // ------ ------
@@ -72,91 +1073,277 @@ fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
 
 // `view` describes what to display.
 fn view(model: &Model) -> Node<Msg> {
+    let prefix = &model.config.options.class_prefix;
+    // Mounted on a host page, everything is on the one page.
+    let shows = |page: Page| model.config.root.is_some() || model.page == page;
+    let shows_board = shows(Page::Game) || matches!(model.page, Page::Replay(_));
+
+    // Watching someone else's game, the page is theirs.
+    if let Some(spectator) = &model.spectator {
+        return div![
+            C!["container", if model.rtl { "rtl" } else { "ltr" }],
+            attrs! {At::Dir => if model.rtl { "rtl" } else { "ltr" }},
+            style! {"--ui-scale" => f64::from(model.settings.ui_scale) / 100.0},
+            view_toasts(&model.toasts),
+            page::game::view_spectator(spectator, &model.settings, prefix).map_msg(Msg::Game),
+            view_credits()
+        ];
+    }
+
     div![
-        C!["container"],
-        view_heading(),
-        view_above(),
-        div![
-            C!["game-container"],
-            view_grid(),
-            view_tiles(&model.game_state)
-        ],
-        hr!(),
+        C!["container", if model.rtl { "rtl" } else { "ltr" }],
+        attrs! {At::Dir => if model.rtl { "rtl" } else { "ltr" }},
+        style! {"--ui-scale" => f64::from(model.settings.ui_scale) / 100.0},
+        view_storage_error(model.storage_error.as_ref()),
+        IF!(model.prefers_contrast
+            && model.settings.theme == skin::Theme::Classic
+            && !model.settings.contrast_suggested => div![
+            C!["storage-error"],
+            span!["Your system asks for more contrast. Switch to the high-contrast theme?"],
+            a![
+                C!["storage-error-button"],
+                "Switch",
+                ev(Ev::Click, |_| Msg::HighContrastSuggestion(true))
+            ],
+            a![
+                C!["storage-error-button"],
+                "No thanks",
+                ev(Ev::Click, |_| Msg::HighContrastSuggestion(false))
+            ]
+        ]),
+        IF!(model.usage.is_near_quota() && model.storage_error.is_none() => div![
+            C!["storage-error"],
+            "Storage is almost full. Free up space under Storage in the settings."
+        ]),
+        view_toasts(&model.toasts),
+        model
+            .qr_code
+            .as_ref()
+            .map(|svg| page::game::view_qr_code(svg).map_msg(Msg::Game)),
+        IF!(model.vault.is_some() => page::settings::view_unlock(model).map_msg(Msg::Settings)),
+        model
+            .tutorial
+            .as_ref()
+            .map(|tutorial| tutorial.view().map_msg(Msg::Tutorial)),
+        IF!(model.achievements_open => {
+            page::achievements::view(&model.stats, &model.achievements).map_msg(Msg::Achievements)
+        }),
+        view_heading(model),
+        IF!(model.config.root.is_none() => view_nav(model.page)),
+        IF!(shows(Page::Game) => page::game::view_above_board(model).map_msg(Msg::Game)),
+        IF!(shows_board => page::game::view_board(model).map_msg(Msg::Game)),
+        IF!(matches!(model.page, Page::Replay(_)) => {
+            page::archive::view_replay(model).map_msg(Msg::Archive)
+        }),
+        IF!(shows(Page::Game) => nodes![
+            page::game::view_below_board(model).map_msg(Msg::Game),
+            page::archive::view_history(model).map_msg(Msg::Archive),
+            page::archive::view_archive(model).map_msg(Msg::Archive),
+            page::game::view_below_history(model).map_msg(Msg::Game),
+        ]),
+        IF!(shows(Page::Stats) => {
+            page::stats::view(&model.stats, model.games.len()).map_msg(Msg::Stats)
+        }),
+        IF!(shows(Page::Settings) => page::settings::view(model).map_msg(Msg::Settings)),
         view_credits()
     ]
 }
 
-fn view_heading() -> Node<Msg> {
-    div![C!["heading"], h1![C!["title"], "Seed2048"]]
-}
-
-fn view_above() -> Node<Msg> {
-    div![
-        C!["above-game"],
-        p![
-            C!["game-intro"],
-            "Built with ",
-            strong!("Seed"),
-            ", a Rust framework."
-        ],
-        a![
-            C!["restart-button"],
-            "New Game",
-            ev(Ev::Click, |_| Msg::NewGame)
+// Links to the pages, the browser's history keeps track of them.
+fn view_nav(page: Page) -> Node<Msg> {
+    nav![
+        C!["pages"],
+        [
+            (Page::Game, "Game"),
+            (Page::Stats, "Statistics"),
+            (Page::Settings, "Settings"),
         ]
+        .iter()
+        .map(|&(to, name)| {
+            a![
+                C!["page-link", IF!(to == page => "current")],
+                attrs! {
+                    At::Href => to.href(),
+                    At::from("aria-current") => (to == page).as_at_value(),
+                },
+                name
+            ]
+        })
     ]
 }
 
-fn view_grid() -> Node<Msg> {
-    let mut cells = Vec::new();
-    for _ in 0..4 {
-        cells.push(div![C!["grid-cell"]]);
+fn view_storage_error(error: Option<&StorageError>) -> Node<Msg> {
+    match error {
+        Some(error) => div![
+            C!["storage-error"],
+            span![error.to_string()],
+            a![
+                C!["storage-error-button"],
+                "Retry",
+                ev(Ev::Click, |_| Msg::StorageRetried)
+            ],
+            a![
+                C!["storage-error-button"],
+                "Dismiss",
+                ev(Ev::Click, |_| Msg::StorageErrorDismissed)
+            ]
+        ],
+        None => empty![],
     }
+}
 
-    let mut rows = Vec::new();
-    for _ in 0..4 {
-        rows.push(div![C!["grid-row"], &cells]);
-    }
+// Stacked in a corner, the latest at the bottom.
+fn view_toasts(toasts: &Toasts) -> Node<Msg> {
+    div![
+        C!["toasts"],
+        toasts.iter().map(|toast| {
+            let id = toast.id;
+            div![
+                C!["toast", toast.severity.class()],
+                attrs! {At::from("role") => if toast.severity == Severity::Error {
+                    "alert"
+                } else {
+                    "status"
+                }},
+                span![&toast.message],
+                a![
+                    C!["toast-close"],
+                    attrs! {At::AriaLabel => "Dismiss"},
+                    "×",
+                    ev(Ev::Click, move |_| Msg::ToastDismissed(id))
+                ]
+            ]
+        })
+    ]
+}
 
-    div![C!["grid-container"], &rows]
+fn view_heading(model: &Model) -> Node<Msg> {
+    div![
+        C!["heading"],
+        h1![
+            C!["title"],
+            match model.game_state.target() {
+                Some(target) => format!("Seed{}", target),
+                None => "Seed∞".to_string(),
+            }
+        ],
+        div![
+            C!["scores-container"],
+            view_score(model),
+            view_counter(model),
+            view_combo(&model.game_state)
+        ],
+        a![
+            C!["achievements-link"],
+            format!(
+                "Achievements {}/{}",
+                model.achievements.len(),
+                achievements::ACHIEVEMENTS.len()
+            ),
+            ev(Ev::Click, |_| Msg::Achievements(
+                page::achievements::Msg::AchievementsToggled
+            ))
+        ]
+    ]
 }
 
-fn tile_name(index: usize, tile: game_state::Tile) -> String {
-    let state = tile.get_state();
-    let value = tile.get_value();
+fn view_score(model: &Model) -> Vec<Node<Msg>> {
+    let score = model.game_state.score();
+    let gained = model.last_move.score_gained;
 
-    format!(
-        "tile tile-{} tile-position-{}-{}{}",
-        if value <= 2048 {
-            value.to_string()
-        } else {
-            "super".to_string()
-        },
-        index % 4 + 1,
-        index / 4 + 1,
-        state
-    )
+    nodes![
+        div![
+            C!["score-container"],
+            score,
+            // Keyed by the move, so the animation plays again for every move that scores.
+            IF!(gained > 0 => div![
+                el_key(&model.game_state.move_count()),
+                C!["score-addition"],
+                format!("+{}", gained)
+            ])
+        ],
+        div![C!["best-container"], model.stats.best_score().max(score)]
+    ]
 }
 
-fn view_tile(index: usize, tile: game_state::Tile) -> Node<Msg> {
-    let value = tile.get_value();
-    let name = tile_name(index, tile);
-
-    if let Some(prev) = tile.get_prev() {
-        let prev_name = tile_name(prev, tile);
-        div![C![name], div![C!["tile-inner"], value]]
-    } else {
-        div![C![name], div![C!["tile-inner"], value]]
+// The time or moves left in modes that end on their own.
+fn view_counter(model: &Model) -> Node<Msg> {
+    match model.mode {
+        Mode::Endless | Mode::Editor => empty![],
+        // The player whose move it is in gold.
+        Mode::HotSeat => span![
+            C!["player-scores"],
+            model
+                .hot_seat
+                .scores()
+                .iter()
+                .enumerate()
+                .map(|(player, score)| div![
+                    C![
+                        "player-score",
+                        IF!(player == model.hot_seat.turn() => "player-turn")
+                    ],
+                    attrs! {At::from("data-player") => format!("Player {}", player + 1)},
+                    score
+                ])
+        ],
+        Mode::TimeAttack => div![
+            C!["timer", IF!(model.time_left <= 10 => "timer-low")],
+            format!("{}:{:02}", model.time_left / 60, model.time_left % 60)
+        ],
+        Mode::MoveLimit | Mode::Puzzle => {
+            let moves_left = match model.mode {
+                Mode::MoveLimit => model.game_state.moves_left().unwrap_or(0),
+                _ => model.moves_left as usize,
+            };
+            div![
+                C!["moves-left", IF!(moves_left <= 10 => "moves-low")],
+                moves_left
+            ]
+        }
+        Mode::Ladder => div![
+            C!["goal"],
+            LADDER
+                .get(model.rung)
+                .map_or("All done".to_string(), Goal::to_string)
+        ],
+        Mode::ScoreTarget => {
+            let score = model.game_state.score().min(SCORE_TARGET);
+            div![
+                C!["score-progress"],
+                div![
+                    C!["score-progress-bar"],
+                    style! {St::Width => format!("{}%", score * 100 / SCORE_TARGET)}
+                ],
+                span![
+                    C!["score-progress-label"],
+                    format!("{} / {}", score, SCORE_TARGET)
+                ]
+            ]
+        }
     }
 }
 
-fn view_tiles(game_state: &game_state::GameState) -> Node<Msg> {
-    let mut tiles = Vec::new();
-    for (i, tile) in game_state.get_tiles() {
-        tiles.push(view_tile(i, tile));
+fn view_combo(game_state: &GameState) -> Node<Msg> {
+    let bonus = game_state.combo_bonus();
+    if bonus == 0 {
+        return empty![];
     }
 
-    div![C!["tile-container"], tiles]
+    div![
+        C!["combo"],
+        format!("x{}.{}", 1 + bonus / 100, bonus % 100 / 10)
+    ]
+}
+
+// Board classes with the prefix a host page asked for, see
+// `Options::class_prefix`.
+fn prefixed(prefix: &str, classes: &str) -> String {
+    classes
+        .split_whitespace()
+        .map(|class| format!("{}{}", prefix, class))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 fn view_credits() -> Node<Msg> {
@@ -178,6 +1365,84 @@ fn view_credits() -> Node<Msg> {
 // (This function is invoked by `init` function in `index.html`.)
 #[wasm_bindgen(start)]
 pub fn start() {
+    // Host pages mount their games with `start_with_config` instead.
+    if document().get_element_by_id("app").is_none() {
+        return;
+    }
+    pwa::register();
     // Mount the `app` to the element with the `id` "app".
-    App::start("app", init, update, view);
+    App::start(
+        "app",
+        |url, orders| init(url, Config::default(), orders),
+        update,
+        view,
+    );
+}
+
+// Plays a game `onGameOver` was called with again from its seed, e.g. on the
+// server of a leaderboard, and returns the score, best tile and moves it
+// really came to. Fails for games that couldn't have been played.
+#[wasm_bindgen]
+pub fn verify_replay(game: JsValue) -> Result<JsValue, JsValue> {
+    embed::verify(&game).map_err(JsValue::from)
+}
+
+// Plays games headlessly with a strategy and reports their scores, best
+// tiles and moves, see `simulate::Options` for what `options` may set.
+#[wasm_bindgen]
+pub fn simulate_games(options: JsValue) -> Result<JsValue, JsValue> {
+    let options = js_sys::JSON::stringify(&options)
+        .ok()
+        .and_then(|json| json.as_string())
+        .unwrap_or_else(|| "{}".to_string());
+    let report = simulate::run_json(&options)?;
+    js_sys::JSON::parse(&report)
+}
+
+// Mounts a game to the element with the `id` `element_id`, keeping its saves
+// apart from other games on the page under `storage_namespace`. See
+// `Seed2048Options` for what `options` may set.
+#[wasm_bindgen]
+pub fn start_with_config(
+    element_id: &str,
+    storage_namespace: &str,
+    options: Option<Seed2048Options>,
+) -> Result<(), JsValue> {
+    let root = document()
+        .get_element_by_id(element_id)
+        .ok_or_else(|| JsValue::from(format!("No element with the id \"{}\"", element_id)))?;
+    let options = match options {
+        Some(options) => Options::parse(&options)?,
+        None => Options::default(),
+    };
+    if options
+        .variant
+        .is_some_and(|variant| VARIANTS.iter().all(|&(v, _)| v != variant))
+    {
+        return Err("That variant isn't played here".into());
+    }
+
+    // For the host's stylesheet, next to the classes the element has.
+    let mut classes = vec![root.class_name()];
+    if let Some(theme) = &options.theme {
+        classes.push(format!("theme-{}", theme));
+    }
+    if options.read_only {
+        classes.push("read-only".to_string());
+    }
+    root.set_class_name(classes.join(" ").trim());
+
+    let config = Config {
+        // Kept for as long as the page, like the game itself.
+        namespace: Box::leak(storage_namespace.to_string().into_boxed_str()),
+        root: Some(root.clone()),
+        options,
+    };
+    App::start(
+        root,
+        move |url, orders| init(url, config, orders),
+        update,
+        view,
+    );
+    Ok(())
 }
@@ -0,0 +1,84 @@
+use seed::{prelude::*, *};
+
+// Primary subtags of the languages written right to left.
+const RTL_LANGUAGES: [&str; 10] = ["ar", "ckb", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+pub fn is_rtl(language: &str) -> bool {
+    let primary = language.split(['-', '_']).next().unwrap_or_default();
+    RTL_LANGUAGES
+        .iter()
+        .any(|rtl| rtl.eq_ignore_ascii_case(primary))
+}
+
+// Whether the page reads right to left where the game is mounted, by the
+// nearest `dir`, or else the nearest `lang`.
+pub fn is_rtl_at(element: Option<&web_sys::Element>) -> bool {
+    let element = match element.cloned().or_else(|| document().document_element()) {
+        Some(element) => element,
+        None => return false,
+    };
+    let closest = |attribute: &str| {
+        element
+            .closest(&format!("[{}]", attribute))
+            .ok()
+            .flatten()
+            .and_then(|element| element.get_attribute(attribute))
+    };
+
+    match closest("dir").as_deref() {
+        Some("rtl") => true,
+        Some("ltr") => false,
+        _ => closest("lang").is_some_and(|language| is_rtl(&language)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::locale::is_rtl;
+
+    #[test]
+    fn test_is_rtl() {
+        struct TestCase<'a> {
+            name: &'a str,
+            language: &'a str,
+            want: bool,
+        }
+
+        let tests = [
+            TestCase {
+                name: "RTL: English",
+                language: "en",
+                want: false,
+            },
+            TestCase {
+                name: "RTL: Arabic",
+                language: "ar",
+                want: true,
+            },
+            TestCase {
+                name: "RTL: Hebrew In Israel",
+                language: "he-IL",
+                want: true,
+            },
+            TestCase {
+                name: "RTL: Persian, Underscore",
+                language: "FA_ir",
+                want: true,
+            },
+            TestCase {
+                name: "RTL: Only The Primary Subtag",
+                language: "en-ar",
+                want: false,
+            },
+            TestCase {
+                name: "RTL: Empty",
+                language: "",
+                want: false,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, is_rtl(t.language), "{}", t.name);
+        }
+    }
+}
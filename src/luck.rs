@@ -0,0 +1,137 @@
+use crate::evaluate::{evaluate, Weights};
+use crate::game_state::{GameState, Step};
+
+// How the tiles that landed in a game compare to what its spawn policy
+// promises, by value and by where they landed.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Luck {
+    pub spawns: usize,
+    // The smallest value that spawns, 2 in classic games. Anything above it
+    // is a head start.
+    pub smallest: usize,
+    pub big: usize,
+    pub expected_big: f64,
+    // Tiles that landed where the board judges worse than with the tile on
+    // the average empty cell, and how many were to be expected.
+    pub bad_cells: usize,
+    pub expected_bad_cells: f64,
+}
+
+impl Luck {
+    // Notes a tile landing on the board as it was before it landed.
+    pub fn record(&mut self, before: &GameState, index: usize, value: usize, weights: &Weights) {
+        let odds = before.spawn_policy().odds(before.score());
+        self.smallest = odds.iter().map(|&(value, _)| value).min().unwrap_or(value);
+        self.spawns += 1;
+        if value > self.smallest {
+            self.big += 1;
+        }
+        self.expected_big += odds
+            .iter()
+            .filter(|&&(value, _)| value > self.smallest)
+            .map(|&(_, chance)| chance)
+            .sum::<f64>();
+
+        let landed: Vec<(usize, f64)> = before
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_none())
+            .map(|(cell, _)| {
+                let mut landed = before.clone();
+                landed.set_value(cell, value);
+                (cell, evaluate(&landed, weights))
+            })
+            .collect();
+        if landed.is_empty() {
+            return;
+        }
+        let average = landed.iter().map(|&(_, eval)| eval).sum::<f64>() / landed.len() as f64;
+        let bad = |eval: f64| eval < average;
+        self.expected_bad_cells +=
+            landed.iter().filter(|&&(_, eval)| bad(eval)).count() as f64 / landed.len() as f64;
+        if landed
+            .iter()
+            .any(|&(cell, eval)| cell == index && bad(eval))
+        {
+            self.bad_cells += 1;
+        }
+    }
+
+    // How much luckier than expected, 0.1 for 10%: more big tiles and fewer
+    // bad cells than expected, the two weighed alike. None before any spawn.
+    pub fn luck(&self) -> Option<f64> {
+        if self.spawns == 0 {
+            return None;
+        }
+        let above = |observed: f64, expected: f64| {
+            if expected > 0.0 {
+                observed / expected - 1.0
+            } else {
+                0.0
+            }
+        };
+        let values = above(self.big as f64, self.expected_big);
+        let cells = -above(self.bad_cells as f64, self.expected_bad_cells);
+        Some((values + cells) / 2.0)
+    }
+}
+
+// The luck of a game from the spawns its steps log, `boards` being the
+// board after each step.
+pub fn luck(boards: &[GameState], steps: &[Step], weights: &Weights) -> Luck {
+    let mut luck = Luck::default();
+    for (board, step) in boards.iter().zip(steps) {
+        if let Some((index, value)) = step.spawned {
+            let mut before = board.clone();
+            before.set_tile(index, None);
+            luck.record(&before, index, value, weights);
+        }
+    }
+    luck
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluate::Weights;
+    use crate::game_state::GameState;
+    use crate::luck::Luck;
+
+    #[test]
+    fn test_record() {
+        let weights = Weights::default();
+        // A 2 next to the 4 in the corner makes a mess, in the far corner it
+        // leaves room.
+        let before = GameState::from_values(&[4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let mut luck = Luck::default();
+        assert_eq!(None, luck.luck(), "Record: No Spawns");
+
+        luck.record(&before, 15, 4, &weights);
+        assert_eq!(
+            (1, 2, 1),
+            (luck.spawns, luck.smallest, luck.big),
+            "Record: A 4"
+        );
+        assert!(
+            (luck.expected_big - 0.1).abs() < 1e-9,
+            "Record: Expected 4s"
+        );
+
+        let mut unlucky = Luck::default();
+        unlucky.record(&before, 1, 2, &weights);
+        assert_eq!(0, unlucky.big, "Record: A 2");
+        assert_eq!(
+            (1, 0),
+            (unlucky.bad_cells, luck.bad_cells),
+            "Record: Bad Cells"
+        );
+        assert!(
+            unlucky.expected_bad_cells > 0.0 && unlucky.expected_bad_cells < 1.0,
+            "Record: Expected Bad Cells"
+        );
+        assert!(
+            luck.luck().unwrap() > unlucky.luck().unwrap(),
+            "Record: Luckier"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+// How a game is played out, independent of its variant.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Mode {
+    #[default]
+    Endless,
+    // Highest score before the clock runs out.
+    TimeAttack,
+    // Highest score within a fixed number of moves.
+    MoveLimit,
+    // Curated positions with a goal to reach in a few moves.
+    Puzzle,
+    // Free editing of the board, played on from the edited position.
+    Editor,
+    // Reach a set score in as few moves and as little time as possible.
+    ScoreTarget,
+    // A series of goals, one after the other on the same board.
+    Ladder,
+    // Two players taking turns on one board, each scoring their own moves.
+    HotSeat,
+}
+
+impl Mode {
+    // Runs ranked against each other, by score or by time.
+    pub fn is_competitive(self) -> bool {
+        matches!(self, Mode::TimeAttack | Mode::MoveLimit | Mode::ScoreTarget)
+    }
+
+    // Runs against the clock, which may be paused.
+    pub fn is_timed(self) -> bool {
+        matches!(self, Mode::TimeAttack | Mode::ScoreTarget)
+    }
+}
+
+pub const TIME_ATTACK_SECONDS: u32 = 120;
+pub const MOVE_LIMIT: usize = 200;
+pub const SCORE_TARGET: usize = 20_000;
@@ -0,0 +1,184 @@
+use seed::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+
+// What players of a room send each other, as JSON text. The server only
+// passes every message on to the others in the room.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum Message {
+    // Sent on joining and in answer to the first hello of an opponent.
+    Hello(Hello),
+    // Every move played, with the score after it. The opponent plays it on
+    // their copy of the board.
+    Moved { direction: Direction, score: usize },
+}
+
+// A player and the game they offer to play.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Hello {
+    pub name: String,
+    pub seed: u64,
+    pub variant: Variant,
+    pub spawn: SpawnPolicy,
+    pub combo: bool,
+}
+
+impl Hello {
+    // The game both players go with, whichever side works it out: the lower
+    // seed, ties broken by name.
+    pub fn agree<'a>(&'a self, other: &'a Hello) -> &'a Hello {
+        if (other.seed, &other.name) < (self.seed, &self.name) {
+            other
+        } else {
+            self
+        }
+    }
+
+    pub fn game_state(&self) -> GameState {
+        let mut game_state = GameState::with_seed(self.variant, self.spawn, self.seed);
+        game_state.set_combo_scoring(self.combo);
+        game_state
+    }
+}
+
+// The other player's board, kept in step by playing their moves on the
+// same seed.
+#[derive(Debug, Clone)]
+pub struct Opponent {
+    pub name: String,
+    pub game_state: GameState,
+    // As they sent it, the board is only ever as good as the moves.
+    pub score: usize,
+}
+
+impl Opponent {
+    pub fn new(name: String, game: &Hello) -> Opponent {
+        Opponent {
+            name,
+            game_state: game.game_state(),
+            score: 0,
+        }
+    }
+
+    pub fn play(&mut self, direction: Direction, score: usize) {
+        self.game_state.move_tiles(direction);
+        self.score = score;
+    }
+}
+
+// A room joined over a WebSocket, closed when dropped.
+pub struct Versus {
+    socket: WebSocket,
+    pub room: String,
+    pub hello: Hello,
+    pub opponent: Option<Opponent>,
+}
+
+impl Versus {
+    pub fn new(socket: WebSocket, room: String, hello: Hello) -> Versus {
+        Versus {
+            socket,
+            room,
+            hello,
+            opponent: None,
+        }
+    }
+
+    // Messages that don't go out leave the opponent's board behind, there's
+    // nothing to do about them until the socket closes.
+    pub fn send(&self, message: &Message) {
+        self.socket.send_json(message).ok();
+    }
+}
+
+// Every room is a path on the server, e.g. `wss://example.com/rooms/friday`.
+pub fn room_url(server: &str, room: &str) -> String {
+    format!(
+        "{}/{}",
+        server.trim().trim_end_matches('/'),
+        String::from(js_sys::encode_uri_component(room.trim()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{Direction, SpawnPolicy, Variant};
+    use crate::multiplayer::{Hello, Message, Opponent};
+
+    fn hello(name: &str, seed: u64) -> Hello {
+        Hello {
+            name: name.to_string(),
+            seed,
+            variant: Variant::Classic,
+            spawn: SpawnPolicy::Classic,
+            combo: false,
+        }
+    }
+
+    #[test]
+    fn test_agree() {
+        struct TestCase<'a> {
+            name: &'a str,
+            mine: Hello,
+            theirs: Hello,
+            want: &'a str,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Agree: Lower Seed",
+                mine: hello("Ann", 9),
+                theirs: hello("Bo", 3),
+                want: "Bo",
+            },
+            TestCase {
+                name: "Agree: Same Seed",
+                mine: hello("Bo", 3),
+                theirs: hello("Ann", 3),
+                want: "Ann",
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, t.mine.agree(&t.theirs).name, "{}", t.name);
+            assert_eq!(
+                t.want,
+                t.theirs.agree(&t.mine).name,
+                "{}: Other Side",
+                t.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_mirror() {
+        let game = hello("Ann", 42);
+        let mut mine = game.game_state();
+        let mut opponent = Opponent::new("Ann".to_string(), &game);
+        for direction in [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+        ] {
+            mine.move_tiles(direction);
+            let message = Message::Moved {
+                direction,
+                score: mine.score(),
+            };
+            let text = serde_json::to_string(&message).unwrap();
+            match serde_json::from_str(&text).unwrap() {
+                Message::Moved { direction, score } => opponent.play(direction, score),
+                Message::Hello(_) => panic!("Mirror: Wrong Message"),
+            }
+        }
+        assert_eq!(
+            mine.to_string(),
+            opponent.game_state.to_string(),
+            "Mirror: Board"
+        );
+        assert_eq!(mine.score(), opponent.score, "Mirror: Score");
+    }
+}
@@ -0,0 +1,221 @@
+use crate::game_state::GameState;
+use crate::topology::Board;
+
+// Starts every network blob, followed by the version of the format.
+const MAGIC: &[u8; 4] = b"NTUP";
+const VERSION: u8 = 1;
+
+// Tiles are told apart up to 32768, everything from there on looks the same.
+const CODES: usize = 16;
+const MAX_LEN: usize = 6;
+
+#[derive(Debug, Clone)]
+struct Tuple {
+    // The cells of the pattern under each of the eight symmetries of the board.
+    symmetries: Vec<Vec<usize>>,
+    // One weight for every combination of tile codes on the cells.
+    weights: Vec<f32>,
+}
+
+// Judges square boards by a learned weight for what lies on every n-tuple of
+// cells, summed over the tuples. The weights come from a blob made by
+// training outside the game:
+//
+// - "NTUP" and the version, 1, as a byte
+// - the number of tuples as a byte, then for each its length and its cells
+//   as bytes, cells counted row by row from the top left
+// - for each tuple in turn, 16 ^ length weights as little-endian f32, by the
+//   codes of the cells with the first cell the most significant digit
+//
+// A cell's code is the exponent of its tile, 15 at most, and 0 when it's
+// empty or holds a tile that isn't a power of two. Every tuple is also laid
+// on the board turned and mirrored. Networks are usually trained on the
+// 4-tuples 0 1 2 3 and 4 5 6 7 and the 6-tuples 0 1 2 4 5 6 and
+// 4 5 6 8 9 10.
+#[derive(Debug, Clone)]
+pub struct NTupleEvaluator {
+    tuples: Vec<Tuple>,
+}
+
+impl NTupleEvaluator {
+    pub fn from_bytes(bytes: &[u8]) -> Result<NTupleEvaluator, String> {
+        let mut rest = bytes
+            .strip_prefix(MAGIC.as_ref())
+            .ok_or("Not an n-tuple network")?;
+        let mut take = |count: usize| -> Result<&[u8], String> {
+            if rest.len() < count {
+                return Err("The n-tuple network is cut short".to_string());
+            }
+            let (taken, left) = rest.split_at(count);
+            rest = left;
+            Ok(taken)
+        };
+
+        let version = take(1)?[0];
+        if version != VERSION {
+            return Err(format!("Unknown n-tuple network version {}", version));
+        }
+        let count = take(1)?[0];
+        let mut patterns = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let len = take(1)?[0] as usize;
+            if len == 0 || len > MAX_LEN {
+                return Err(format!("Tuples of {} cells aren't supported", len));
+            }
+            let cells = take(len)?;
+            if let Some(cell) = cells.iter().find(|&&cell| cell >= 16) {
+                return Err(format!("Cell {} is off the board", cell));
+            }
+            patterns.push(cells.iter().map(|&cell| cell as usize).collect::<Vec<_>>());
+        }
+
+        let mut tuples = Vec::with_capacity(patterns.len());
+        for cells in patterns {
+            let weights = take(CODES.pow(cells.len() as u32) * 4)?
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect();
+            tuples.push(Tuple {
+                symmetries: symmetries(&cells),
+                weights,
+            });
+        }
+        if !rest.is_empty() {
+            return Err("The n-tuple network goes on past its weights".to_string());
+        }
+        Ok(NTupleEvaluator { tuples })
+    }
+
+    // None for boards that aren't square, the tuples are laid out on 4x4.
+    pub fn evaluate(&self, game_state: &GameState) -> Option<f64> {
+        if game_state.board() != Board::Square {
+            return None;
+        }
+        let codes: Vec<usize> = game_state
+            .cells()
+            .iter()
+            .map(|cell| match cell {
+                Some(tile) if tile.is_number() && tile.get_value().is_power_of_two() => {
+                    (tile.get_value().trailing_zeros() as usize).min(CODES - 1)
+                }
+                _ => 0,
+            })
+            .collect();
+        let codes = &codes;
+
+        let value = self
+            .tuples
+            .iter()
+            .flat_map(|tuple| {
+                tuple.symmetries.iter().map(move |cells| {
+                    let index = cells
+                        .iter()
+                        .fold(0, |index, &cell| index * CODES + codes[cell]);
+                    f64::from(tuple.weights[index])
+                })
+            })
+            .sum();
+        Some(value)
+    }
+}
+
+// The cells under every rotation of the board, and the same mirrored.
+fn symmetries(cells: &[usize]) -> Vec<Vec<usize>> {
+    let turn = |cell: usize| (cell % 4) * 4 + (3 - cell / 4);
+    let mirror = |cell: usize| (cell / 4) * 4 + (3 - cell % 4);
+
+    let mut all = Vec::with_capacity(8);
+    let mut current = cells.to_vec();
+    for _ in 0..4 {
+        all.push(current.iter().map(|&cell| mirror(cell)).collect());
+        current = current.iter().map(|&cell| turn(cell)).collect();
+        all.push(current.clone());
+    }
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::ntuple::{symmetries, NTupleEvaluator};
+
+    // A network of a single tuple on the corner cell, weighing each code by
+    // itself.
+    fn corner_network() -> Vec<u8> {
+        let mut bytes = b"NTUP\x01\x01\x01\x00".to_vec();
+        for code in 0..16 {
+            bytes.extend_from_slice(&(code as f32).to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let block = [0, 1, 2, 4, 5, 6];
+        let all = symmetries(&block);
+        assert_eq!(8, all.len(), "Symmetries: Count");
+        assert!(all.contains(&block.to_vec()), "Symmetries: Identity");
+        let mut corners: Vec<usize> = symmetries(&[0]).into_iter().flatten().collect();
+        corners.sort_unstable();
+        corners.dedup();
+        assert_eq!(vec![0, 3, 12, 15], corners, "Symmetries: Corners");
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        struct TestCase<'a> {
+            name: &'a str,
+            bytes: Vec<u8>,
+            want_ok: bool,
+        }
+        let network = corner_network();
+        let tests = [
+            TestCase {
+                name: "From Bytes: Corner",
+                bytes: network.clone(),
+                want_ok: true,
+            },
+            TestCase {
+                name: "From Bytes: Not A Network",
+                bytes: b"GIF89a".to_vec(),
+                want_ok: false,
+            },
+            TestCase {
+                name: "From Bytes: Cut Short",
+                bytes: network[..network.len() - 1].to_vec(),
+                want_ok: false,
+            },
+            TestCase {
+                name: "From Bytes: Off The Board",
+                bytes: b"NTUP\x01\x01\x01\x10".to_vec(),
+                want_ok: false,
+            },
+            TestCase {
+                name: "From Bytes: Trailing Bytes",
+                bytes: [network.as_slice(), &[0]].concat(),
+                want_ok: false,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(
+                t.want_ok,
+                NTupleEvaluator::from_bytes(&t.bytes).is_ok(),
+                "{}",
+                t.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let network = NTupleEvaluator::from_bytes(&corner_network()).unwrap();
+        // The corner is seen twice from each corner, once mirrored.
+        let board = GameState::from_values(&[8, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4]);
+        assert_eq!(
+            Some(2.0 * (3.0 + 1.0 + 2.0)),
+            network.evaluate(&board),
+            "Evaluate: Corners"
+        );
+    }
+}
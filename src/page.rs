@@ -0,0 +1,7 @@
+// Each page with its view and the messages only it sends. What every page
+// shares, the model and saving it included, stays at the root.
+pub mod achievements;
+pub mod archive;
+pub mod game;
+pub mod settings;
+pub mod stats;
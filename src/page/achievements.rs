@@ -0,0 +1,86 @@
+use seed::{prelude::*, *};
+
+use std::collections::BTreeMap;
+
+use crate::stats::Stats;
+use crate::toast::Severity;
+use crate::{achievements, show_toast, Model};
+
+pub enum Msg {
+    AchievementsToggled,
+}
+
+pub fn update(msg: Msg, model: &mut Model) {
+    match msg {
+        Msg::AchievementsToggled => model.achievements_open = !model.achievements_open,
+    }
+}
+
+pub fn unlock(model: &mut Model, orders: &mut impl Orders<crate::Msg>) {
+    let now = js_sys::Date::now();
+    for achievement in achievements::unlock(&mut model.achievements, &model.stats, now) {
+        let note = format!("Achievement unlocked: {}", achievement.name);
+        show_toast(model, orders, note, Severity::Success);
+    }
+}
+
+// Every achievement, unlocked or not, with how far along the incremental
+// ones are.
+pub fn view(stats: &Stats, unlocked: &BTreeMap<String, f64>) -> Node<Msg> {
+    div![
+        C!["qr-modal"],
+        attrs! {At::from("role") => "dialog"},
+        ev(Ev::Click, |_| Msg::AchievementsToggled),
+        div![
+            C!["qr-dialog", "achievements-dialog"],
+            ev(Ev::Click, |ev| ev.stop_propagation()),
+            h2!["Achievements"],
+            ul![
+                C!["achievements"],
+                achievements::ACHIEVEMENTS.iter().map(|achievement| {
+                    let unlocked_at = unlocked.get(achievement.name).map(|&at| {
+                        String::from(
+                            js_sys::Date::new(&at.into())
+                                .to_locale_date_string("default", &JsValue::UNDEFINED),
+                        )
+                    });
+                    let progress = achievement.progress(stats);
+                    li![
+                        C![
+                            "achievement",
+                            if unlocked_at.is_some() {
+                                "unlocked"
+                            } else {
+                                "locked"
+                            }
+                        ],
+                        div![C!["achievement-name"], achievement.name],
+                        div![C!["achievement-description"], achievement.description],
+                        match unlocked_at {
+                            Some(at) => div![C!["achievement-date"], format!("Unlocked {}", at)],
+                            None if achievement.is_incremental() => div![
+                                C!["achievement-progress"],
+                                div![
+                                    C!["achievement-progress-bar"],
+                                    style! {
+                                        St::Width => format!("{}%", progress * 100 / achievement.goal)
+                                    }
+                                ],
+                                span![
+                                    C!["achievement-progress-label"],
+                                    format!("{}/{}", progress, achievement.goal)
+                                ]
+                            ],
+                            None => empty![],
+                        }
+                    ]
+                })
+            ],
+            a![
+                C!["qr-close"],
+                "Close",
+                ev(Ev::Click, |_| Msg::AchievementsToggled)
+            ]
+        ]
+    ]
+}
@@ -0,0 +1,405 @@
+use seed::{prelude::*, *};
+
+use crate::archive::ArchivedGame;
+use crate::game_state::{Action, GameState, Step};
+use crate::mode::Mode;
+use crate::page::game::{DIRECTIONS, POWER_UPS};
+use crate::route::Page;
+use crate::{open_page, quota, start_game, Model, Preview, MODES, VARIANTS};
+
+pub enum Msg {
+    HistoryToggled,
+    StepPicked(usize),
+    ArchiveToggled,
+    ArchiveOutcomeChanged(String),
+    ArchiveModeChanged(String),
+    ArchiveSizeChanged(String),
+    ArchivedGameOpened(f64),
+    ArchivedStepPicked(usize),
+    Branched,
+    ArchiveLoaded(Result<Vec<ArchivedGame>, String>),
+    Archived(Result<(), String>),
+}
+
+// Returns false when only what is shown changed, there is nothing to save.
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<crate::Msg>) -> bool {
+    match msg {
+        Msg::HistoryToggled => {
+            model.history_open = !model.history_open;
+            model.preview = None;
+            return false;
+        }
+        // Picking the previewed step again goes back to the game.
+        Msg::StepPicked(step) => {
+            if model
+                .preview()
+                .map(|preview| (preview.archived, preview.step))
+                == Some((None, step))
+            {
+                model.preview = None;
+                return false;
+            }
+
+            model.preview = replay_step(&model.game_state, step).map(|game_state| Preview {
+                step,
+                game_state,
+                archived: None,
+                seed: model.game_state.seed(),
+                actions: model.game_state.history().len(),
+            });
+            return false;
+        }
+        Msg::ArchiveToggled => {
+            model.archive_open = !model.archive_open;
+            model.archived_game = None;
+            model.preview = None;
+            return false;
+        }
+        Msg::ArchiveOutcomeChanged(value) => {
+            model.archive_filter.won = match value.as_str() {
+                "won" => Some(true),
+                "lost" => Some(false),
+                _ => None,
+            };
+            return false;
+        }
+        Msg::ArchiveModeChanged(index) => {
+            model.archive_filter.mode = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| MODES.get(i))
+                .map(|&(mode, _)| mode);
+            return false;
+        }
+        Msg::ArchiveSizeChanged(size) => {
+            model.archive_filter.size = size.parse().ok();
+            return false;
+        }
+        // Opening the listed game again closes it.
+        Msg::ArchivedGameOpened(ended_at) => {
+            model.archived_game = (model.archived_game != Some(ended_at)).then_some(ended_at);
+            model.preview = None;
+            return false;
+        }
+        Msg::ArchivedStepPicked(step) => {
+            let game = match model.archived_game() {
+                Some(game) => game,
+                None => return false,
+            };
+            let previewed = model
+                .preview()
+                .map(|preview| (preview.archived, preview.step));
+            if previewed == Some((Some(game.record.ended_at), step)) {
+                model.preview = None;
+                return false;
+            }
+
+            model.preview = replay_step(&game.game_state, step).map(|game_state| Preview {
+                step,
+                game_state,
+                archived: Some(game.record.ended_at),
+                seed: model.game_state.seed(),
+                actions: model.game_state.history().len(),
+            });
+            return false;
+        }
+        // Plays on from the previewed board as a new endless game of its variant.
+        Msg::Branched => {
+            let preview = match model.preview.take() {
+                Some(preview) if preview.is_current(&model.game_state) => preview,
+                _ => return false,
+            };
+            let (variant, target) = match preview.archived {
+                Some(ended_at) => match model
+                    .archive
+                    .iter()
+                    .find(|game| game.record.ended_at == ended_at)
+                {
+                    Some(game) => (game.record.variant, game.game_state.target()),
+                    None => return false,
+                },
+                None => (model.variant, model.game_state.target()),
+            };
+
+            model.variant = variant;
+            model.mode = Mode::Endless;
+            start_game(model, orders);
+            model.game_state = preview.game_state;
+            // Replays leave out the target, so played on games replay in full.
+            model.game_state.set_target(target);
+            // Played on in the game, out of the replay.
+            if model.page != Page::Game {
+                window().location().set_hash(&Page::Game.href()).ok();
+                open_page(model, Page::Game);
+            }
+        }
+        // Games archived before the archive was read are kept.
+        Msg::ArchiveLoaded(Ok(mut archive)) => {
+            for game in model.archive.drain(..) {
+                if archive
+                    .iter()
+                    .all(|loaded| loaded.record.ended_at != game.record.ended_at)
+                {
+                    archive.push(game);
+                }
+            }
+            model.archive = archive;
+            return false;
+        }
+        Msg::ArchiveLoaded(Err(err)) | Msg::Archived(Err(err)) => {
+            log!("The archive isn't available:", err);
+            return false;
+        }
+        Msg::Archived(Ok(())) => {
+            orders.perform_cmd(async { crate::Msg::QuotaEstimated(quota::estimate().await) });
+            return false;
+        }
+    }
+    true
+}
+
+// The board after the given step of the game, replayed from its start.
+fn replay_step(game_state: &GameState, step: usize) -> Option<GameState> {
+    let mut frames = 0;
+    let mut previewed = None;
+    game_state.replay(|frame| {
+        if frames == step + 1 {
+            previewed = Some(frame.clone());
+        }
+        frames += 1;
+    });
+    previewed
+}
+
+// An archived game on a page of its own, the board showing the move picked.
+pub fn view_replay(model: &Model) -> Node<Msg> {
+    let game = match model.archived_game() {
+        Some(game) => game,
+        None => {
+            return div![
+                C!["move-history"],
+                p![C!["history-hint"], "That game isn't in the archive."]
+            ]
+        }
+    };
+    let previewed = model
+        .preview()
+        .filter(|preview| preview.archived == Some(game.record.ended_at))
+        .map(|preview| preview.step);
+
+    div![
+        C!["move-history"],
+        a![
+            C!["history-toggle"],
+            attrs! {At::Href => Page::Game.href()},
+            "Back To The Game"
+        ],
+        view_steps(&game.game_state.steps(), previewed, Msg::ArchivedStepPicked)
+    ]
+}
+
+fn step_name(action: &Action) -> &'static str {
+    match action {
+        Action::Move(direction) => DIRECTIONS
+            .iter()
+            .find(|(d, _, _)| d == direction)
+            .map_or("", |&(_, _, name)| name),
+        Action::PowerUp(power_up, _) => POWER_UPS
+            .iter()
+            .find(|(p, _, _)| p == power_up)
+            .map_or("", |&(_, name, _)| name),
+        Action::Shuffle => "Shuffle",
+    }
+}
+
+// Every action of the game so far, replayed only while the list is open.
+pub fn view_history(model: &Model) -> Node<Msg> {
+    let toggle = a![
+        C!["history-toggle"],
+        if model.history_open {
+            "Hide Moves"
+        } else {
+            "Show Moves"
+        },
+        ev(Ev::Click, |_| Msg::HistoryToggled)
+    ];
+    if !model.history_open {
+        return div![C!["move-history"], toggle];
+    }
+
+    let previewed = model
+        .preview()
+        .filter(|preview| preview.archived.is_none())
+        .map(|preview| preview.step);
+
+    div![
+        C!["move-history"],
+        toggle,
+        view_steps(&model.game_state.steps(), previewed, Msg::StepPicked)
+    ]
+}
+
+// The moves of a game, picking one shows the board after it.
+fn view_steps(steps: &[Step], previewed: Option<usize>, pick: fn(usize) -> Msg) -> Vec<Node<Msg>> {
+    vec![
+        if steps.is_empty() {
+            p![C!["history-hint"], "No moves to show yet."]
+        } else if previewed.is_some() {
+            p![
+                C!["history-hint"],
+                "Showing the board after that move, pick it again to go back. ",
+                a![
+                    C!["history-branch"],
+                    "Play On From Here",
+                    ev(Ev::Click, |_| Msg::Branched)
+                ]
+            ]
+        } else {
+            p![C!["history-hint"], "Pick a move to see the board after it."]
+        },
+        ol![
+            C!["history-steps"],
+            steps.iter().enumerate().map(|(i, step)| {
+                li![
+                    C!["history-step", IF!(previewed == Some(i) => "previewed")],
+                    ev(Ev::Click, move |_| pick(i)),
+                    span![C!["history-action"], step_name(&step.action)],
+                    span![C!["history-gain"], format!("+{}", step.score_gained)],
+                    span![
+                        C!["history-merges"],
+                        match step.merges {
+                            0 => String::new(),
+                            1 => "1 merge".to_string(),
+                            merges => format!("{} merges", merges),
+                        }
+                    ]
+                ]
+            })
+        ],
+    ]
+}
+
+// Finished games, newest first, each with its moves to look through or play
+// on from.
+pub fn view_archive(model: &Model) -> Node<Msg> {
+    let toggle = a![
+        C!["history-toggle"],
+        if model.archive_open {
+            "Hide Past Games"
+        } else {
+            "Show Past Games"
+        },
+        ev(Ev::Click, |_| Msg::ArchiveToggled)
+    ];
+    if !model.archive_open {
+        return div![C!["move-history"], toggle];
+    }
+
+    let filter = model.archive_filter;
+    let mut sizes: Vec<usize> = model.archive.iter().map(ArchivedGame::size).collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+    let games: Vec<&ArchivedGame> = model
+        .archive
+        .iter()
+        .rev()
+        .filter(|game| filter.matches(game))
+        .collect();
+    let previewed = model
+        .preview()
+        .filter(|preview| preview.archived.is_some() && preview.archived == model.archived_game)
+        .map(|preview| preview.step);
+
+    div![
+        C!["move-history"],
+        toggle,
+        div![
+            C!["archive-filters"],
+            select![
+                C!["variant-select"],
+                [("", "Won Or Lost"), ("won", "Won"), ("lost", "Lost")]
+                    .iter()
+                    .map(|&(value, name)| {
+                        let selected = match value {
+                            "won" => Some(true),
+                            "lost" => Some(false),
+                            _ => None,
+                        } == filter.won;
+                        option![
+                            attrs! {At::Value => value, At::Selected => selected.as_at_value()},
+                            name
+                        ]
+                    }),
+                input_ev(Ev::Change, Msg::ArchiveOutcomeChanged)
+            ],
+            select![
+                C!["variant-select"],
+                option![attrs! {At::Value => ""}, "Any Mode"],
+                MODES.iter().enumerate().map(|(i, (mode, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (filter.mode == Some(*mode)).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::ArchiveModeChanged)
+            ],
+            select![
+                C!["variant-select"],
+                option![attrs! {At::Value => ""}, "Any Size"],
+                sizes.iter().map(|&size| {
+                    option![
+                        attrs! {
+                            At::Value => size,
+                            At::Selected => (filter.size == Some(size)).as_at_value()
+                        },
+                        format!("{} Cells", size)
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::ArchiveSizeChanged)
+            ]
+        ],
+        IF!(games.is_empty() => p![C!["history-hint"], "No finished games to show."]),
+        ol![
+            C!["archive-games"],
+            games.iter().map(|game| {
+                let record = game.record;
+                let open = model.archived_game == Some(record.ended_at);
+                let variant = VARIANTS
+                    .iter()
+                    .find(|(v, _)| *v == record.variant)
+                    .map_or("", |(_, name)| name);
+                let mode = MODES
+                    .iter()
+                    .find(|(m, _)| *m == record.mode)
+                    .map_or("", |(_, name)| name);
+                let ended_at = js_sys::Date::new(&record.ended_at.into())
+                    .to_locale_string("default", &JsValue::UNDEFINED);
+
+                li![
+                    C!["archive-game", IF!(open => "open")],
+                    div![
+                        C!["archive-summary"],
+                        ev(Ev::Click, move |_| Msg::ArchivedGameOpened(record.ended_at)),
+                        span![C!["archive-date"], String::from(ended_at)],
+                        span![format!("{}, {}", variant, mode)],
+                        span![format!(
+                            "{} points, best tile {}{}",
+                            record.score,
+                            record.max_tile,
+                            if game.won { ", won" } else { "" }
+                        )]
+                    ],
+                    IF!(open && model.config.root.is_none() => a![
+                        C!["history-branch"],
+                        attrs! {At::Href => Page::Replay(record.ended_at).href()},
+                        "Open As Replay"
+                    ]),
+                    IF!(open => view_steps(&game.game_state.steps(), previewed, Msg::ArchivedStepPicked))
+                ]
+            })
+        ]
+    ]
+}
@@ -0,0 +1,2800 @@
+use seed::{prelude::*, *};
+
+use std::rc::Rc;
+
+use crate::ai::{self, Brain, Projector, Race, Racer, OUTLOOK_MOVES};
+use crate::analysis::{self, Analysis};
+use crate::broadcast::{self, Broadcast, Spectator};
+use crate::challenge::Challenge;
+use crate::evaluate::Weights;
+use crate::game_state::{
+    self, Action, Direction, GameEvent, GameState, MergeRule, MoveResult, PowerUp, RenderKind,
+    RenderTile, Variant,
+};
+use crate::goal::{self, Goal, LADDER};
+use crate::grade::{self, Accuracy};
+use crate::leaderboard::Leaderboard;
+use crate::mode::Mode;
+use crate::multiplayer::{self, Hello, Opponent, Versus};
+use crate::ntuple::NTupleEvaluator;
+use crate::puzzle::PUZZLES;
+use crate::rng::GameRng;
+use crate::route::Page;
+use crate::settings::Settings;
+use crate::splits::{self, Splits};
+use crate::tilt::Tilt;
+use crate::toast::Severity;
+use crate::topology::Board;
+use crate::tournament::{self, Tournament};
+use crate::{
+    apply_settings, load_game, load_leaderboard, page_link, prefixed, qr, screenshot, share,
+    show_toast, skin, start_game, start_game_with, svg, tutorial, Model, MODES, VARIANTS,
+};
+
+// How long tiles take to slide, `$transition-speed` in main.scss.
+const SLIDE_MS: u32 = 100;
+// How far spectators are behind the game they watch.
+const SPECTATE_DELAY_MS: u32 = 3000;
+// Greedy games played on from every board for its outlook, see `ai::outlook`.
+const OUTLOOK_PLAYOUTS: usize = 24;
+// Greedy games played to the end for the projected final score, once the
+// board has been left alone this long, so fast play doesn't start one after
+// every move.
+const PROJECTION_PLAYOUTS: usize = 8;
+const PROJECTION_DELAY_MS: u32 = 400;
+// Moves of those games played between two frames. Thousands of moves in one
+// go would freeze the page.
+const PROJECTION_SLICE: usize = 100;
+// Shorter drags are clicks, e.g. on cells in the editor.
+const DRAG_THRESHOLD_PX: i32 = 30;
+// A pause in wheel events this long ends a scroll, so one flick is one move.
+const WHEEL_GAP_MS: f64 = 200.0;
+const LEFT_KEY: &str = "ArrowLeft";
+const RIGHT_KEY: &str = "ArrowRight";
+const UP_KEY: &str = "ArrowUp";
+const DOWN_KEY: &str = "ArrowDown";
+const ABOVE_KEY: &str = "PageUp";
+const BELOW_KEY: &str = "PageDown";
+// Arrow and spoken name of every direction, in the order scanning steps through them.
+pub const DIRECTIONS: [(Direction, &str, &str); 10] = [
+    (Direction::Up, "↑", "Up"),
+    (Direction::UpRight, "↗", "Up right"),
+    (Direction::Right, "→", "Right"),
+    (Direction::DownRight, "↘", "Down right"),
+    (Direction::Down, "↓", "Down"),
+    (Direction::DownLeft, "↙", "Down left"),
+    (Direction::Left, "←", "Left"),
+    (Direction::UpLeft, "↖", "Up left"),
+    (Direction::Above, "⤒", "Above"),
+    (Direction::Below, "⤓", "Below"),
+];
+// The modes a tournament can be played in, their budget the same for everyone.
+const TOURNAMENT_MODES: [Mode; 2] = [Mode::MoveLimit, Mode::TimeAttack];
+// Name and targeting hint of every power-up.
+pub const POWER_UPS: [(PowerUp, &str, &str); 3] = [
+    (PowerUp::Remove, "Remove", "Pick a tile to remove."),
+    (PowerUp::Swap, "Swap", "Pick two tiles to swap."),
+    (PowerUp::Double, "Double", "Pick a tile to double."),
+];
+// Values offered by the editor palette, 0 clears a cell.
+const EDITOR_VALUES: [usize; 12] = [0, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048];
+// Milliseconds between the computer's moves in a race against it.
+pub const AI_PACES: [(u32, &str); 4] = [
+    (250, "Fast"),
+    (500, "Brisk"),
+    (1000, "Steady"),
+    (2000, "Leisurely"),
+];
+const AI_BRAINS: [(Brain, &str); 3] = [
+    (Brain::Greedy, "Greedy"),
+    (Brain::MonteCarlo, "Monte Carlo"),
+    (Brain::NTuple, "N-tuple network"),
+];
+// Weights of the n-tuple brain, fetched the first time it's picked. See
+// `NTupleEvaluator` for the format.
+const NTUPLE_URL: &str = "public/ntuple.bin";
+
+pub enum Msg {
+    Move(web_sys::KeyboardEvent),
+    DragStarted(i32, i32),
+    DragEnded(i32, i32),
+    Wheeled(f64, f64),
+    Tilted(Tilt),
+    ScanStepped,
+    ScanDwelled(u32),
+    ScanConfirmed,
+    NewGame,
+    Challenge,
+    ChallengeDismissed,
+    TournamentNamesChanged(String),
+    TournamentModeChanged(String),
+    TournamentStarted,
+    TournamentTurnStarted,
+    TournamentAbandoned,
+    VersusServerChanged(String),
+    VersusRoomChanged(String),
+    VersusJoined,
+    VersusOpened,
+    VersusReceived(WebSocketMessage),
+    VersusClosed,
+    VersusLeft,
+    AiPaceChanged(String),
+    AiBrainChanged(String),
+    AiNetworkLoaded(Result<Rc<NTupleEvaluator>, String>),
+    AiRaceStarted,
+    AiStepped,
+    AiRaceLeft,
+    BroadcastToggled,
+    BroadcastOpened,
+    BroadcastReceived(WebSocketMessage),
+    BroadcastClosed,
+    BroadcastLinkCopied,
+    SpectatorOpened,
+    SpectatorReceived(WebSocketMessage),
+    SpectateDue(broadcast::Log),
+    SpectatorClosed,
+    SpectateStopped,
+    PauseToggled,
+    VisibilityChanged,
+    FullscreenToggled,
+    Share,
+    SaveImage,
+    SaveSvg,
+    SaveReplay,
+    CopySvg,
+    CopyText,
+    ShowQrCode,
+    QrCodeClosed,
+    GhostShown(Direction),
+    GhostHidden,
+    PadPressed(Direction),
+    ViewportChanged,
+    VariantChanged(String),
+    ModeChanged(String),
+    PuzzleSelected(usize),
+    EditorValueSelected(usize),
+    BoardPasted(String),
+    CellClicked(usize),
+    PowerUpSelected(PowerUp),
+    Shuffle,
+    EditorCleared,
+    PlayFromHere,
+    Tick,
+    ProjectionDue(u64),
+    ProjectionPlayed,
+    AutoRestarted(u64),
+    SlideEnded,
+}
+
+// Returns false when nothing that is saved or broadcast changed.
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<crate::Msg>) -> bool {
+    match msg {
+        Msg::Move(ev) => {
+            // Shortcuts like Ctrl+A and form fields (e.g. the sync form)
+            // keep their keys.
+            if ev.ctrl_key()
+                || ev.meta_key()
+                || ev.alt_key()
+                || !is_game_focused(ev.target(), &model.config.options.class_prefix)
+                || !model.config.takes_keys(ev.target().as_ref())
+            {
+                return false;
+            }
+
+            if model.settings.scanning {
+                let msg = match ev.key().as_str() {
+                    " " => Some(Msg::ScanStepped),
+                    "Enter" => Some(Msg::ScanConfirmed),
+                    _ => None,
+                };
+                if let Some(msg) = msg {
+                    ev.prevent_default();
+                    // Held switches don't race through the directions.
+                    if !ev.repeat() {
+                        orders.send_msg(crate::Msg::Game(msg));
+                    }
+                    return false;
+                }
+            }
+
+            if ev.key().eq_ignore_ascii_case("p") && model.can_pause() {
+                ev.prevent_default();
+                orders.send_msg(crate::Msg::Game(Msg::PauseToggled));
+                return false;
+            }
+
+            let direction = match ev.key().as_str() {
+                LEFT_KEY | "a" => Some(Direction::Left),
+                RIGHT_KEY | "d" => Some(Direction::Right),
+                UP_KEY | "w" => Some(Direction::Up),
+                DOWN_KEY | "s" => Some(Direction::Down),
+                "q" => Some(Direction::UpLeft),
+                "e" => Some(Direction::UpRight),
+                "z" => Some(Direction::DownLeft),
+                "c" => Some(Direction::DownRight),
+                ABOVE_KEY | "r" => Some(Direction::Above),
+                BELOW_KEY | "f" => Some(Direction::Below),
+                _ => None,
+            };
+
+            let direction = match direction {
+                Some(direction) if model.allows(direction) => direction,
+                _ => return false,
+            };
+            // Only keys that play a move, the rest still scroll or tab around the page.
+            ev.prevent_default();
+
+            // Shift only shows where the move would take the tiles.
+            if ev.shift_key() {
+                model.ghost = Some(direction);
+                return false;
+            }
+
+            // Held keys repeat at the rate of the OS, far faster than anyone
+            // would press them.
+            let now = js_sys::Date::now();
+            let last = model.key_times.iter().position(|&(d, _)| d == direction);
+            if ev.repeat() {
+                if model.mode.is_competitive() && model.settings.competitive_no_repeat {
+                    return false;
+                }
+                if last.is_some_and(|i| {
+                    now - model.key_times[i].1 < f64::from(model.settings.repeat_interval)
+                }) {
+                    return false;
+                }
+            }
+            match last {
+                Some(i) => model.key_times[i].1 = now,
+                None => model.key_times.push((direction, now)),
+            }
+
+            // Ignored keys and moves that change nothing leave everything,
+            // saves included, as it is.
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        Msg::GhostShown(direction) => {
+            model.ghost = Some(direction);
+            return false;
+        }
+        Msg::GhostHidden => {
+            model.ghost = None;
+            return false;
+        }
+        Msg::PadPressed(direction) => {
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        // Nothing worth saving happens before the drag ends.
+        Msg::DragStarted(x, y) => {
+            model.drag_start = Some((x, y));
+            return false;
+        }
+        Msg::DragEnded(x, y) => {
+            let (dx, dy) = match model.drag_start.take() {
+                Some((start_x, start_y)) => (x - start_x, y - start_y),
+                None => return false,
+            };
+            if dx.abs().max(dy.abs()) < DRAG_THRESHOLD_PX {
+                return false;
+            }
+
+            let direction = match model
+                .game_state
+                .board()
+                .swipe_direction(f64::from(dx), f64::from(dy))
+            {
+                Some(direction) if model.allows(direction) => direction,
+                _ => return false,
+            };
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        Msg::Tilted(tilt) => {
+            let rest = *model.tilt_rest.get_or_insert(tilt);
+            // A device held tipped keeps moving, just not with every event.
+            let now = js_sys::Date::now();
+            if now - model.tilted_at < f64::from(model.settings.tilt_cooldown) {
+                return false;
+            }
+
+            let threshold = f64::from(model.settings.tilt_threshold);
+            let direction = match tilt.direction(rest, threshold, model.game_state.board()) {
+                Some(direction) if model.allows(direction) => direction,
+                _ => return false,
+            };
+            model.tilted_at = now;
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        Msg::ScanStepped => {
+            let directions: Vec<Direction> = DIRECTIONS
+                .iter()
+                .map(|&(direction, _, _)| direction)
+                .filter(|&direction| model.allows(direction))
+                .collect();
+            let next = model
+                .scan
+                .and_then(|scan| directions.iter().position(|&d| d == scan))
+                .map_or(0, |i| i + 1);
+            model.scan = directions.get(next % directions.len().max(1)).copied();
+
+            model.scan_generation += 1;
+            let generation = model.scan_generation;
+            orders.perform_cmd(cmds::timeout(model.settings.scan_dwell, move || {
+                crate::Msg::Game(Msg::ScanDwelled(generation))
+            }));
+            return false;
+        }
+        Msg::ScanDwelled(generation) if generation != model.scan_generation => return false,
+        Msg::ScanDwelled(_) | Msg::ScanConfirmed => {
+            model.scan_generation += 1;
+            let direction = match model.scan.take() {
+                Some(direction) => direction,
+                None => return false,
+            };
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        Msg::Wheeled(dx, dy) => {
+            // Trackpads and free-spinning wheels send a burst of events per flick.
+            let now = js_sys::Date::now();
+            let flick = now - model.wheeled_at > WHEEL_GAP_MS;
+            model.wheeled_at = now;
+            if !flick {
+                return false;
+            }
+
+            let direction = match model.game_state.board().swipe_direction(dx, dy) {
+                Some(direction) if model.allows(direction) => direction,
+                _ => return false,
+            };
+            if !request_move(model, orders, direction) {
+                return false;
+            }
+        }
+        Msg::SlideEnded => {
+            model.sliding = false;
+
+            let mut moved = false;
+            while let Some(direction) = model.queued_moves.pop_front() {
+                if play_move(model, orders, direction) {
+                    moved = true;
+                    break;
+                }
+            }
+            if !moved {
+                return false;
+            }
+        }
+        // Tournament games are started by passing the device on, races by
+        // joining again.
+        Msg::NewGame => {
+            if model.plays_others() {
+                return false;
+            }
+            start_game(model, orders);
+        }
+        // Asks for a name to put on the link, cancelling sends nothing.
+        Msg::Challenge => {
+            let name = window().prompt_with_message_and_default(
+                "Your name, for whoever you challenge:",
+                &model.player_name,
+            );
+            let name = match name {
+                Ok(Some(name)) => name.trim().to_string(),
+                _ => return false,
+            };
+            let challenge = Challenge {
+                seed: model.game_state.seed(),
+                variant: model.variant,
+                mode: model.mode,
+                spawn: model.game_state.spawn_policy(),
+                combo: model.game_state.combo_scoring(),
+                score: model.game_state.score(),
+                name: name.clone(),
+            };
+            let link = page_link(challenge.to_query());
+            let text = share::challenge(challenge.score, &link);
+            orders.perform_cmd(async { crate::Msg::Shared(share::share(text).await) });
+
+            model.player_name = name;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::ChallengeDismissed => {
+            model.challenge = None;
+            return false;
+        }
+        // Only the form changes, nothing that is saved.
+        Msg::TournamentNamesChanged(names) => {
+            model.tournament_names = names;
+            return false;
+        }
+        Msg::TournamentModeChanged(index) => {
+            if let Some(&mode) = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| TOURNAMENT_MODES.get(i))
+            {
+                model.tournament_mode = mode;
+            }
+            return false;
+        }
+        // Everyone plays the variant on the board, with the budget picked.
+        Msg::TournamentStarted => {
+            let tournament = Tournament::new(
+                &model.tournament_names,
+                model.variant,
+                model.tournament_mode,
+                rand::random(),
+            );
+            match tournament {
+                Ok(tournament) => {
+                    model.versus = None;
+                    model.ai_race = None;
+                    model.ai_timer = None;
+                    model.mode = tournament.mode();
+                    model.leaderboard = load_leaderboard(&model.storage, model.mode);
+                    model.tournament = Some(tournament);
+                    model.tournament_turn = false;
+                    model.challenge = None;
+                    model.timer = None;
+                    model.paused_at = None;
+                }
+                Err(message) => {
+                    show_toast(model, orders, message, Severity::Warning);
+                    return false;
+                }
+            }
+        }
+        Msg::TournamentTurnStarted => {
+            let (variant, seed) = match &model.tournament {
+                Some(tournament) => match tournament.turn() {
+                    Some(turn) => (tournament.variant(), turn.seed),
+                    None => return false,
+                },
+                None => return false,
+            };
+            let game_state = GameState::with_seed(variant, model.settings.spawn, seed);
+            start_game_with(model, orders, apply_settings(game_state, model.settings));
+            model.tournament_turn = true;
+        }
+        Msg::TournamentAbandoned => {
+            model.tournament = None;
+            model.tournament_turn = false;
+            model.timer = None;
+            model.paused_at = None;
+        }
+        // The server is saved once a room is joined on it.
+        Msg::VersusServerChanged(server) => {
+            model.versus_server = server;
+            return false;
+        }
+        Msg::VersusRoomChanged(room) => {
+            model.versus_room = room;
+            return false;
+        }
+        // Offers a game of the variant on the board, see `Hello::agree`.
+        Msg::VersusJoined => {
+            let url = multiplayer::room_url(&model.versus_server, &model.versus_room);
+            let socket = WebSocket::builder(url, orders)
+                .on_open(|| crate::Msg::Game(Msg::VersusOpened))
+                .on_message(|message| crate::Msg::Game(Msg::VersusReceived(message)))
+                .on_close(|_| crate::Msg::Game(Msg::VersusClosed))
+                .on_error(|| crate::Msg::Game(Msg::VersusClosed))
+                .build_and_open();
+            let socket = match socket {
+                Ok(socket) => socket,
+                Err(_) => {
+                    let note = "Couldn't join the room. Is the server address right?".to_string();
+                    show_toast(model, orders, note, Severity::Error);
+                    return false;
+                }
+            };
+            let hello = Hello {
+                name: model.player_name.clone(),
+                seed: rand::random(),
+                variant: model.variant,
+                spawn: model.settings.spawn,
+                combo: model.settings.combo,
+            };
+            let room = model.versus_room.trim().to_string();
+            model.versus = Some(Versus::new(socket, room, hello));
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::VersusOpened => {
+            if let Some(versus) = &model.versus {
+                versus.send(&multiplayer::Message::Hello(versus.hello.clone()));
+            }
+            return false;
+        }
+        // Anything that isn't a message of the game is ignored.
+        Msg::VersusReceived(message) => match message.json() {
+            Ok(multiplayer::Message::Hello(theirs)) => start_versus(model, orders, theirs),
+            Ok(multiplayer::Message::Moved { direction, score }) => {
+                if let Some(opponent) = model.versus.as_mut().and_then(|v| v.opponent.as_mut()) {
+                    opponent.play(direction, score);
+                }
+                return false;
+            }
+            Err(_) => return false,
+        },
+        // Closed by the server or the network, the game on the board plays on.
+        Msg::VersusClosed => {
+            if model.versus.take().is_some() {
+                let note = "The connection to the room closed.".to_string();
+                show_toast(model, orders, note, Severity::Warning);
+            }
+            return false;
+        }
+        Msg::VersusLeft => {
+            model.versus = None;
+            return false;
+        }
+        // A race under way picks the new pace up from the computer's next move.
+        Msg::AiPaceChanged(index) => {
+            if let Some(&(pace, _)) = index.parse().ok().and_then(|i: usize| AI_PACES.get(i)) {
+                model.ai_pace = pace;
+                if model.ai_timer.is_some() {
+                    model.ai_timer =
+                        Some(orders.stream_with_handle(streams::interval(pace, || {
+                            crate::Msg::Game(Msg::AiStepped)
+                        })));
+                }
+            }
+            return false;
+        }
+        // Takes over from the computer's next move, a race under way included.
+        Msg::AiBrainChanged(index) => {
+            if let Some(&(brain, _)) = index.parse().ok().and_then(|i: usize| AI_BRAINS.get(i)) {
+                model.ai_brain = brain;
+                if let Some(race) = model.ai_race.as_mut() {
+                    race.brain = brain;
+                }
+                if brain == Brain::NTuple && model.ai_network.is_none() {
+                    orders.perform_cmd(async {
+                        crate::Msg::Game(Msg::AiNetworkLoaded(fetch_network().await.map(Rc::new)))
+                    });
+                }
+            }
+            return false;
+        }
+        Msg::AiNetworkLoaded(Ok(network)) => {
+            if let Some(race) = model.ai_race.as_mut() {
+                race.network = Some(Rc::clone(&network));
+            }
+            model.ai_network = Some(network);
+            return false;
+        }
+        // Back to the greedy brain, which needs nothing loaded.
+        Msg::AiNetworkLoaded(Err(err)) => {
+            log!("Loading the n-tuple network failed:", err);
+            model.ai_brain = Brain::Greedy;
+            if let Some(race) = model.ai_race.as_mut() {
+                race.brain = Brain::Greedy;
+            }
+            show_toast(
+                model,
+                orders,
+                "Couldn't load the n-tuple network.".to_string(),
+                Severity::Error,
+            );
+            return false;
+        }
+        // A fresh endless game, the computer playing the same seed and spawns.
+        Msg::AiRaceStarted => {
+            model.tournament = None;
+            model.tournament_turn = false;
+            model.versus = None;
+            model.mode = Mode::Endless;
+            model.leaderboard = load_leaderboard(&model.storage, model.mode);
+            model.ai_race = None;
+            start_game(model, orders);
+            let mut race = Race::new(model.game_state.clone(), model.ai_brain);
+            race.network = model.ai_network.clone();
+            race.weights = model.config.options.ai_weights.unwrap_or_default();
+            model.ai_race = Some(race);
+            model.ai_timer = Some(
+                orders.stream_with_handle(streams::interval(model.ai_pace, || {
+                    crate::Msg::Game(Msg::AiStepped)
+                })),
+            );
+        }
+        Msg::AiStepped => {
+            let race = match model.ai_race.as_mut() {
+                Some(race) if !race.is_over() => race,
+                _ => return false,
+            };
+            race.step();
+            race.settle(&model.game_state);
+            if !race.is_over() {
+                return false;
+            }
+            model.ai_timer = None;
+            model.record_game(orders);
+        }
+        // The game on the board plays on alone.
+        Msg::AiRaceLeft => {
+            model.ai_race = None;
+            model.ai_timer = None;
+            return false;
+        }
+        // Nothing is saved about broadcasts, they end with the page.
+        Msg::BroadcastToggled => {
+            if model.broadcast.take().is_some() {
+                return false;
+            }
+            let channel = format!("{:08x}", rand::random::<u32>());
+            let url = multiplayer::room_url(&model.versus_server, &channel);
+            let socket = WebSocket::builder(url, orders)
+                .on_open(|| crate::Msg::Game(Msg::BroadcastOpened))
+                .on_message(|message| crate::Msg::Game(Msg::BroadcastReceived(message)))
+                .on_close(|_| crate::Msg::Game(Msg::BroadcastClosed))
+                .on_error(|| crate::Msg::Game(Msg::BroadcastClosed))
+                .build_and_open();
+            match socket {
+                Ok(socket) => model.broadcast = Some(Broadcast::new(socket, channel)),
+                Err(_) => {
+                    let note =
+                        "Couldn't start the broadcast. Is the server address under Versus right?";
+                    show_toast(model, orders, note.to_string(), Severity::Error);
+                }
+            }
+            return false;
+        }
+        Msg::BroadcastOpened => {
+            if let Some(broadcast) = model.broadcast.as_mut() {
+                broadcast.send_all(&model.game_state);
+            }
+            return false;
+        }
+        // Spectators joining ask for the game so far, the rest is our own logs.
+        Msg::BroadcastReceived(message) => {
+            if let Ok(broadcast::Message::Watching) = message.json() {
+                if let Some(broadcast) = model.broadcast.as_mut() {
+                    broadcast.send_all(&model.game_state);
+                }
+            }
+            return false;
+        }
+        Msg::BroadcastClosed => {
+            if model.broadcast.take().is_some() {
+                let note = "The broadcast stopped, the connection closed.".to_string();
+                show_toast(model, orders, note, Severity::Warning);
+            }
+            return false;
+        }
+        Msg::BroadcastLinkCopied => {
+            if let Some(broadcast) = &model.broadcast {
+                let link = page_link(vec![
+                    ("watch", broadcast.channel.clone()),
+                    ("server", model.versus_server.trim().to_string()),
+                ]);
+                orders.perform_cmd(async { crate::Msg::Copied(share::copy(link).await) });
+            }
+            return false;
+        }
+        Msg::SpectatorOpened => {
+            if let Some(spectator) = &model.spectator {
+                spectator.ask();
+            }
+            return false;
+        }
+        // Held back a little, so spectators can't call out moves ahead of them.
+        Msg::SpectatorReceived(message) => {
+            if let Ok(broadcast::Message::Log(log)) = message.json() {
+                orders.perform_cmd(cmds::timeout(SPECTATE_DELAY_MS, || {
+                    crate::Msg::Game(Msg::SpectateDue(log))
+                }));
+            }
+            return false;
+        }
+        Msg::SpectateDue(log) => {
+            if let Some(spectator) = model.spectator.as_mut() {
+                if !spectator.follower.follow(log) {
+                    spectator.ask();
+                }
+            }
+            return false;
+        }
+        Msg::SpectatorClosed => {
+            if model.spectator.is_some() {
+                let note = "The connection to the broadcast closed.".to_string();
+                show_toast(model, orders, note, Severity::Warning);
+            }
+            return false;
+        }
+        Msg::SpectateStopped => {
+            model.spectator = None;
+            Url::current()
+                .set_search(UrlSearch::default())
+                .go_and_replace();
+            return false;
+        }
+        // Only the clock changes, nothing that is saved.
+        Msg::PauseToggled => {
+            if model.paused_at.is_some() {
+                resume(model, orders);
+            } else {
+                pause(model);
+            }
+            return false;
+        }
+        // Leaving the tab stops the clock, coming back leaves it to the player.
+        Msg::VisibilityChanged => {
+            if document().hidden() {
+                pause(model);
+            }
+            return false;
+        }
+        Msg::FullscreenToggled => {
+            if model.fullscreen {
+                document().exit_fullscreen();
+            } else if let Some(frame) = model.game_frame.get() {
+                if let Err(err) = frame.request_fullscreen() {
+                    log!("Fullscreen was refused:", err);
+                }
+            }
+            return false;
+        }
+        Msg::Share => {
+            let text = share::message(
+                model.game_state.score(),
+                model.game_state.seed(),
+                &window().location().href().unwrap_or_default(),
+            );
+            orders.perform_cmd(async { crate::Msg::Shared(share::share(text).await) });
+            return false;
+        }
+        Msg::SaveImage => {
+            if let Err(err) = screenshot::save_png(&model.game_state, model.settings.exponents) {
+                log!("Saving the image failed:", err);
+                show_toast(
+                    model,
+                    orders,
+                    "Couldn't save the image.".to_string(),
+                    Severity::Error,
+                );
+            }
+            return false;
+        }
+        Msg::SaveSvg => {
+            let svg = svg::board_svg(&model.game_state, model.settings.exponents);
+            let url = format!(
+                "data:image/svg+xml;charset=utf-8,{}",
+                String::from(js_sys::encode_uri_component(&svg))
+            );
+            if let Err(err) = screenshot::download(&url, "2048.svg") {
+                log!("Saving the SVG failed:", err);
+                show_toast(
+                    model,
+                    orders,
+                    "Couldn't save the image.".to_string(),
+                    Severity::Error,
+                );
+            }
+            return false;
+        }
+        Msg::SaveReplay => {
+            match screenshot::save_gif(&model.game_state, model.settings.exponents) {
+                Ok(true) => (),
+                Ok(false) => show_toast(
+                    model,
+                    orders,
+                    "There's nothing to replay yet.".to_string(),
+                    Severity::Info,
+                ),
+                Err(err) => {
+                    log!("Saving the replay failed:", err);
+                    show_toast(
+                        model,
+                        orders,
+                        "Couldn't save the replay.".to_string(),
+                        Severity::Error,
+                    );
+                }
+            }
+            return false;
+        }
+        Msg::CopySvg => {
+            let svg = svg::board_svg(&model.game_state, model.settings.exponents);
+            orders.perform_cmd(async { crate::Msg::Copied(share::copy(svg).await) });
+            return false;
+        }
+        Msg::CopyText => {
+            let text = share::board_text(&model.game_state);
+            orders.perform_cmd(async { crate::Msg::Copied(share::copy(text).await) });
+            return false;
+        }
+        Msg::ShowQrCode => {
+            let url = window().location().href().unwrap_or_default();
+            match qr::QrCode::encode(&url) {
+                Some(qr_code) => model.qr_code = Some(qr_code.to_svg()),
+                None => show_toast(
+                    model,
+                    orders,
+                    "The link is too long for a QR code.".to_string(),
+                    Severity::Warning,
+                ),
+            }
+            return false;
+        }
+        Msg::QrCodeClosed => {
+            model.qr_code = None;
+            return false;
+        }
+        Msg::ViewportChanged => {
+            model.fullscreen = document().fullscreen_element().is_some();
+            // Measured unscaled, then scaled to fit the screen with a margin. The
+            // UI scale applies on top, so it is taken back out.
+            let ui_scale = f64::from(model.settings.ui_scale) / 100.0;
+            let board_width = model
+                .game_frame
+                .get()
+                .and_then(|frame| frame.first_element_child())
+                .and_then(|board| board.dyn_into::<web_sys::HtmlElement>().ok())
+                .map_or(0, |board| board.offset_width());
+            let screen = window()
+                .inner_width()
+                .ok()
+                .zip(window().inner_height().ok())
+                .and_then(|(width, height)| Some(width.as_f64()?.min(height.as_f64()?)));
+            model.fullscreen_scale = match screen {
+                Some(screen) if board_width > 0 => {
+                    0.9 * screen / (f64::from(board_width) * ui_scale)
+                }
+                _ => 1.0,
+            };
+            return false;
+        }
+        // Picking another game leaves the tournament.
+        Msg::VariantChanged(index) => {
+            if let Some((variant, _)) = index.parse().ok().and_then(|i: usize| VARIANTS.get(i)) {
+                model.tournament = None;
+                model.tournament_turn = false;
+                model.versus = None;
+                model.ai_race = None;
+                model.ai_timer = None;
+                model.variant = *variant;
+                if model.mode == Mode::Endless {
+                    model.game_state = load_game(&model.storage, model.variant, model.settings);
+                    model.updated_at = js_sys::Date::now();
+                } else {
+                    start_game(model, orders);
+                }
+            }
+        }
+        Msg::ModeChanged(index) => {
+            if let Some((mode, _)) = index.parse().ok().and_then(|i: usize| MODES.get(i)) {
+                model.tournament = None;
+                model.tournament_turn = false;
+                model.versus = None;
+                model.ai_race = None;
+                model.ai_timer = None;
+                model.mode = *mode;
+                model.leaderboard = load_leaderboard(&model.storage, model.mode);
+                match model.mode {
+                    Mode::Endless => {
+                        model.timer = None;
+                        model.paused_at = None;
+                        model.game_state = load_game(&model.storage, model.variant, model.settings);
+                        orders.send_msg(crate::Msg::Settings(crate::page::settings::Msg::Sync));
+                    }
+                    // Editing starts from the saved game of the variant.
+                    Mode::Editor => {
+                        model.timer = None;
+                        model.paused_at = None;
+                        model.game_state = load_game(&model.storage, model.variant, model.settings);
+                    }
+                    _ => start_game(model, orders),
+                }
+            }
+        }
+        Msg::PuzzleSelected(index) => {
+            if index < PUZZLES.len() {
+                model.puzzle = index;
+                start_game(model, orders);
+            }
+        }
+        Msg::EditorValueSelected(value) => {
+            model.editor_value = value;
+        }
+        Msg::CellClicked(index) => {
+            if model.mode == Mode::Editor {
+                model.game_state.set_value(index, model.editor_value);
+            } else if let Some(power_up) = model.power_up {
+                // Clicking a picked tile again unpicks it.
+                match model.targets.iter().position(|&i| i == index) {
+                    Some(picked) => {
+                        model.targets.remove(picked);
+                    }
+                    None => model.targets.push(index),
+                }
+
+                if model.targets.len() == power_up.targets() {
+                    model.game_state.use_power_up(power_up, &model.targets);
+                    model.power_up = None;
+                    model.targets.clear();
+                    model.updated_at = js_sys::Date::now();
+                }
+            }
+        }
+        Msg::Shuffle => {
+            if model.game_state.shuffle() {
+                model.targets.clear();
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::PowerUpSelected(power_up) => {
+            model.targets.clear();
+            model.power_up = if model.power_up == Some(power_up) {
+                None
+            } else {
+                Some(power_up)
+            };
+        }
+        // Only boards of the same shape are taken, the rest of the game stays as it is.
+        Msg::BoardPasted(text) => {
+            if let Ok(pasted) = text.parse::<GameState>() {
+                if pasted.board() == model.game_state.board() {
+                    for (index, &tile) in pasted.cells().iter().enumerate() {
+                        model.game_state.set_tile(index, tile);
+                    }
+                }
+            }
+        }
+        Msg::EditorCleared => {
+            for index in 0..model.game_state.cells().len() {
+                model.game_state.set_value(index, 0);
+            }
+        }
+        Msg::PlayFromHere => {
+            // The edited board replaces the saved game of the variant.
+            model.mode = Mode::Endless;
+            model.updated_at = js_sys::Date::now();
+        }
+        // Only the end of the run changes anything that is saved.
+        Msg::Tick => {
+            model.time_left = model.time_left.saturating_sub(1);
+            if model.time_left != 0 {
+                return false;
+            }
+            model.timer = None;
+            model.record_run();
+            model.record_game(orders);
+        }
+        // Unless the board changed in the meantime, or was projected already.
+        Msg::ProjectionDue(board) => {
+            if !model.settings.show_projection
+                || model.game_state.board_hash() != board
+                || model
+                    .projection
+                    .is_some_and(|(projected, _)| projected == board)
+            {
+                return false;
+            }
+            let weights = model.config.options.ai_weights.unwrap_or_default();
+            // The same spawns for every projection of the game, so two in a
+            // row differ by the moves more than by luck.
+            let projector = Projector::new(
+                &model.game_state,
+                PROJECTION_PLAYOUTS,
+                &weights,
+                &mut GameRng::new(model.game_state.seed()),
+            );
+            model.projector = Some((board, projector));
+            orders.send_msg(crate::Msg::Game(Msg::ProjectionPlayed));
+        }
+        // Another slice of the projection, the next one after the page has had
+        // its turn. Boards moved on from are dropped, their own is due.
+        Msg::ProjectionPlayed => {
+            let (board, projector) = match model.projector.as_mut() {
+                Some(projector) => projector,
+                None => return false,
+            };
+            if !model.settings.show_projection || model.game_state.board_hash() != *board {
+                model.projector = None;
+                return false;
+            }
+            match projector.play(PROJECTION_SLICE) {
+                Some(projection) => {
+                    model.projection_change = model
+                        .projection
+                        .map(|(_, before)| projection.mean - before.mean);
+                    model.projection = Some((*board, projection));
+                    model.projector = None;
+                }
+                None => {
+                    orders
+                        .perform_cmd(cmds::timeout(0, || crate::Msg::Game(Msg::ProjectionPlayed)));
+                }
+            }
+        }
+        // Unless the player moved on to another game in the meantime.
+        Msg::AutoRestarted(seed) => {
+            if model.game_state.seed() != seed || !model.is_finished() {
+                return false;
+            }
+            start_game(model, orders);
+        }
+    }
+    true
+}
+
+// Above the board: the challenge taken up, the last move read out and the
+// picks of game and mode.
+pub fn view_above_board(model: &Model) -> Vec<Node<Msg>> {
+    nodes![
+        model.challenge.as_ref().map(|challenge| view_challenge(
+            challenge,
+            model.game_state.score(),
+            model.is_finished()
+        )),
+        view_announcement(&model.last_move, model.game_state.board()),
+        view_above(
+            model.variant,
+            model.mode,
+            model.fullscreen_enabled.then_some(model.fullscreen),
+            model.can_pause().then_some(model.paused_at.is_some())
+        ),
+    ]
+}
+
+// What goes fullscreen, the board itself can't be scaled up once it is. Also
+// the board of replays.
+pub fn view_board(model: &Model) -> Node<Msg> {
+    let board = model.game_state.board();
+    let shown = model.shown_game_state();
+    let prefix = &model.config.options.class_prefix;
+    div![
+        C![
+            "game-frame",
+            IF!(model.fullscreen => "fullscreen"),
+            IF!(model.races() || model.ai_race.is_some() => "versus")
+        ],
+        attrs! {At::Dir => "ltr"},
+        el_ref(&model.game_frame),
+        IF!(model.fullscreen => style! {"--fullscreen-scale" => model.fullscreen_scale}),
+        div![
+            // Focusable, so clicking the board takes the keys back from the page.
+            attrs! {At::TabIndex => 0},
+            ev(Ev::PointerDown, |ev| {
+                let ev: web_sys::PointerEvent = ev.unchecked_into();
+                (ev.is_primary() && ev.button() == 0)
+                    .then(|| Msg::DragStarted(ev.client_x(), ev.client_y()))
+            }),
+            // Only when asked for, the page scrolls as usual otherwise.
+            IF!(model.settings.wheel_moves => ev(Ev::Wheel, |ev| {
+                ev.prevent_default();
+                let ev: web_sys::WheelEvent = ev.unchecked_into();
+                // Shift turns a plain wheel sideways.
+                if ev.shift_key() && ev.delta_x() == 0.0 {
+                    Msg::Wheeled(ev.delta_y(), 0.0)
+                } else {
+                    Msg::Wheeled(ev.delta_x(), ev.delta_y())
+                }
+            })),
+            C![
+                prefixed(prefix, "game-container"),
+                model.settings.theme.class(),
+                IF!(model.game_state.rules() == MergeRule::Threes => "threes"),
+                IF!(model.game_state.rules() == MergeRule::Triples => "triples"),
+                IF!(model.game_state.wraps() => "wrap"),
+                IF!(board == Board::Hex => "hex"),
+                IF!(board == Board::Cube => "cube"),
+                IF!(model.mode == Mode::Editor => "editor"),
+                IF!(model.power_up.is_some() => "targeting"),
+                IF!(model.preview().is_some() => "previewing"),
+                IF!(model.paused_at.is_some() => "paused"),
+                IF!(model.awaits_turn() => "between-turns")
+            ],
+            match &model.tournament {
+                Some(tournament) if !model.tournament_turn => view_next_turn(tournament),
+                _ if model.preview().is_none() => view_result(model),
+                _ => empty![],
+            },
+            IF!(model.paused_at.is_some() => view_paused()),
+            view_celebration(model.celebration),
+            view_move_indicator(model),
+            IF!(model.game_state.wraps() => view_wrap_edges()),
+            view_grid(
+                shown,
+                model.mode == Mode::Editor || model.power_up.is_some(),
+                prefix
+            ),
+            view_tiles(shown, &model.targets, &model.settings, prefix),
+            IF!(model.settings.spawn_odds => view_spawn_odds(shown, prefix)),
+            view_ghost(model)
+        ],
+        model
+            .versus
+            .as_ref()
+            .and_then(|versus| versus.opponent.as_ref())
+            .map(|opponent| view_opponent(
+                if opponent.name.is_empty() {
+                    "Opponent"
+                } else {
+                    &opponent.name
+                },
+                &opponent.game_state,
+                opponent.score,
+                model.game_state.score(),
+                &model.settings,
+                prefix
+            )),
+        model.ai_race.as_ref().map(|race| view_opponent(
+            "Computer",
+            &race.game_state,
+            race.game_state.score(),
+            model.game_state.score(),
+            &model.settings,
+            prefix
+        ))
+    ]
+}
+
+// What helps playing, below the board.
+pub fn view_below_board(model: &Model) -> Vec<Node<Msg>> {
+    let prefix = &model.config.options.class_prefix;
+    nodes![
+        IF!(model.settings.show_splits && model.times_splits() => {
+            view_splits(&model.splits, &model.pace)
+        }),
+        IF!(model.settings.show_outlook => view_outlook(model)),
+        IF!(model.settings.show_projection => view_projection(model)),
+        IF!(model.settings.show_grades => {
+            view_accuracy(&model.accuracy, model.game_state.move_count())
+        }),
+        model
+            .analysis
+            .as_ref()
+            .filter(|_| model.is_finished())
+            .map(|analysis| {
+                view_analysis(analysis, model.game_state.score(), &model.settings, prefix)
+            }),
+        IF!(matches!(
+            model.mode,
+            Mode::Endless | Mode::TimeAttack | Mode::MoveLimit | Mode::ScoreTarget | Mode::Ladder
+        ) && !model.races() && model.ai_race.is_none() => {
+            view_power_ups(&model.game_state, model.power_up)
+        }),
+        IF!(model.settings.scanning => view_scan(model)),
+        view_move_pad(model),
+        view_board_actions(),
+    ]
+}
+
+// Playing with others, the controls and what the mode adds, below the
+// history and the archive.
+pub fn view_below_history(model: &Model) -> Vec<Node<Msg>> {
+    let board = model.game_state.board();
+    nodes![
+        match &model.tournament {
+            Some(tournament) => view_tournament(tournament),
+            None => view_tournament_setup(&model.tournament_names, model.tournament_mode),
+        },
+        view_versus(
+            model.versus.as_ref(),
+            &model.versus_server,
+            &model.versus_room
+        ),
+        view_ai_race(model.ai_race.as_ref(), model.ai_pace, model.ai_brain),
+        view_broadcast(model.broadcast.as_ref()),
+        view_controls(board),
+        match model.mode {
+            Mode::Endless => empty![],
+            Mode::Puzzle => view_puzzles(model),
+            Mode::Editor => view_editor(model.editor_value, &model.game_state),
+            Mode::ScoreTarget | Mode::Ladder => empty![],
+            _ => view_leaderboard(model.mode, &model.leaderboard),
+        },
+    ]
+}
+
+// Keys go to the game while focus is on the board or on nothing that takes
+// keys itself, like a text field or a select.
+pub fn is_game_focused(target: Option<web_sys::EventTarget>, prefix: &str) -> bool {
+    let element = match target.and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok()) {
+        Some(element) => element,
+        None => return true,
+    };
+
+    if let Ok(Some(_)) = element.closest(&format!(".{}game-container", prefix)) {
+        return true;
+    }
+
+    !(element.has_type::<web_sys::HtmlInputElement>()
+        || element.has_type::<web_sys::HtmlTextAreaElement>()
+        || element.has_type::<web_sys::HtmlSelectElement>()
+        || element.is_content_editable())
+}
+
+// Plays the move, or queues it while the tiles are still sliding. Returns
+// whether the board changed.
+pub fn request_move(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    direction: Direction,
+) -> bool {
+    // Other pages don't show the game, replays show a past one.
+    if model.page != Page::Game {
+        return false;
+    }
+    // The tutorial takes the moves while it is shown.
+    if model.tutorial.is_some() {
+        orders.send_msg(crate::Msg::Tutorial(tutorial::Msg::Moved(direction)));
+        return false;
+    }
+    // Moving again mid-slide would leave the tiles on screen behind the board.
+    if model.sliding {
+        if model.queued_moves.len() < model.settings.input_buffer {
+            model.queued_moves.push_back(direction);
+        }
+        return false;
+    }
+
+    play_move(model, orders, direction)
+}
+
+// Plays the move and everything it sets off in the current mode. Returns
+// whether the board changed.
+fn play_move(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    direction: Direction,
+) -> bool {
+    if model.config.options.read_only
+        || model.paused_at.is_some()
+        || model.awaits_turn()
+        || model.spectator.is_some()
+    {
+        return false;
+    }
+    if model.is_finished() {
+        model.queued_moves.clear();
+        return false;
+    }
+
+    let weights = model.config.options.ai_weights.unwrap_or_default();
+    let graded = grade::grade_move(&model.game_state, direction, &weights);
+    let result = model.game_state.move_tiles(direction);
+    if !result.moved {
+        return false;
+    }
+    model.accuracy.record(graded);
+    if model.races() {
+        if let Some(versus) = &model.versus {
+            versus.send(&multiplayer::Message::Moved {
+                direction,
+                score: model.game_state.score(),
+            });
+        }
+    }
+    if result.score_gained > 0 {
+        model
+            .config
+            .options
+            .callbacks
+            .score(model.game_state.score());
+    }
+    if model.mode == Mode::HotSeat {
+        model.hot_seat.play(result.score_gained);
+    }
+    if let Some(race) = model.ai_race.as_mut() {
+        race.settle(&model.game_state);
+        if race.is_over() {
+            model.ai_timer = None;
+        }
+    }
+    model.last_move = result;
+
+    model.sliding = true;
+    orders.perform_cmd(cmds::timeout(SLIDE_MS, || {
+        crate::Msg::Game(Msg::SlideEnded)
+    }));
+
+    let events = model.game_state.drain_events();
+    let game_over = events.contains(&GameEvent::GameOver);
+    if game_over {
+        model.analysis = analysis::analyze(&model.game_state, &weights);
+    }
+
+    // Only moves that change the board use up the budget.
+    if model.mode == Mode::Puzzle {
+        model.moves_left = model.moves_left.saturating_sub(1);
+    }
+
+    // Using up the last move ends the game too.
+    if model.mode == Mode::MoveLimit && game_over {
+        model.record_run();
+    }
+
+    // A stuck board ends the run before the clock does.
+    if game_over && model.mode == Mode::TimeAttack {
+        model.timer = None;
+        model.record_run();
+    }
+
+    if model.mode == Mode::Ladder {
+        let rung = goal::climb(&LADDER, model.rung, &model.game_state);
+        // The last goal is celebrated by the result screen instead.
+        model.celebration = (rung > model.rung && rung < LADDER.len()).then(|| LADDER[rung - 1]);
+        model.rung = rung;
+    }
+
+    if model.mode == Mode::ScoreTarget && model.is_finished() {
+        model.finished_at = Some(js_sys::Date::now());
+    }
+
+    if model.mode == Mode::Puzzle && model.puzzle().is_solved(&model.game_state) {
+        model.solved_puzzles.insert(model.puzzle().name.to_string());
+    }
+
+    if game_over && model.keeps_records() {
+        model.stats.record_final_board(&model.game_state);
+    }
+    // Finished games never get here again, so each is recorded once.
+    if model.is_finished() {
+        model.record_game(orders);
+    }
+    if model.keeps_records() {
+        for event in events {
+            model.stats.record_event(event);
+        }
+        crate::page::achievements::unlock(model, orders);
+    }
+    if model.times_splits() {
+        let elapsed = js_sys::Date::now() - model.started_at;
+        if model.splits.reach(model.game_state.max_tile(), elapsed) {
+            model.best_splits = model.best_splits.best(&model.splits);
+        }
+    }
+    refresh_outlook(model);
+    schedule_projection(model, orders);
+    model.updated_at = js_sys::Date::now();
+
+    true
+}
+
+pub fn schedule_projection(model: &Model, orders: &mut impl Orders<crate::Msg>) {
+    if !model.settings.show_projection {
+        return;
+    }
+    let board = model.game_state.board_hash();
+    orders.perform_cmd(cmds::timeout(PROJECTION_DELAY_MS, move || {
+        crate::Msg::Game(Msg::ProjectionDue(board))
+    }));
+}
+
+// Works the outlook out again if it's shown and the board changed. The
+// playouts draw their spawns from the board, so a board always gets the
+// same outlook.
+pub fn refresh_outlook(model: &mut Model) {
+    if !model.settings.show_outlook {
+        return;
+    }
+    let hash = model.game_state.board_hash();
+    if model.outlook.is_some_and(|(board, _)| board == hash) {
+        return;
+    }
+    let outlook = ai::outlook(
+        &model.game_state,
+        OUTLOOK_PLAYOUTS,
+        &Weights::default(),
+        &mut GameRng::new(hash),
+    );
+    model.outlook = Some((hash, outlook));
+}
+
+async fn fetch_bytes(url: &str) -> fetch::Result<Vec<u8>> {
+    Request::new(url)
+        .fetch()
+        .await?
+        .check_status()?
+        .bytes()
+        .await
+}
+
+async fn fetch_network() -> Result<NTupleEvaluator, String> {
+    let bytes = fetch_bytes(NTUPLE_URL)
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+    NTupleEvaluator::from_bytes(&bytes)
+}
+
+// Stops the clock, the time paused doesn't count towards the run.
+fn pause(model: &mut Model) {
+    if model.paused_at.is_none() && model.can_pause() {
+        model.paused_at = Some(js_sys::Date::now());
+        model.timer = None;
+    }
+}
+
+fn resume(model: &mut Model, orders: &mut impl Orders<crate::Msg>) {
+    if let Some(paused_at) = model.paused_at.take() {
+        model.started_at += js_sys::Date::now() - paused_at;
+        if model.mode == Mode::TimeAttack {
+            model.timer = Some(
+                orders.stream_with_handle(streams::interval(1000, || crate::Msg::Game(Msg::Tick))),
+            );
+        }
+    }
+}
+
+// Plays the challenge's game from its seed, with its variant, mode and
+// spawns. The rest of the settings stay the player's own.
+pub fn start_challenge(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    challenge: Challenge,
+) {
+    model.tournament = None;
+    model.tournament_turn = false;
+    model.versus = None;
+    model.ai_race = None;
+    model.ai_timer = None;
+    model.variant = challenge.variant;
+    model.mode = challenge.mode;
+    model.leaderboard = load_leaderboard(&model.storage, model.mode);
+    let settings = Settings {
+        spawn: challenge.spawn,
+        combo: challenge.combo,
+        ..model.settings
+    };
+    let game_state = GameState::with_seed(challenge.variant, challenge.spawn, challenge.seed);
+    start_game_with(model, orders, apply_settings(game_state, settings));
+    model.challenge = Some(challenge);
+}
+
+// Starts the race agreed with the first opponent to say hello, answering
+// them so they hear of this side too. Hellos from anyone joining later are
+// left unanswered, a room has two players.
+pub fn start_versus(model: &mut Model, orders: &mut impl Orders<crate::Msg>, theirs: Hello) {
+    let versus = match model.versus.as_mut() {
+        Some(versus) if versus.opponent.is_none() => versus,
+        _ => return,
+    };
+    versus.send(&multiplayer::Message::Hello(versus.hello.clone()));
+    let game = versus.hello.agree(&theirs).clone();
+    versus.opponent = Some(Opponent::new(theirs.name, &game));
+
+    // Both clocks start together, give or take the network.
+    model.tournament = None;
+    model.tournament_turn = false;
+    model.ai_race = None;
+    model.ai_timer = None;
+    model.variant = game.variant;
+    model.mode = Mode::TimeAttack;
+    model.leaderboard = load_leaderboard(&model.storage, model.mode);
+    let settings = Settings {
+        spawn: game.spawn,
+        combo: game.combo,
+        ..model.settings
+    };
+    start_game_with(model, orders, apply_settings(game.game_state(), settings));
+}
+
+// Follows the channel's broadcast in place of the game on this page.
+pub fn spectate(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    server: &str,
+    channel: String,
+) {
+    let url = multiplayer::room_url(server, &channel);
+    let socket = WebSocket::builder(url, orders)
+        .on_open(|| crate::Msg::Game(Msg::SpectatorOpened))
+        .on_message(|message| crate::Msg::Game(Msg::SpectatorReceived(message)))
+        // Errors close the socket too, they'd only be told twice.
+        .on_close(|_| crate::Msg::Game(Msg::SpectatorClosed))
+        .build_and_open();
+    match socket {
+        Ok(socket) => model.spectator = Some(Spectator::new(socket, channel)),
+        Err(_) => {
+            let note = "Couldn't connect to the broadcast.".to_string();
+            show_toast(model, orders, note, Severity::Error);
+        }
+    }
+}
+
+// Describes the last move for screen readers, e.g. "Merged 8 at row 1, column 1. +8 points."
+fn view_announcement(result: &MoveResult, board: Board) -> Node<Msg> {
+    let cell = |index: usize| {
+        let (x, y) = board.topology().position(index);
+        format!("row {}, column {}", y as usize + 1, x as usize + 1)
+    };
+
+    let mut sentences: Vec<String> = result
+        .merges
+        .iter()
+        .map(|merge| format!("Merged {} at {}.", merge.value, cell(merge.index)))
+        .collect();
+    if result.score_gained > 0 {
+        sentences.push(format!("+{} points.", result.score_gained));
+    }
+    if let Some((index, value)) = result.spawned {
+        sentences.push(format!("New {} at {}.", value, cell(index)));
+    }
+
+    div![
+        C!["visually-hidden"],
+        attrs! {At::from("aria-live") => "polite"},
+        sentences.join(" ")
+    ]
+}
+
+// `fullscreen` is whether the board is fullscreen, if the browser allows it at all.
+fn view_above(
+    variant: Variant,
+    mode: Mode,
+    fullscreen: Option<bool>,
+    paused: Option<bool>,
+) -> Node<Msg> {
+    div![
+        C!["above-game"],
+        p![
+            C!["game-intro"],
+            "Built with ",
+            strong!("Seed"),
+            ", a Rust framework."
+        ],
+        a![
+            C!["restart-button"],
+            "New Game",
+            ev(Ev::Click, |_| Msg::NewGame)
+        ],
+        fullscreen.map(|fullscreen| a![
+            C!["restart-button", "fullscreen-button"],
+            if fullscreen {
+                "Exit Fullscreen"
+            } else {
+                "Fullscreen"
+            },
+            ev(Ev::Click, |_| Msg::FullscreenToggled)
+        ]),
+        paused.map(|paused| a![
+            C!["restart-button", "pause-button"],
+            attrs! {At::Title => "P"},
+            if paused { "Resume" } else { "Pause" },
+            ev(Ev::Click, |_| Msg::PauseToggled)
+        ]),
+        view_variants(variant),
+        view_modes(mode)
+    ]
+}
+
+fn view_variants(selected: Variant) -> Node<Msg> {
+    select![
+        C!["variant-select"],
+        VARIANTS.iter().enumerate().map(|(i, (variant, name))| {
+            option![
+                attrs! {
+                    At::Value => i,
+                    At::Selected => (*variant == selected).as_at_value()
+                },
+                name
+            ]
+        }),
+        input_ev(Ev::Change, Msg::VariantChanged)
+    ]
+}
+
+fn view_modes(selected: Mode) -> Node<Msg> {
+    select![
+        C!["variant-select"],
+        MODES.iter().enumerate().map(|(i, (mode, name))| {
+            option![
+                attrs! {
+                    At::Value => i,
+                    At::Selected => (*mode == selected).as_at_value()
+                },
+                name
+            ]
+        }),
+        input_ev(Ev::Change, Msg::ModeChanged)
+    ]
+}
+
+fn view_result(model: &Model) -> Node<Msg> {
+    let won = model.game_state.is_won();
+    if !model.is_finished() && !won {
+        return empty![];
+    }
+    // Games starting over by themselves only leave a toast.
+    if model.is_finished() && model.restarts_itself() {
+        return empty![];
+    }
+
+    div![
+        C!["game-message", if won { "game-won" } else { "game-over" }],
+        p![match (model.mode, model.game_state.target()) {
+            (Mode::TimeAttack, _) if model.time_left == 0 => "Time's up!".to_string(),
+            (Mode::Puzzle, _) if model.puzzle().is_solved(&model.game_state) => {
+                "Solved!".to_string()
+            }
+            (Mode::ScoreTarget, _) if won => "Target reached!".to_string(),
+            (Mode::Ladder, _) => "Ladder complete!".to_string(),
+            _ if model.ai_race.as_ref().is_some_and(Race::is_over) => {
+                match model.ai_race.as_ref().and_then(Race::winner) {
+                    Some(Racer::Player) => "You win the race!".to_string(),
+                    Some(Racer::Computer) => "The computer wins!".to_string(),
+                    None => "A draw!".to_string(),
+                }
+            }
+            (Mode::HotSeat, _) if model.is_finished() => match model.hot_seat.leader() {
+                Some(player) => format!("Player {} wins!", player + 1),
+                None => "A draw!".to_string(),
+            },
+            (_, Some(target)) if won => format!("You reached {}!", target),
+            _ => "Out of moves!".to_string(),
+        }],
+        div![
+            C!["lower"],
+            div![
+                C!["result"],
+                match (model.mode, model.finished_at) {
+                    (Mode::HotSeat, _) => {
+                        let scores = model.hot_seat.scores();
+                        format!(
+                            "Player 1: {} · Player 2: {} · Together: {}",
+                            scores[0],
+                            scores[1],
+                            model.game_state.score()
+                        )
+                    }
+                    (Mode::ScoreTarget, Some(finished_at)) => {
+                        let seconds = ((finished_at - model.started_at) / 1000.0) as u32;
+                        format!(
+                            "Moves: {} · Time: {}:{:02}",
+                            model.game_state.move_count(),
+                            seconds / 60,
+                            seconds % 60
+                        )
+                    }
+                    _ => format!(
+                        "Score: {} · Best tile: {}",
+                        model.game_state.score(),
+                        model.game_state.max_tile()
+                    ),
+                }
+            ],
+            IF!(!model.plays_others() => a!["Try again", ev(Ev::Click, |_| Msg::NewGame)]),
+            a!["Share", ev(Ev::Click, |_| Msg::Share)],
+            IF!(!matches!(model.mode, Mode::Puzzle | Mode::Editor) && !model.plays_others() => a![
+                "Challenge a Friend",
+                ev(Ev::Click, |_| Msg::Challenge)
+            ])
+        ]
+    ]
+}
+
+// The time to every milestone of the game, ahead of the best in green and
+// behind it in red.
+fn view_splits(splits: &Splits, pace: &Splits) -> Node<Msg> {
+    table![
+        C!["splits"],
+        splits
+            .iter()
+            .zip(pace.iter())
+            .map(|((milestone, time), (_, best))| {
+                let delta = time.zip(best).map(|(time, best)| time - best);
+                tr![
+                    C![IF!(time.is_some() => "split-reached")],
+                    td![C!["split-tile"], milestone],
+                    td![
+                        C!["split-time"],
+                        time.map_or("-".to_string(), splits::format_time)
+                    ],
+                    td![
+                        C![
+                            "split-delta",
+                            delta.map(|delta| if delta < 0.0 {
+                                "split-ahead"
+                            } else {
+                                "split-behind"
+                            })
+                        ],
+                        delta.map(splits::format_delta)
+                    ],
+                    td![C!["split-best"], best.map(splits::format_time)]
+                ]
+            })
+    ]
+}
+
+// The range of final scores projected, faded while the board waits for its
+// own projection.
+fn view_projection(model: &Model) -> Node<Msg> {
+    let (board, projection) = match model.projection {
+        Some(projection) => projection,
+        None => return empty![],
+    };
+
+    div![
+        C![
+            "projection",
+            IF!(board != model.game_state.board_hash() => "projection-stale")
+        ],
+        attrs! {At::Title => "Where games the computer played on from here ended"},
+        span![C!["projection-label"], "Final score"],
+        span![
+            C!["projection-value"],
+            format!(
+                "{} – {}",
+                share::thousands(projection.low),
+                share::thousands(projection.high)
+            )
+        ],
+        model.projection_change.map(|change| {
+            span![
+                C![
+                    "projection-change",
+                    if change < 0.0 {
+                        "projection-down"
+                    } else {
+                        "projection-up"
+                    }
+                ],
+                format!("{:+.0}", change)
+            ]
+        })
+    ]
+}
+
+// The grade of the last move, keyed by it so every grade fades in anew, and
+// the accuracy of the game so far.
+fn view_accuracy(accuracy: &Accuracy, move_count: usize) -> Node<Msg> {
+    let percent = match accuracy.percent() {
+        Some(percent) => percent,
+        None => return empty![],
+    };
+
+    div![
+        C!["accuracy"],
+        accuracy.last.map(|graded| {
+            span![
+                el_key(&move_count),
+                C!["grade", graded.grade.class()],
+                attrs! {At::Title => format!("The computer's move: {:?}", graded.best)},
+                graded.grade.label()
+            ]
+        }),
+        span![C!["accuracy-value"], format!("Accuracy {:.0}%", percent)]
+    ]
+}
+
+// The report on a lost game, each blunder with the board the computer's move
+// would have left.
+fn view_analysis(
+    analysis: &Analysis,
+    score: usize,
+    settings: &Settings,
+    prefix: &str,
+) -> Node<Msg> {
+    div![
+        C!["analysis"],
+        h3!["Game Analysis"],
+        ul![
+            C!["analysis-summary"],
+            analysis
+                .accuracy
+                .map(|accuracy| li![format!("Accuracy: {:.0}%", accuracy)]),
+            li![
+                attrs! {At::Title => "The score had every tile landed been worth the average"},
+                format!(
+                    "Luck-adjusted score: {} (scored {})",
+                    share::thousands(analysis.luck_adjusted_score),
+                    share::thousands(score)
+                )
+            ],
+            analysis.luck.luck().map(|luck| {
+                let percent = (luck * 100.0).round();
+                li![
+                    C!["analysis-luck"],
+                    if percent > 0.0 {
+                        format!("You were {}% luckier than average", percent)
+                    } else if percent < 0.0 {
+                        format!("You were {}% unluckier than average", -percent)
+                    } else {
+                        "You were as lucky as average".to_string()
+                    },
+                    ul![
+                        li![format!(
+                            "Tiles above {}: {} of {}, {:.0} expected",
+                            analysis.luck.smallest,
+                            analysis.luck.big,
+                            analysis.luck.spawns,
+                            analysis.luck.expected_big
+                        )],
+                        li![
+                            attrs! {At::Title => "Cells worse for the tile than the average empty cell"},
+                            format!(
+                                "Tiles in bad cells: {}, {:.0} expected",
+                                analysis.luck.bad_cells, analysis.luck.expected_bad_cells
+                            )
+                        ]
+                    ]
+                ]
+            }),
+            analysis.lost_at.map(|lost_at| li![match lost_at {
+                0 => "Lost from the start".to_string(),
+                lost_at => format!("Beyond saving after move {}", lost_at),
+            }])
+        ],
+        if analysis.blunders.is_empty() {
+            p![C!["analysis-hint"], "No blunders, well played."]
+        } else {
+            ol![
+                C!["analysis-blunders"],
+                analysis.blunders.iter().map(|blunder| {
+                    li![
+                        C!["analysis-blunder"],
+                        p![format!(
+                            "Move {}: {:?}, {:?} was better",
+                            blunder.move_number, blunder.played, blunder.graded.best
+                        )],
+                        div![
+                            C!["analysis-board"],
+                            attrs! {At::AriaHidden => true},
+                            view_still_board(&blunder.better, settings, prefix)
+                        ]
+                    ]
+                })
+            ]
+        }
+    ]
+}
+
+// The chances of the board, once worked out for it. Boards changed by other
+// means than moves, e.g. undone, show nothing until the next move.
+fn view_outlook(model: &Model) -> Node<Msg> {
+    let outlook = match model.outlook {
+        Some((board, outlook)) if board == model.game_state.board_hash() => outlook,
+        _ => return empty![],
+    };
+    let percent = |chance: f64| match (chance * 100.0).round() {
+        percent if percent == 0.0 && chance > 0.0 => "<1%".to_string(),
+        percent => format!("{}%", percent),
+    };
+
+    div![
+        C!["outlook"],
+        outlook.win.zip(model.game_state.target()).map(|(win, target)| {
+            div![
+                C!["outlook-item"],
+                attrs! {At::Title => "A rough guess: as risky as the next moves are, all the way to the tile"},
+                span![C!["outlook-label"], format!("Reaching {}", target)],
+                span![C!["outlook-value"], percent(win)]
+            ]
+        }),
+        div![
+            C!["outlook-item"],
+            attrs! {At::Title => "The share of games the computer played on from here that were still going"},
+            span![C!["outlook-label"], format!("Next {} moves", OUTLOOK_MOVES)],
+            span![C!["outlook-value"], percent(outlook.survival)]
+        ]
+    ]
+}
+
+// Whose score the game is out to beat and, once it is over, whether it did.
+fn view_challenge(challenge: &Challenge, score: usize, finished: bool) -> Node<Msg> {
+    let name = if challenge.name.is_empty() {
+        "your friend"
+    } else {
+        challenge.name.as_str()
+    };
+    let target = share::thousands(challenge.score);
+    let note = if !finished {
+        format!("Beat {}'s {} points, same seed, same tiles.", name, target)
+    } else if score > challenge.score {
+        format!(
+            "You beat {}'s {} points by {}!",
+            name,
+            target,
+            share::thousands(score - challenge.score)
+        )
+    } else {
+        format!(
+            "{} stays ahead by {} points.",
+            name,
+            share::thousands(challenge.score - score)
+        )
+    };
+
+    div![
+        C!["storage-error", "challenge"],
+        span![note],
+        a![
+            C!["storage-error-button"],
+            "Dismiss",
+            ev(Ev::Click, |_| Msg::ChallengeDismissed)
+        ]
+    ]
+}
+
+// Over the blurred board, so a paused run can't be planned ahead.
+// Over the last player's board, who plays next or who won.
+fn view_next_turn(tournament: &Tournament) -> Node<Msg> {
+    let (note, action) = match tournament.turn() {
+        Some(turn) => (
+            format!("Pass the device to {}", turn.player),
+            a!["Start", ev(Ev::Click, |_| Msg::TournamentTurnStarted)],
+        ),
+        None => (
+            format!("{} wins!", tournament.winner().unwrap_or_default()),
+            a!["Done", ev(Ev::Click, |_| Msg::TournamentAbandoned)],
+        ),
+    };
+
+    div![
+        C![
+            "game-message",
+            "game-tournament",
+            IF!(tournament.turn().is_none() => "game-won")
+        ],
+        p![note],
+        div![C!["lower"], action]
+    ]
+}
+
+// The opponent's board as it stands, beside the player's on wide screens.
+fn view_opponent(
+    name: &str,
+    game_state: &GameState,
+    their_score: usize,
+    score: usize,
+    settings: &Settings,
+    prefix: &str,
+) -> Node<Msg> {
+    let lead = match score.cmp(&their_score) {
+        std::cmp::Ordering::Greater => {
+            format!("You lead by {}", share::thousands(score - their_score))
+        }
+        std::cmp::Ordering::Less => {
+            format!("You trail by {}", share::thousands(their_score - score))
+        }
+        std::cmp::Ordering::Equal => "Level".to_string(),
+    };
+
+    div![
+        C!["opponent"],
+        h3![format!("{}: {}", name, share::thousands(their_score))],
+        p![C!["opponent-lead"], lead],
+        div![
+            C!["opponent-board"],
+            attrs! {At::AriaHidden => true},
+            view_still_board(game_state, settings, prefix)
+        ]
+    ]
+}
+
+// Someone else's game as their broadcast shows it, a few seconds behind.
+pub fn view_spectator(spectator: &Spectator, settings: &Settings, prefix: &str) -> Node<Msg> {
+    let game_state = spectator.follower.game_state.as_ref();
+
+    div![
+        C!["spectator"],
+        h2![format!("Watching {}", spectator.channel)],
+        p![match game_state {
+            Some(game_state) => format!(
+                "Score: {} · Best tile: {}",
+                share::thousands(game_state.score()),
+                game_state.max_tile()
+            ),
+            None => "Waiting for the game…".to_string(),
+        }],
+        game_state.map(|game_state| view_still_board(game_state, settings, prefix)),
+        a![
+            C!["versus-button"],
+            "Stop Watching",
+            ev(Ev::Click, |_| Msg::SpectateStopped)
+        ]
+    ]
+}
+
+// A board that isn't played on here, e.g. an opponent's.
+fn view_still_board(game_state: &GameState, settings: &Settings, prefix: &str) -> Node<Msg> {
+    div![
+        C![
+            prefixed(prefix, "game-container"),
+            settings.theme.class(),
+            IF!(game_state.rules() == MergeRule::Threes => "threes"),
+            IF!(game_state.rules() == MergeRule::Triples => "triples"),
+            IF!(game_state.wraps() => "wrap"),
+            IF!(game_state.board() == Board::Hex => "hex"),
+            IF!(game_state.board() == Board::Cube => "cube")
+        ],
+        view_grid(game_state, false, prefix),
+        view_tiles(game_state, &[], settings, prefix)
+    ]
+}
+
+fn view_paused() -> Node<Msg> {
+    div![
+        C!["game-message", "game-paused"],
+        p!["Paused"],
+        div![
+            C!["lower"],
+            a!["Resume", ev(Ev::Click, |_| Msg::PauseToggled)]
+        ]
+    ]
+}
+
+// Arrows on every edge, pointing at where tiles leaving the board come back in.
+fn view_wrap_edges() -> Node<Msg> {
+    div![
+        C!["wrap-edges"],
+        span![C!["wrap-edge", "wrap-edge-top"], "⇅"],
+        span![C!["wrap-edge", "wrap-edge-bottom"], "⇅"],
+        span![C!["wrap-edge", "wrap-edge-left"], "⇄"],
+        span![C!["wrap-edge", "wrap-edge-right"], "⇄"]
+    ]
+}
+
+// An arrow flashing on the edge the tiles moved towards, for the last move or
+// the previewed one.
+fn view_move_indicator(model: &Model) -> Node<Msg> {
+    let (direction, key) = match model.preview() {
+        Some(preview) => match preview.game_state.history().last() {
+            Some(Action::Move(direction)) => (*direction, format!("step-{}", preview.step)),
+            _ => return empty![],
+        },
+        None => match model.last_move.direction {
+            // Keyed by the move, so every move flashes anew.
+            Some(direction) => (
+                direction,
+                format!("move-{}", model.game_state.history().len()),
+            ),
+            None => return empty![],
+        },
+    };
+
+    DIRECTIONS
+        .iter()
+        .find(|&&(d, _, _)| d == direction)
+        .map_or(empty![], |&(_, arrow, name)| {
+            div![
+                el_key(&key),
+                C![
+                    "move-indicator",
+                    format!("move-indicator-{}", name.to_lowercase().replace(' ', "-"))
+                ],
+                attrs! {At::AriaHidden => "true"},
+                arrow
+            ]
+        })
+}
+
+// Shown over the board until the next move after a goal of the ladder is reached.
+fn view_celebration(celebration: Option<Goal>) -> Node<Msg> {
+    match celebration {
+        Some(goal) => div![
+            C!["goal-celebration"],
+            p!["Goal reached!"],
+            div![C!["goal-reached"], goal.to_string()]
+        ],
+        None => empty![],
+    }
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+    MODES
+        .iter()
+        .find(|(m, _)| *m == mode)
+        .map_or("", |(_, name)| name)
+}
+
+// Names to play a tournament with and the budget of every game.
+fn view_tournament_setup(names: &str, selected: Mode) -> Node<Msg> {
+    div![
+        C!["tournament"],
+        h2!["Tournament"],
+        p![format!(
+            "Up to {} players take turns on this device. Both players of a match get the same board, the higher score goes through.",
+            tournament::MAX_PLAYERS
+        )],
+        textarea![
+            C!["tournament-names"],
+            attrs! {
+                At::Rows => 4,
+                At::Placeholder => "One name per line",
+                At::Value => names
+            },
+            input_ev(Ev::Input, Msg::TournamentNamesChanged)
+        ],
+        select![
+            C!["variant-select"],
+            TOURNAMENT_MODES.iter().enumerate().map(|(i, &mode)| {
+                option![
+                    attrs! {
+                        At::Value => i,
+                        At::Selected => (mode == selected).as_at_value()
+                    },
+                    mode_name(mode)
+                ]
+            }),
+            input_ev(Ev::Change, Msg::TournamentModeChanged)
+        ],
+        a![
+            C!["tournament-button"],
+            "Start Tournament",
+            ev(Ev::Click, |_| Msg::TournamentStarted)
+        ]
+    ]
+}
+
+// Every round so far, the winner of each match in gold.
+fn view_tournament(tournament: &Tournament) -> Node<Msg> {
+    let view_player = |player: Option<usize>, score: Option<usize>, winner: Option<usize>| {
+        div![
+            C![
+                "tournament-player",
+                IF!(player.is_some() && player == winner => "tournament-winner")
+            ],
+            span![player.map_or("Bye", |player| tournament.player(player))],
+            span![C!["tournament-score"], score.map(share::thousands)]
+        ]
+    };
+
+    div![
+        C!["tournament"],
+        h2![format!("Tournament: {}", mode_name(tournament.mode()))],
+        div![
+            C!["tournament-rounds"],
+            tournament.rounds().iter().enumerate().map(|(i, round)| {
+                div![
+                    C!["tournament-round"],
+                    h3![format!("Round {}", i + 1)],
+                    round.iter().map(|m| {
+                        let winner = m.winner();
+                        div![
+                            C!["tournament-match"],
+                            // A bye goes through without a score.
+                            view_player(
+                                Some(m.players.0),
+                                m.scores.0.filter(|_| m.players.1.is_some()),
+                                winner
+                            ),
+                            view_player(m.players.1, m.scores.1, winner)
+                        ]
+                    })
+                ]
+            })
+        ],
+        a![
+            C!["tournament-button"],
+            "Abandon Tournament",
+            ev(Ev::Click, |_| Msg::TournamentAbandoned)
+        ]
+    ]
+}
+
+// Joining a room to race someone online, or the room joined.
+fn view_versus(versus: Option<&Versus>, server: &str, room: &str) -> Node<Msg> {
+    let versus = match versus {
+        Some(versus) => versus,
+        None => {
+            return div![
+                C!["versus"],
+                h2!["Versus"],
+                p!["Race a friend online: join the same room and both of you play the same board against the clock, each watching the other's."],
+                input![
+                    C!["versus-field"],
+                    attrs! {
+                        At::Type => "url",
+                        At::Placeholder => "Server, e.g. wss://example.com/rooms",
+                        At::Value => server
+                    },
+                    input_ev(Ev::Input, Msg::VersusServerChanged)
+                ],
+                input![
+                    C!["versus-field"],
+                    attrs! {
+                        At::Placeholder => "Room",
+                        At::Value => room
+                    },
+                    input_ev(Ev::Input, Msg::VersusRoomChanged)
+                ],
+                a![
+                    C!["versus-button"],
+                    "Join Room",
+                    ev(Ev::Click, |_| Msg::VersusJoined)
+                ]
+            ]
+        }
+    };
+
+    div![
+        C!["versus"],
+        h2!["Versus"],
+        p![match &versus.opponent {
+            None => format!("Waiting for someone to join {}…", versus.room),
+            Some(opponent) if opponent.name.is_empty() => format!("Racing in {}.", versus.room),
+            Some(opponent) => format!("Racing {} in {}.", opponent.name, versus.room),
+        }],
+        a![
+            C!["versus-button"],
+            "Leave Room",
+            ev(Ev::Click, |_| Msg::VersusLeft)
+        ]
+    ]
+}
+
+// Racing the computer on the same board, or the race under way.
+fn view_ai_race(race: Option<&Race>, pace: u32, brain: Brain) -> Node<Msg> {
+    div![
+        C!["versus"],
+        h2!["Race the Computer"],
+        p![match race.map(|race| (race.is_over(), race.winner())) {
+            None => "The computer plays a copy of your board beside it, same tiles in the same places. First to 2048 wins, or the higher score once either board is stuck.",
+            Some((false, _)) => "Racing the computer.",
+            Some((true, Some(Racer::Player))) => "You beat the computer.",
+            Some((true, Some(Racer::Computer))) => "The computer won this one.",
+            Some((true, None)) => "A draw with the computer.",
+        }],
+        select![
+            C!["variant-select"],
+            AI_PACES.iter().enumerate().map(|(i, &(value, name))| {
+                option![
+                    attrs! {
+                        At::Value => i,
+                        At::Selected => (value == pace).as_at_value()
+                    },
+                    name
+                ]
+            }),
+            input_ev(Ev::Change, Msg::AiPaceChanged)
+        ],
+        select![
+            C!["variant-select"],
+            AI_BRAINS.iter().enumerate().map(|(i, &(value, name))| {
+                option![
+                    attrs! {
+                        At::Value => i,
+                        At::Selected => (value == brain).as_at_value()
+                    },
+                    name
+                ]
+            }),
+            input_ev(Ev::Change, Msg::AiBrainChanged)
+        ],
+        a![
+            C!["versus-button"],
+            if race.is_some() { "Race Again" } else { "Start Race" },
+            ev(Ev::Click, |_| Msg::AiRaceStarted)
+        ],
+        race.map(|_| a![
+            C!["versus-button"],
+            "Leave Race",
+            ev(Ev::Click, |_| Msg::AiRaceLeft)
+        ])
+    ]
+}
+
+// Sending this game out for others to watch, on the server of the versus game.
+fn view_broadcast(broadcast: Option<&Broadcast>) -> Node<Msg> {
+    div![
+        C!["versus"],
+        h2!["Broadcast"],
+        match broadcast {
+            None => nodes![
+                p!["Stream your game to anyone with the link, a few seconds behind, over the server set under Versus."],
+                a![
+                    C!["versus-button"],
+                    "Start Broadcast",
+                    ev(Ev::Click, |_| Msg::BroadcastToggled)
+                ]
+            ],
+            Some(broadcast) => nodes![
+                p![format!("Broadcasting on {}.", broadcast.channel)],
+                a![
+                    C!["versus-button"],
+                    "Copy Link",
+                    ev(Ev::Click, |_| Msg::BroadcastLinkCopied)
+                ],
+                a![
+                    C!["versus-button"],
+                    "Stop Broadcast",
+                    ev(Ev::Click, |_| Msg::BroadcastToggled)
+                ]
+            ],
+        }
+    ]
+}
+
+fn view_leaderboard(mode: Mode, leaderboard: &Leaderboard) -> Node<Msg> {
+    div![
+        C!["leaderboard"],
+        h2![format!("{} Leaderboard", mode_name(mode))],
+        if leaderboard.entries().is_empty() {
+            p!["No finished runs yet."]
+        } else {
+            ol![leaderboard.entries().iter().map(|entry| {
+                li![format!(
+                    "{} points, best tile {}",
+                    entry.score, entry.best_tile
+                )]
+            })]
+        }
+    ]
+}
+
+fn view_puzzles(model: &Model) -> Node<Msg> {
+    let puzzle = model.puzzle();
+
+    div![
+        C!["puzzles"],
+        h2![format!(
+            "{}: {} in {} moves",
+            puzzle.name, puzzle.goal, puzzle.moves
+        )],
+        p![
+            "Allowed moves: ",
+            puzzle
+                .allowed
+                .iter()
+                .map(|direction| format!("{:?}", direction))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ],
+        ol![PUZZLES.iter().enumerate().map(|(i, puzzle)| {
+            li![
+                C![
+                    "puzzle",
+                    IF!(i == model.puzzle => "puzzle-selected"),
+                    IF!(model.solved_puzzles.contains(puzzle.name) => "puzzle-solved")
+                ],
+                a![puzzle.name, ev(Ev::Click, move |_| Msg::PuzzleSelected(i))]
+            ]
+        })]
+    ]
+}
+
+fn view_editor(selected: usize, game_state: &GameState) -> Node<Msg> {
+    let text = game_state.to_string();
+
+    div![
+        C!["editor-container"],
+        p!["Pick a value, then click cells on the board to place it."],
+        div![
+            C!["editor-palette"],
+            EDITOR_VALUES.iter().map(|&value| {
+                a![
+                    C![
+                        "editor-value",
+                        IF!(value == selected => "editor-value-selected")
+                    ],
+                    if value == 0 {
+                        "Empty".to_string()
+                    } else {
+                        value.to_string()
+                    },
+                    ev(Ev::Click, move |_| Msg::EditorValueSelected(value))
+                ]
+            })
+        ],
+        a![
+            C!["editor-button"],
+            "Clear",
+            ev(Ev::Click, |_| Msg::EditorCleared)
+        ],
+        a![
+            C!["editor-button"],
+            "Play from here",
+            ev(Ev::Click, |_| Msg::PlayFromHere)
+        ],
+        p!["Or copy the board below, or paste one in."],
+        textarea![
+            C!["editor-board"],
+            attrs! {
+                At::Rows => text.lines().count(),
+                At::SpellCheck => "false",
+                At::Value => text
+            },
+            input_ev(Ev::Change, Msg::BoardPasted)
+        ]
+    ]
+}
+
+// Cells of irregular boards are placed through the `--cell-x` and `--cell-y`
+// custom properties instead of the fixed `tile-position` classes.
+fn position_style(board: Board, index: usize) -> Style {
+    match board {
+        Board::Square => style! {},
+        _ => {
+            let (x, y) = board.topology().position(index);
+            style! {"--cell-x" => x, "--cell-y" => y}
+        }
+    }
+}
+
+fn view_power_ups(game_state: &GameState, selected: Option<PowerUp>) -> Node<Msg> {
+    let hint = POWER_UPS
+        .iter()
+        .find(|(power_up, _, _)| Some(*power_up) == selected)
+        .map(|(_, _, hint)| p![C!["power-up-hint"], hint]);
+
+    div![
+        C!["power-ups"],
+        POWER_UPS.iter().map(|&(power_up, name, _)| {
+            let count = game_state.power_up_count(power_up);
+
+            a![
+                C![
+                    "power-up",
+                    IF!(Some(power_up) == selected => "power-up-selected"),
+                    IF!(count == 0 => "power-up-empty")
+                ],
+                format!("{} ({})", name, count),
+                IF!(count > 0 => ev(Ev::Click, move |_| Msg::PowerUpSelected(power_up)))
+            ]
+        }),
+        a![
+            C![
+                "power-up",
+                IF!(game_state.shuffles_left() == 0 => "power-up-empty")
+            ],
+            format!("Shuffle ({})", game_state.shuffles_left()),
+            IF!(game_state.shuffles_left() > 0 => ev(Ev::Click, |_| Msg::Shuffle))
+        ],
+        hint
+    ]
+}
+
+// Buttons for the moves of the board; hovering one shows where it would take the tiles.
+fn view_move_pad(model: &Model) -> Node<Msg> {
+    div![
+        C!["move-pad"],
+        // Laid out like the arrows they show, whichever way the page reads.
+        attrs! {At::Dir => "ltr"},
+        DIRECTIONS
+            .iter()
+            .filter(|&&(direction, _, _)| model.allows(direction))
+            .map(|&(direction, arrow, name)| {
+                a![
+                    C!["move-pad-button"],
+                    attrs! {At::AriaLabel => name, At::Title => name},
+                    arrow,
+                    ev(Ev::MouseEnter, move |_| Msg::GhostShown(direction)),
+                    ev(Ev::MouseLeave, |_| Msg::GhostHidden),
+                    ev(Ev::Click, move |_| Msg::PadPressed(direction))
+                ]
+            })
+    ]
+}
+
+// The chance of the next tile spawning in each empty cell, and the value it
+// would have on average, as if it spawned on the board as it is.
+fn view_spawn_odds(game_state: &GameState, prefix: &str) -> Node<Msg> {
+    let board = game_state.board();
+
+    div![
+        C!["spawn-odds-container"],
+        game_state
+            .spawn_odds()
+            .into_iter()
+            .map(|(index, chance, expected)| {
+                // Placed like a tile, but drawn as a label only.
+                div![
+                    C![
+                        prefixed(prefix, &format!("tile{}", tile_position(board, index))),
+                        "spawn-odds"
+                    ],
+                    position_style(board, index),
+                    div![
+                        C![prefixed(prefix, "tile-inner")],
+                        div![format!("{:.0}%", chance * 100.0)],
+                        div![C!["spawn-odds-value"], format!("≈{:.1}", expected)]
+                    ]
+                ]
+            })
+    ]
+}
+
+// Translucent tiles where the ghosted move would leave them, without the
+// tile it spawns, which can't be known yet.
+fn view_ghost(model: &Model) -> Node<Msg> {
+    let after = match model.ghost {
+        Some(direction) if model.preview().is_none() => model.game_state.preview_move(direction),
+        _ => None,
+    };
+    let after = match after {
+        Some(after) => after,
+        None => return empty![],
+    };
+
+    div![
+        C![
+            prefixed(&model.config.options.class_prefix, "tile-container"),
+            "ghost-container"
+        ],
+        attrs! {At::AriaHidden => "true"},
+        after
+            .get_tiles()
+            .filter(|render| render.kind != RenderKind::Under)
+            .map(|render| {
+                // Drawn in place, the tiles would slide in from their old cells.
+                let render = RenderTile {
+                    kind: RenderKind::Moved,
+                    prev: None,
+                    ..render
+                };
+                view_tile(
+                    after.board(),
+                    render,
+                    false,
+                    &model.settings,
+                    &model.config.options.class_prefix,
+                )
+            })
+    ]
+}
+
+// The directions scanning steps through, with the highlighted one also
+// announced. The buttons work as the switches for pointer-based devices.
+fn view_scan(model: &Model) -> Node<Msg> {
+    let highlighted = DIRECTIONS
+        .iter()
+        .find(|&&(direction, _, _)| Some(direction) == model.scan)
+        .map_or("", |&(_, _, name)| name);
+
+    div![
+        C!["scan"],
+        attrs! {At::Dir => "ltr"},
+        DIRECTIONS
+            .iter()
+            .filter(|&&(direction, _, _)| model.allows(direction))
+            .map(|&(direction, arrow, name)| {
+                span![
+                    C![
+                        "scan-direction",
+                        IF!(Some(direction) == model.scan => "scan-highlighted")
+                    ],
+                    attrs! {At::AriaLabel => name},
+                    arrow
+                ]
+            }),
+        a![
+            C!["scan-button"],
+            "Next",
+            ev(Ev::Click, |_| Msg::ScanStepped)
+        ],
+        a![
+            C!["scan-button"],
+            "Move",
+            ev(Ev::Click, |_| Msg::ScanConfirmed)
+        ],
+        div![
+            C!["visually-hidden"],
+            attrs! {At::from("aria-live") => "polite"},
+            highlighted
+        ]
+    ]
+}
+
+// Closes on any click, there is nothing to do in it but scan.
+pub fn view_qr_code(svg: &str) -> Node<Msg> {
+    div![
+        C!["qr-modal"],
+        attrs! {At::from("role") => "dialog"},
+        ev(Ev::Click, |_| Msg::QrCodeClosed),
+        div![
+            C!["qr-dialog"],
+            img![attrs! {
+                At::Src => format!(
+                    "data:image/svg+xml;charset=utf-8,{}",
+                    String::from(js_sys::encode_uri_component(svg))
+                ),
+                At::Alt => "QR code of the link to this page",
+            }],
+            p!["Scan to open the game on another device."],
+            a![C!["qr-close"], "Close"]
+        ]
+    ]
+}
+
+fn view_board_actions() -> Node<Msg> {
+    div![
+        C!["board-actions"],
+        a![
+            C!["board-action"],
+            "Save Image",
+            ev(Ev::Click, |_| Msg::SaveImage)
+        ],
+        a![
+            C!["board-action"],
+            "Save SVG",
+            ev(Ev::Click, |_| Msg::SaveSvg)
+        ],
+        a![
+            C!["board-action"],
+            "Save Replay",
+            ev(Ev::Click, |_| Msg::SaveReplay)
+        ],
+        a![
+            C!["board-action"],
+            "Copy SVG",
+            ev(Ev::Click, |_| Msg::CopySvg)
+        ],
+        a![
+            C!["board-action"],
+            "Copy as Text",
+            ev(Ev::Click, |_| Msg::CopyText)
+        ],
+        a![
+            C!["board-action"],
+            "QR Code",
+            ev(Ev::Click, |_| Msg::ShowQrCode)
+        ]
+    ]
+}
+
+fn view_controls(board: Board) -> Node<Msg> {
+    let controls = match board {
+        Board::Square => return empty![],
+        Board::Hex => "Use A/D, Q/E and Z/C to slide along the six sides of the hexagon.",
+        Board::Cube => "Use R/F or Page Up/Down to slide between the layers.",
+    };
+
+    p![C!["game-explanation"], controls]
+}
+
+// Cells can be clicked in the editor and while picking power-up targets,
+// tiles let those clicks through.
+// Cells of square boards are labelled with their tile for screen readers.
+fn view_grid(game_state: &GameState, editable: bool, prefix: &str) -> Node<Msg> {
+    let board = game_state.board();
+    let cell = |i: usize, label: Option<String>| {
+        div![
+            C![prefixed(prefix, "grid-cell")],
+            position_style(board, i),
+            label.map(|label| attrs! {At::AriaLabel => label}),
+            IF!(editable => ev(Ev::Click, move |_| Msg::CellClicked(i)))
+        ]
+    };
+
+    if board != Board::Square {
+        let cells = (0..board.topology().cell_count()).map(|i| cell(i, None));
+
+        return div![C![prefixed(prefix, "grid-container")], cells];
+    }
+
+    let mut rows = Vec::new();
+    for row in 0..4 {
+        rows.push(div![
+            C![prefixed(prefix, "grid-row")],
+            (0..4).map(|col| {
+                let label = skin::cell_label(game_state.tile_at(row, col));
+                cell(row * 4 + col, Some(label))
+            })
+        ]);
+    }
+
+    div![C![prefixed(prefix, "grid-container")], &rows]
+}
+
+fn tile_position(board: Board, index: usize) -> String {
+    match board {
+        Board::Square => format!(" tile-position-{}-{}", index % 4 + 1, index / 4 + 1),
+        _ => String::new(),
+    }
+}
+
+fn tile_name(board: Board, render: RenderTile, prefix: &str) -> String {
+    let tile = render.tile;
+    let value = tile.get_value();
+    let position = tile_position(board, render.pos);
+    let state = match render.kind {
+        RenderKind::New => " tile-new",
+        RenderKind::Merged => " tile-merged",
+        RenderKind::Moved | RenderKind::Under => "",
+    };
+
+    let classes = format!(
+        "tile tile-{}{}{}",
+        if tile.is_obstacle() {
+            "obstacle".to_string()
+        } else if tile.is_bomb() {
+            "bomb".to_string()
+        } else if tile.is_multiplier() {
+            "multiplier".to_string()
+        } else if value <= 2048 {
+            value.to_string()
+        } else {
+            "super".to_string()
+        },
+        position,
+        state
+    );
+    prefixed(prefix, &classes)
+}
+
+fn view_tile(
+    board: Board,
+    render: RenderTile,
+    targeted: bool,
+    settings: &Settings,
+    prefix: &str,
+) -> Node<Msg> {
+    let tile = render.tile;
+    let value = skin::tile_label(tile, settings.exponents);
+    let frozen = tile.frozen_moves();
+    // The same element follows a tile from move to move, so it slides over
+    // instead of popping up at its new cell. A merged tile is drawn on top of
+    // the tiles it was made of.
+    let key = match render.kind {
+        RenderKind::Merged => format!("{}-merged", tile.id()),
+        _ => tile.id().to_string(),
+    };
+    // Tiles sliding in stay above those that didn't move.
+    let moving = render.prev.is_some_and(|prev| prev != render.pos);
+
+    div![
+        el_key(&key),
+        C![
+            tile_name(board, render, prefix),
+            IF!(moving => prefixed(prefix, "tile-moving")),
+            IF!(targeted => prefixed(prefix, "tile-target")),
+            IF!(frozen > 0 => prefixed(prefix, "tile-frozen"))
+        ],
+        position_style(board, render.pos),
+        div![
+            C![prefixed(prefix, "tile-inner")],
+            settings
+                .theme
+                .tile_colors(tile)
+                .map(|(background, text)| style! {
+                    St::Background => background,
+                    St::Color => text,
+                }),
+            settings
+                .glyphs
+                .then(|| skin::tile_glyph(tile))
+                .flatten()
+                .map(|glyph| span![
+                    C![prefixed(prefix, "tile-glyph")],
+                    attrs! {At::AriaHidden => "true"},
+                    glyph
+                ]),
+            value,
+            IF!(frozen > 0 => span![C![prefixed(prefix, "tile-frozen-count")], frozen])
+        ]
+    ]
+}
+
+fn view_tiles(
+    game_state: &game_state::GameState,
+    targets: &[usize],
+    settings: &Settings,
+    prefix: &str,
+) -> Node<Msg> {
+    let mut tiles: Vec<Node<Msg>> = game_state
+        .get_tiles()
+        .map(|render| {
+            // Only the tile in the cell can be picked, not the ones merged into it.
+            let targeted = render.kind != RenderKind::Under && targets.contains(&render.pos);
+            view_tile(game_state.board(), render, targeted, settings, prefix)
+        })
+        .collect();
+    for &i in game_state.exploded() {
+        tiles.push(div![
+            el_key(&format!("explosion-{}", i)),
+            C![prefixed(
+                prefix,
+                &format!(
+                    "tile tile-explosion{}",
+                    tile_position(game_state.board(), i)
+                )
+            )],
+            position_style(game_state.board(), i),
+        ]);
+    }
+
+    div![C![prefixed(prefix, "tile-container")], tiles]
+}
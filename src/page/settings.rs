@@ -0,0 +1,1052 @@
+use seed::{prelude::*, *};
+
+use crate::backup::{self, Backup, Import};
+use crate::crypt::Vault;
+use crate::game_state::{MergeRule, SpawnPolicy};
+use crate::mode::Mode;
+use crate::page::game::{refresh_outlook, schedule_projection};
+use crate::settings::Settings;
+use crate::storage::Seal;
+use crate::sync::{self, SyncConfig, SyncStatus};
+use crate::tilt::{self, Tilt};
+use crate::toast::Severity;
+use crate::{
+    archive, leaderboard_key, load_saves, new_game, quota, rewrite_archive, screenshot, show_toast,
+    skin, storage_key, Model, MODES, VARIANTS,
+};
+
+// Threes games always use `SpawnPolicy::Threes`, so it isn't offered here.
+const SPAWN_POLICIES: [(SpawnPolicy, &str); 5] = [
+    (SpawnPolicy::Classic, "2s and 4s"),
+    (SpawnPolicy::OnlyTwos, "Only 2s"),
+    (SpawnPolicy::TwoFourEight, "2s, 4s and 8s"),
+    (SpawnPolicy::Curve, "More 4s as the score grows"),
+    (SpawnPolicy::Evil, "Evil: always the worst tile"),
+];
+const INPUT_BUFFERS: [(usize, &str); 4] =
+    [(0, "Off"), (1, "1 move"), (2, "2 moves"), (4, "4 moves")];
+const REPEAT_INTERVALS: [(u32, &str); 5] = [
+    (0, "Every repeat"),
+    (100, "Every 100 ms"),
+    (150, "Every 150 ms"),
+    (250, "Every 250 ms"),
+    (500, "Every 500 ms"),
+];
+const TILT_THRESHOLDS: [(u32, &str); 3] = [(10, "High"), (20, "Medium"), (30, "Low")];
+const TILT_COOLDOWNS: [(u32, &str); 3] = [(250, "250 ms"), (500, "500 ms"), (1000, "1 s")];
+const SCAN_DWELLS: [(u32, &str); 4] = [(1000, "1 s"), (2000, "2 s"), (3000, "3 s"), (5000, "5 s")];
+const THEMES: [(skin::Theme, &str); 4] = [
+    (skin::Theme::Classic, "Classic"),
+    (skin::Theme::HighContrast, "High contrast"),
+    (skin::Theme::Deuteranopia, "Deuteranopia-safe"),
+    (skin::Theme::Protanopia, "Protanopia-safe"),
+];
+const UI_SCALES: [(u32, &str); 7] = [
+    (75, "75%"),
+    (90, "90%"),
+    (100, "100%"),
+    (125, "125%"),
+    (150, "150%"),
+    (175, "175%"),
+    (200, "200%"),
+];
+const WIN_TARGETS: [(Option<usize>, &str); 5] = [
+    (Some(1024), "1024"),
+    (Some(2048), "2048"),
+    (Some(4096), "4096"),
+    (Some(8192), "8192"),
+    (None, "None"),
+];
+
+pub enum Msg {
+    ArchivePruned,
+    ArchiveCompressed,
+    SpawnPolicyChanged(String),
+    ComboScoringToggled,
+    ExponentsToggled,
+    GlyphsToggled,
+    AutoRestartToggled,
+    SplitsToggled,
+    OutlookToggled,
+    GradesToggled,
+    ProjectionToggled,
+    SpawnOddsToggled,
+    ThemeChanged(String),
+    UiScaleChanged(String),
+    TargetChanged(String),
+    InputBufferChanged(String),
+    RepeatIntervalChanged(String),
+    CompetitiveRepeatToggled,
+    WheelMovesToggled,
+    TiltToggled,
+    TiltPermitted(Result<(), String>),
+    TiltThresholdChanged(String),
+    TiltCooldownChanged(String),
+    ScanningToggled,
+    ScanDwellChanged(String),
+    SyncEndpointChanged(String),
+    SyncTokenChanged(String),
+    Sync,
+    Synced(fetch::Result<Box<sync::Snapshot>>),
+    ExportBackup,
+    BackupPicked(web_sys::File),
+    BackupRead(Result<String, String>),
+    BackupImported(Import),
+    BackupCancelled,
+    PassphraseChanged(String),
+    Unlocked,
+    UnlockDeclined,
+    EncryptionEnabled,
+    EncryptionDisabled,
+    IncognitoToggled,
+}
+
+// Returns false when there is no setting or save to write back.
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<crate::Msg>) -> bool {
+    match msg {
+        Msg::ArchivePruned => {
+            let confirmed = window()
+                .confirm_with_message("Delete the older half of your past games for good?")
+                .unwrap_or(false);
+            if !confirmed {
+                return false;
+            }
+            archive::prune(&mut model.archive);
+            rewrite_archive(model, orders);
+            return false;
+        }
+        Msg::ArchiveCompressed => {
+            archive::compress(&mut model.archive);
+            rewrite_archive(model, orders);
+            return false;
+        }
+        Msg::SpawnPolicyChanged(index) => {
+            if let Some((spawn, _)) = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| SPAWN_POLICIES.get(i))
+            {
+                model.settings.spawn = *spawn;
+                // The running game picks the new odds up from its next spawn.
+                if model.game_state.rules() == MergeRule::Classic {
+                    model.game_state.set_spawn(*spawn);
+                }
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::ComboScoringToggled => {
+            model.settings.combo = !model.settings.combo;
+            model.game_state.set_combo_scoring(model.settings.combo);
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::ExponentsToggled => {
+            model.settings.exponents = !model.settings.exponents;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::GlyphsToggled => {
+            model.settings.glyphs = !model.settings.glyphs;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::AutoRestartToggled => {
+            model.settings.auto_restart = !model.settings.auto_restart;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::SplitsToggled => {
+            model.settings.show_splits = !model.settings.show_splits;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::OutlookToggled => {
+            model.settings.show_outlook = !model.settings.show_outlook;
+            model.updated_at = js_sys::Date::now();
+            refresh_outlook(model);
+        }
+        Msg::GradesToggled => {
+            model.settings.show_grades = !model.settings.show_grades;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::ProjectionToggled => {
+            model.settings.show_projection = !model.settings.show_projection;
+            model.updated_at = js_sys::Date::now();
+            schedule_projection(model, orders);
+        }
+        Msg::SpawnOddsToggled => {
+            model.settings.spawn_odds = !model.settings.spawn_odds;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::ThemeChanged(index) => {
+            if let Some((theme, _)) = index.parse().ok().and_then(|i: usize| THEMES.get(i)) {
+                model.settings.theme = *theme;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::UiScaleChanged(index) => {
+            if let Some((scale, _)) = index.parse().ok().and_then(|i: usize| UI_SCALES.get(i)) {
+                model.settings.ui_scale = *scale;
+                model.updated_at = js_sys::Date::now();
+                // The fullscreen board is scaled to fit the screen as it is.
+                orders.send_msg(crate::Msg::Game(crate::page::game::Msg::ViewportChanged));
+            }
+        }
+        Msg::TargetChanged(index) => {
+            if let Some((target, _)) = index.parse().ok().and_then(|i: usize| WIN_TARGETS.get(i)) {
+                model.settings.target = *target;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::InputBufferChanged(index) => {
+            if let Some((length, _)) = index.parse().ok().and_then(|i: usize| INPUT_BUFFERS.get(i))
+            {
+                model.settings.input_buffer = *length;
+                model.queued_moves.truncate(*length);
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::RepeatIntervalChanged(index) => {
+            if let Some((interval, _)) = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| REPEAT_INTERVALS.get(i))
+            {
+                model.settings.repeat_interval = *interval;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::CompetitiveRepeatToggled => {
+            model.settings.competitive_no_repeat = !model.settings.competitive_no_repeat;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::WheelMovesToggled => {
+            model.settings.wheel_moves = !model.settings.wheel_moves;
+            model.updated_at = js_sys::Date::now();
+        }
+        // Asked from the tap on the setting, which iOS needs to show its prompt.
+        Msg::TiltToggled => {
+            model.settings.tilt = !model.settings.tilt;
+            model.tilt = None;
+            model.tilt_error = None;
+            if model.settings.tilt {
+                orders.perform_cmd(async {
+                    crate::Msg::Settings(Msg::TiltPermitted(tilt::request_permission().await))
+                });
+            }
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::TiltPermitted(Ok(())) => {
+            if !model.settings.tilt {
+                return false;
+            }
+            model.tilt_rest = None;
+            model.tilt = Some(orders.stream_with_handle(streams::window_event(
+                Ev::from("deviceorientation"),
+                |ev| {
+                    let ev: web_sys::DeviceOrientationEvent = ev.unchecked_into();
+                    Some(crate::Msg::Game(crate::page::game::Msg::Tilted(Tilt {
+                        beta: ev.beta()?,
+                        gamma: ev.gamma()?,
+                    })))
+                },
+            )));
+            return false;
+        }
+        Msg::TiltPermitted(Err(err)) => {
+            model.settings.tilt = false;
+            model.tilt_error = Some(err);
+        }
+        Msg::ScanningToggled => {
+            model.settings.scanning = !model.settings.scanning;
+            model.scan = None;
+            model.scan_generation += 1;
+            model.updated_at = js_sys::Date::now();
+        }
+        Msg::ScanDwellChanged(index) => {
+            if let Some((dwell, _)) = index.parse().ok().and_then(|i: usize| SCAN_DWELLS.get(i)) {
+                model.settings.scan_dwell = *dwell;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::TiltThresholdChanged(index) => {
+            if let Some((threshold, _)) = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| TILT_THRESHOLDS.get(i))
+            {
+                model.settings.tilt_threshold = *threshold;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::TiltCooldownChanged(index) => {
+            if let Some((cooldown, _)) = index
+                .parse()
+                .ok()
+                .and_then(|i: usize| TILT_COOLDOWNS.get(i))
+            {
+                model.settings.tilt_cooldown = *cooldown;
+                model.updated_at = js_sys::Date::now();
+            }
+        }
+        Msg::SyncEndpointChanged(endpoint) => {
+            model.sync.endpoint = endpoint;
+        }
+        Msg::SyncTokenChanged(token) => {
+            model.sync.token = token;
+        }
+        Msg::Sync => {
+            // Timed games are never synced, only the endless game of each variant.
+            if model.sync.is_configured() && model.mode == Mode::Endless && model.keeps_records() {
+                model.sync_status = SyncStatus::Syncing;
+
+                let config = model.sync.clone();
+                let local = model.snapshot();
+                orders.perform_cmd(async move {
+                    crate::Msg::Settings(Msg::Synced(sync::sync(config, local).await.map(Box::new)))
+                });
+            }
+        }
+        Msg::Synced(Ok(snapshot)) if model.mode != Mode::Endless => {
+            model.stats = model.stats.merge(&snapshot.stats);
+            model.sync_status = SyncStatus::Synced;
+        }
+        Msg::Synced(Ok(snapshot)) => {
+            // Moves made while the request was in flight must not be overwritten.
+            let resolved = sync::resolve(model.snapshot(), Some(*snapshot));
+            model.restore(resolved);
+            model.sync_status = SyncStatus::Synced;
+        }
+        Msg::Synced(Err(err)) => {
+            model.sync_status = SyncStatus::Failed(format!("{:?}", err));
+        }
+        Msg::ExportBackup => {
+            let saved = model.backup().to_json().and_then(|json| {
+                screenshot::download_bytes(json.as_bytes(), "2048-backup.json")
+                    .map_err(|err| format!("{:?}", err))
+            });
+            if let Err(err) = saved {
+                log!("Exporting the backup failed:", err);
+                show_toast(
+                    model,
+                    orders,
+                    "Couldn't export the backup.".to_string(),
+                    Severity::Error,
+                );
+            }
+            return false;
+        }
+        Msg::BackupPicked(file) => {
+            orders.perform_cmd(async {
+                crate::Msg::Settings(Msg::BackupRead(backup::read(file).await))
+            });
+            return false;
+        }
+        Msg::BackupRead(json) => {
+            match json.and_then(|json| Backup::parse(&json)) {
+                Ok(backup) => model.backup_import = Some(backup),
+                Err(err) => show_toast(model, orders, err, Severity::Error),
+            }
+            return false;
+        }
+        Msg::BackupImported(import) => {
+            let backup = match model.backup_import.take() {
+                Some(backup) => backup,
+                None => return false,
+            };
+            import_backup(model, orders, backup, import);
+            show_toast(
+                model,
+                orders,
+                "Backup imported.".to_string(),
+                Severity::Success,
+            );
+        }
+        Msg::BackupCancelled => {
+            model.backup_import = None;
+            return false;
+        }
+        Msg::PassphraseChanged(passphrase) => {
+            model.passphrase = passphrase;
+            model.passphrase_error = None;
+            return false;
+        }
+        Msg::Unlocked => {
+            let key = match model
+                .vault
+                .as_ref()
+                .and_then(|vault| vault.unlock(&model.passphrase))
+            {
+                Some(key) => key,
+                None => {
+                    model.passphrase_error = Some("That isn't the passphrase.".to_string());
+                    return false;
+                }
+            };
+            model.vault = None;
+            model.passphrase.clear();
+            model.storage.unlock(key);
+            load_saves(model, orders);
+            return false;
+        }
+        // The saves stay encrypted and untouched, the fresh game isn't saved.
+        Msg::UnlockDeclined => {
+            model.vault = None;
+            model.passphrase.clear();
+            model.passphrase_error = None;
+            return false;
+        }
+        Msg::EncryptionEnabled => {
+            if model.passphrase.is_empty() {
+                model.passphrase_error = Some("Pick a passphrase first.".to_string());
+                return false;
+            }
+            let (vault, key) = match Vault::new(&model.passphrase) {
+                Some(sealed) => sealed,
+                None => {
+                    model.passphrase_error = Some("Couldn't use that passphrase.".to_string());
+                    return false;
+                }
+            };
+            model.passphrase.clear();
+            reseal(model, orders, Seal::Sealed(key), Some(&vault));
+            show_toast(
+                model,
+                orders,
+                "Saves are encrypted from now on.".to_string(),
+                Severity::Success,
+            );
+        }
+        Msg::EncryptionDisabled => {
+            reseal(model, orders, Seal::Plain, None);
+            show_toast(
+                model,
+                orders,
+                "Saves aren't encrypted anymore.".to_string(),
+                Severity::Success,
+            );
+        }
+        // The game carries over into incognito, and is dropped for the saved
+        // one when leaving it.
+        Msg::IncognitoToggled => {
+            let incognito = !model.storage.is_incognito();
+            model.storage.set_incognito(incognito);
+            if !incognito {
+                load_saves(model, orders);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+pub fn view(model: &Model) -> Vec<Node<Msg>> {
+    nodes![
+        hr!(),
+        view_settings(&model.settings, model.tilt_error.as_deref()),
+        view_sync(&model.sync, &model.sync_status),
+        view_backup(model.backup_import.as_ref()),
+        view_encryption(model),
+        view_incognito(model.storage.is_incognito()),
+        view_usage(model.usage, model.archive.len()),
+    ]
+}
+
+// Writes what the backup has over what is kept here, or combined with it.
+// Saved games and leaderboards go straight to storage, as only those of the
+// current variant and mode are held in the model.
+fn import_backup(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    backup: Backup,
+    import: Import,
+) {
+    let backup = match import {
+        Import::Merge => model.backup().merge(backup),
+        Import::Replace => backup,
+    };
+
+    let mut errors = Vec::new();
+    for &(mode, _) in &MODES {
+        if let Some(key) = leaderboard_key(mode) {
+            match backup.leaderboards.iter().find(|(m, _)| *m == mode) {
+                Some((_, leaderboard)) => errors.push(model.storage.insert(key, leaderboard)),
+                None => model.storage.remove(key),
+            }
+        }
+    }
+    for &(variant, _) in &VARIANTS {
+        match backup.saves.iter().find(|(v, _)| *v == variant) {
+            Some((_, game_state)) => {
+                errors.push(model.storage.insert(&storage_key(variant), game_state))
+            }
+            None => model.storage.remove(&storage_key(variant)),
+        }
+    }
+    if let Some(Err(err)) = errors.into_iter().find(Result::is_err) {
+        model.storage_error = Some(err);
+    }
+
+    model.leaderboard = backup
+        .leaderboards
+        .iter()
+        .find(|(mode, _)| *mode == model.mode)
+        .map(|(_, leaderboard)| leaderboard.clone())
+        .unwrap_or_default();
+    if model.mode == Mode::Endless {
+        model.game_state = backup
+            .saves
+            .iter()
+            .find(|(variant, _)| *variant == model.variant)
+            .map_or_else(
+                || new_game(model.variant, backup.settings),
+                |(_, game_state)| game_state.clone(),
+            );
+    }
+    model.settings = backup.settings;
+    model.stats.adopt(backup.stats);
+    model.games = backup.games;
+    model.solved_puzzles = backup.solved_puzzles;
+    model.achievements = backup.achievements;
+    model.updated_at = js_sys::Date::now();
+
+    let archive = backup.archive;
+    model.archive = archive.clone();
+    let clear = import == Import::Replace;
+    let storage = model.storage;
+    orders.perform_cmd(async move {
+        crate::Msg::Archive(crate::page::archive::Msg::Archived(
+            archive::save_all(archive, clear, storage).await,
+        ))
+    });
+}
+
+// Rewrites all saves, archived games included, sealed the new way.
+fn reseal(
+    model: &mut Model,
+    orders: &mut impl Orders<crate::Msg>,
+    seal: Seal,
+    vault: Option<&Vault>,
+) {
+    if let Err(err) = model.storage.reseal(seal, vault) {
+        model.storage_error = Some(err);
+    }
+    rewrite_archive(model, orders);
+}
+
+fn view_settings(settings: &Settings, tilt_error: Option<&str>) -> Node<Msg> {
+    div![
+        C!["settings-container"],
+        label![
+            C!["settings-label"],
+            "Theme",
+            select![
+                C!["settings-select"],
+                THEMES.iter().enumerate().map(|(i, (theme, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*theme == settings.theme).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::ThemeChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            "Size",
+            select![
+                C!["settings-select"],
+                UI_SCALES.iter().enumerate().map(|(i, (scale, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*scale == settings.ui_scale).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::UiScaleChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            "New tiles",
+            select![
+                C!["settings-select"],
+                SPAWN_POLICIES.iter().enumerate().map(|(i, (spawn, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*spawn == settings.spawn).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::SpawnPolicyChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            "Winning tile",
+            select![
+                C!["settings-select"],
+                WIN_TARGETS.iter().enumerate().map(|(i, (target, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*target == settings.target).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::TargetChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            "Moves queued mid-slide",
+            select![
+                C!["settings-select"],
+                INPUT_BUFFERS.iter().enumerate().map(|(i, (length, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*length == settings.input_buffer).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::InputBufferChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            "Held keys move",
+            select![
+                C!["settings-select"],
+                REPEAT_INTERVALS.iter().enumerate().map(|(i, (interval, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*interval == settings.repeat_interval).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::RepeatIntervalChanged)
+            ]
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.competitive_no_repeat.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::CompetitiveRepeatToggled)
+            ],
+            "No held keys in timed and ranked modes"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.wheel_moves.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::WheelMovesToggled)
+            ],
+            "Scroll over the board to move"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.tilt.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::TiltToggled)
+            ],
+            "Tilt to move (experimental)"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.scanning.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::ScanningToggled)
+            ],
+            "One-switch play: Space picks a direction, Enter or waiting plays it"
+        ],
+        IF!(settings.scanning => label![
+            C!["settings-label"],
+            "Play the picked direction after",
+            select![
+                C!["settings-select"],
+                SCAN_DWELLS.iter().enumerate().map(|(i, (dwell, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*dwell == settings.scan_dwell).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::ScanDwellChanged)
+            ]
+        ]),
+        tilt_error.map(|err| p![C!["settings-error"], err]),
+        IF!(settings.tilt => label![
+            C!["settings-label"],
+            "Tilt sensitivity",
+            select![
+                C!["settings-select"],
+                TILT_THRESHOLDS.iter().enumerate().map(|(i, (threshold, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*threshold == settings.tilt_threshold).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::TiltThresholdChanged)
+            ]
+        ]),
+        IF!(settings.tilt => label![
+            C!["settings-label"],
+            "Between tilt moves",
+            select![
+                C!["settings-select"],
+                TILT_COOLDOWNS.iter().enumerate().map(|(i, (cooldown, name))| {
+                    option![
+                        attrs! {
+                            At::Value => i,
+                            At::Selected => (*cooldown == settings.tilt_cooldown).as_at_value()
+                        },
+                        name
+                    ]
+                }),
+                input_ev(Ev::Change, Msg::TiltCooldownChanged)
+            ]
+        ]),
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.combo.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::ComboScoringToggled)
+            ],
+            "Combo bonus"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.exponents.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::ExponentsToggled)
+            ],
+            "Show exponents"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.glyphs.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::GlyphsToggled)
+            ],
+            "Mark tiles with shapes"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.spawn_odds.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::SpawnOddsToggled)
+            ],
+            "Show where the next tile may spawn"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.auto_restart.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::AutoRestartToggled)
+            ],
+            "Start a new game after game over"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.show_splits.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::SplitsToggled)
+            ],
+            "Show speedrun splits"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.show_outlook.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::OutlookToggled)
+            ],
+            "Show the chances of the board"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.show_grades.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::GradesToggled)
+            ],
+            "Grade my moves"
+        ],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => settings.show_projection.as_at_value()
+                },
+                ev(Ev::Change, |_| Msg::ProjectionToggled)
+            ],
+            "Project the final score"
+        ]
+    ]
+}
+
+fn view_sync(sync: &SyncConfig, status: &SyncStatus) -> Node<Msg> {
+    let status = match status {
+        SyncStatus::Idle => String::new(),
+        SyncStatus::Syncing => "Syncing...".to_string(),
+        SyncStatus::Synced => "Synced.".to_string(),
+        SyncStatus::Failed(err) => format!("Sync failed: {}", err),
+    };
+
+    div![
+        C!["sync-container"],
+        input![
+            C!["sync-input"],
+            attrs! {At::Placeholder => "Sync endpoint", At::Value => sync.endpoint},
+            input_ev(Ev::Input, Msg::SyncEndpointChanged)
+        ],
+        input![
+            C!["sync-input"],
+            attrs! {At::Type => "password", At::Placeholder => "Token", At::Value => sync.token},
+            input_ev(Ev::Input, Msg::SyncTokenChanged)
+        ],
+        a![C!["sync-button"], "Sync", ev(Ev::Click, |_| Msg::Sync)],
+        p![C!["sync-status"], status]
+    ]
+}
+
+fn view_backup(pending: Option<&Backup>) -> Node<Msg> {
+    div![
+        C!["backup-container"],
+        a![
+            C!["backup-button"],
+            "Export Backup",
+            ev(Ev::Click, |_| Msg::ExportBackup)
+        ],
+        label![
+            C!["backup-button"],
+            "Import Backup",
+            input![
+                attrs! {At::Type => "file", At::Accept => ".json,application/json"},
+                ev(Ev::Change, |ev| {
+                    let input: web_sys::HtmlInputElement = ev.target()?.dyn_into().ok()?;
+                    let file = input.files()?.get(0);
+                    // Picking the same file again still counts as a change.
+                    input.set_value("");
+                    file.map(Msg::BackupPicked)
+                })
+            ]
+        ],
+        pending.map(|backup| {
+            let created_at = js_sys::Date::new(&backup.created_at.into())
+                .to_locale_string("default", &JsValue::UNDEFINED);
+            div![
+                C!["backup-prompt"],
+                p![format!(
+                    "This backup from {} has {} games. Merge it with what is kept here, or replace everything with it?",
+                    String::from(created_at),
+                    backup.games.len()
+                )],
+                a![
+                    C!["backup-button"],
+                    "Merge",
+                    ev(Ev::Click, |_| Msg::BackupImported(Import::Merge))
+                ],
+                a![
+                    C!["backup-button"],
+                    "Replace",
+                    ev(Ev::Click, |_| Msg::BackupImported(Import::Replace))
+                ],
+                a![
+                    C!["backup-button"],
+                    "Cancel",
+                    ev(Ev::Click, |_| Msg::BackupCancelled)
+                ]
+            ]
+        })
+    ]
+}
+
+// Asked for over the page at startup while saves are encrypted.
+pub fn view_unlock(model: &Model) -> Node<Msg> {
+    div![
+        C!["qr-modal"],
+        attrs! {At::from("role") => "dialog"},
+        form![
+            C!["qr-dialog", "unlock-dialog"],
+            ev(Ev::Submit, |ev| {
+                ev.prevent_default();
+                Msg::Unlocked
+            }),
+            p!["Your saves are encrypted. Enter the passphrase to load them."],
+            input![
+                C!["passphrase-input"],
+                attrs! {
+                    At::Type => "password",
+                    At::Placeholder => "Passphrase",
+                    At::Value => model.passphrase,
+                    At::AutoFocus => AtValue::None,
+                },
+                input_ev(Ev::Input, Msg::PassphraseChanged)
+            ],
+            model
+                .passphrase_error
+                .as_ref()
+                .map(|err| p![C!["unlock-error"], err]),
+            button![C!["qr-close"], attrs! {At::Type => "submit"}, "Unlock"],
+            a![
+                C!["qr-close"],
+                "Play Without Them",
+                ev(Ev::Click, |_| Msg::UnlockDeclined)
+            ]
+        ]
+    ]
+}
+
+fn view_encryption(model: &Model) -> Node<Msg> {
+    // Incognito games aren't worth encrypting, they're gone with the tab.
+    if model.storage.is_incognito() {
+        return empty![];
+    }
+    let content = match model.storage.seal() {
+        // Nothing can be changed about saves that weren't unlocked.
+        Seal::Locked => return empty![],
+        Seal::Sealed(_) => vec![
+            span!["Saves are encrypted with your passphrase. "],
+            a![
+                C!["backup-button"],
+                "Stop Encrypting",
+                ev(Ev::Click, |_| Msg::EncryptionDisabled)
+            ],
+        ],
+        Seal::Plain => vec![
+            input![
+                C!["passphrase-input"],
+                attrs! {
+                    At::Type => "password",
+                    At::Placeholder => "Passphrase",
+                    At::Value => model.passphrase,
+                },
+                input_ev(Ev::Input, Msg::PassphraseChanged)
+            ],
+            a![
+                C!["backup-button"],
+                "Encrypt Saves",
+                ev(Ev::Click, |_| Msg::EncryptionEnabled)
+            ],
+            p![
+                C!["backup-prompt"],
+                "For shared computers: the passphrase is asked for every time the game opens, and can't be recovered."
+            ],
+        ],
+    };
+
+    div![
+        C!["backup-container"],
+        content,
+        IF!(model.vault.is_none() => model
+            .passphrase_error
+            .as_ref()
+            .map(|err| p![C!["unlock-error"], err]))
+    ]
+}
+
+fn view_incognito(incognito: bool) -> Node<Msg> {
+    div![
+        C!["backup-container"],
+        label![
+            C!["settings-label"],
+            input![
+                attrs! {At::Type => "checkbox", At::Checked => incognito.as_at_value()},
+                ev(Ev::Change, |_| Msg::IncognitoToggled)
+            ],
+            "Incognito"
+        ],
+        p![
+            C!["backup-prompt"],
+            if incognito {
+                "Games in this tab are forgotten once it is closed, and never count towards your statistics."
+            } else {
+                "Play in this tab without touching your saves or statistics."
+            }
+        ]
+    ]
+}
+
+fn view_usage(usage: quota::Usage, archived: usize) -> Node<Msg> {
+    div![
+        C!["backup-container"],
+        h2!["Storage"],
+        p![
+            C!["backup-prompt"],
+            format!(
+                "Saves take {} of about {}.",
+                quota::format_bytes(usage.local),
+                quota::format_bytes(quota::LOCAL_QUOTA)
+            ),
+            usage.site.map(|(used, quota)| {
+                format!(
+                    " With {} past games, the game takes {} of the {} the browser allows.",
+                    archived,
+                    quota::format_bytes(used),
+                    quota::format_bytes(quota)
+                )
+            })
+        ],
+        IF!(archived > 0 => vec![
+            a![
+                C!["backup-button"],
+                "Compress Past Games",
+                attrs! {At::Title => "Only the latest past games keep their moves"},
+                ev(Ev::Click, |_| Msg::ArchiveCompressed)
+            ],
+            a![
+                C!["backup-button"],
+                "Delete Oldest Half",
+                ev(Ev::Click, |_| Msg::ArchivePruned)
+            ],
+        ])
+    ]
+}
@@ -0,0 +1,75 @@
+use seed::{prelude::*, *};
+
+use crate::stats::{self, Stats};
+use crate::toast::Severity;
+use crate::{records, screenshot, show_toast, Model};
+
+pub enum Msg {
+    ExportCsv,
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<crate::Msg>) {
+    match msg {
+        Msg::ExportCsv => {
+            let url = format!(
+                "data:text/csv;charset=utf-8,{}",
+                String::from(js_sys::encode_uri_component(&records::to_csv(&model.games)))
+            );
+            if let Err(err) = screenshot::download(&url, "2048-games.csv") {
+                log!("Exporting the games failed:", err);
+                show_toast(
+                    model,
+                    orders,
+                    "Couldn't export the games.".to_string(),
+                    Severity::Error,
+                );
+            }
+        }
+    }
+}
+
+// The counters kept over all games, with where the high tiles ended up.
+pub fn view(stats: &Stats, games: usize) -> Node<Msg> {
+    let most = stats.heatmap().iter().copied().max().unwrap_or(0);
+
+    div![
+        C!["stats"],
+        h2![C!["stats-title"], "Statistics"],
+        p![
+            C!["stats-counters"],
+            format!(
+                "{} games played, {} won. Best tile: {}.",
+                stats.games_played(),
+                stats.games_won(),
+                stats.best_tile()
+            )
+        ],
+        p![
+            C!["stats-hint"],
+            format!(
+                "Where tiles of {} and up were when games ended:",
+                stats::HIGH_TILE
+            )
+        ],
+        div![
+            C!["heatmap"],
+            stats.heatmap().iter().map(|&count| {
+                let heat = if most == 0 {
+                    0.0
+                } else {
+                    count as f64 / most as f64
+                };
+                div![
+                    C!["heatmap-cell"],
+                    style! {St::BackgroundColor => format!("rgba(237, 194, 46, {:.2})", heat)},
+                    count
+                ]
+            })
+        ],
+        IF!(games > 0 => a![
+            C!["stats-export"],
+            format!("Export {} Games as CSV", games),
+            ev(Ev::Click, |_| Msg::ExportCsv)
+        ])
+    ]
+}
@@ -0,0 +1,110 @@
+use crate::game_state::{Direction, GameState};
+use crate::goal::Goal;
+
+// A fixed starting position to solve within a number of moves. No tiles
+// spawn in puzzles, so every position plays out the same way.
+#[derive(Debug)]
+pub struct Puzzle {
+    pub name: &'static str,
+    pub grid: [usize; 16],
+    pub allowed: &'static [Direction],
+    pub moves: u32,
+    pub goal: Goal,
+}
+
+impl Puzzle {
+    pub fn game_state(&self) -> GameState {
+        GameState::from_values(&self.grid)
+    }
+
+    pub fn is_allowed(&self, direction: Direction) -> bool {
+        self.allowed.contains(&direction)
+    }
+
+    pub fn is_solved(&self, game_state: &GameState) -> bool {
+        self.goal.is_reached(game_state)
+    }
+}
+
+const ALL_MOVES: &[Direction] = &[
+    Direction::Left,
+    Direction::Right,
+    Direction::Up,
+    Direction::Down,
+];
+
+pub const PUZZLES: [Puzzle; 5] = [
+    Puzzle {
+        name: "First Merge",
+        grid: [0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0],
+        allowed: ALL_MOVES,
+        moves: 2,
+        goal: Goal::Tile(4),
+    },
+    Puzzle {
+        name: "Corner Pocket",
+        grid: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 2, 2, 0],
+        allowed: &[Direction::Left, Direction::Down],
+        moves: 2,
+        goal: Goal::Tile(8),
+    },
+    Puzzle {
+        name: "Cascade",
+        grid: [0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0, 8, 0],
+        allowed: &[Direction::Up, Direction::Down],
+        moves: 3,
+        goal: Goal::Tile(16),
+    },
+    Puzzle {
+        name: "Two by Two",
+        grid: [2, 2, 2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        allowed: ALL_MOVES,
+        moves: 3,
+        goal: Goal::Score(48),
+    },
+    Puzzle {
+        name: "The Snake",
+        grid: [128, 64, 32, 16, 8, 4, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0],
+        allowed: ALL_MOVES,
+        moves: 7,
+        goal: Goal::Tile(256),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::puzzle::{Puzzle, PUZZLES};
+
+    // Tries every sequence of allowed moves up to the puzzle's limit.
+    fn solve(puzzle: &Puzzle, game_state: &GameState, moves: u32) -> bool {
+        if puzzle.is_solved(game_state) {
+            return true;
+        }
+
+        moves > 0
+            && puzzle.allowed.iter().any(|&direction| {
+                let mut next = game_state.clone();
+                next.move_tiles(direction).moved && solve(puzzle, &next, moves - 1)
+            })
+    }
+
+    #[test]
+    fn test_puzzles() {
+        for puzzle in &PUZZLES {
+            let game_state = puzzle.game_state();
+
+            assert!(!puzzle.is_solved(&game_state), "{}: Unsolved", puzzle.name);
+            assert!(
+                solve(puzzle, &game_state, puzzle.moves),
+                "{}: Solvable",
+                puzzle.name
+            );
+            assert!(
+                !solve(puzzle, &game_state, puzzle.moves - 1),
+                "{}: Needs Every Move",
+                puzzle.name
+            );
+        }
+    }
+}
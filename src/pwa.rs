@@ -0,0 +1,31 @@
+use seed::{prelude::*, *};
+
+// Part of the service worker URL, so every build installs a worker, and a
+// cache, of its own. Set by `build.rs`, see `sw.js`.
+const BUILD: &str = env!("BUILD_ID");
+
+// Links the web app manifest, which makes the game installable, and registers
+// the service worker that keeps it playable offline. Browsers without service
+// workers (or pages not served over HTTPS) just play online.
+pub fn register() {
+    let head = document().query_selector("head").ok().flatten();
+    if let (Some(head), Ok(link)) = (head, document().create_element("link")) {
+        let _ = link.set_attribute("rel", "manifest");
+        let _ = link.set_attribute("href", "/manifest.webmanifest");
+        let _ = head.append_child(&link);
+    }
+
+    let navigator = window().navigator();
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        return;
+    }
+
+    let registration = navigator
+        .service_worker()
+        .register(&format!("/sw.js?build={}", BUILD));
+    spawn_local(async move {
+        if let Err(err) = JsFuture::from(registration).await {
+            log!("Service worker registration failed:", err);
+        }
+    });
+}
@@ -0,0 +1,472 @@
+// QR codes in byte mode at error correction level M, enough for links to the
+// game. Follows the layout of ISO/IEC 18004; only versions 1 to 10 are
+// supported, which hold up to 213 bytes.
+
+// Per version: error correction codewords per block and the blocks as
+// (count, data codewords per block).
+const VERSIONS: [(usize, &[(usize, usize)]); 10] = [
+    (10, &[(1, 16)]),
+    (16, &[(1, 28)]),
+    (26, &[(1, 44)]),
+    (18, &[(2, 32)]),
+    (24, &[(2, 43)]),
+    (16, &[(4, 27)]),
+    (18, &[(4, 31)]),
+    (22, &[(2, 38), (2, 39)]),
+    (22, &[(3, 36), (2, 37)]),
+    (26, &[(4, 43), (1, 44)]),
+];
+
+const ALIGNMENTS: [&[usize]; 10] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+// Light modules around the code that scanners need to find it.
+const QUIET_ZONE: usize = 4;
+
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    // Finder, timing and alignment patterns and format and version bits,
+    // which data and masks leave alone.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    // The smallest code holding `text`, none if it is too long.
+    pub fn encode(text: &str) -> Option<QrCode> {
+        let bytes = text.as_bytes();
+        let version = (1..=VERSIONS.len()).find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            4 + count_bits + bytes.len() * 8 <= data_codewords(version) * 8
+        })?;
+
+        let mut qr = QrCode {
+            size: version * 4 + 17,
+            modules: Vec::new(),
+            function: Vec::new(),
+        };
+        qr.modules = vec![false; qr.size * qr.size];
+        qr.function = vec![false; qr.size * qr.size];
+        qr.draw_patterns(version);
+        qr.draw_codewords(&interleave(version, &data(version, bytes)));
+
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format(mask);
+                let penalty = qr.penalty();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format(mask);
+
+        Some(qr)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    // An SVG image with one unit per module, quiet zone included.
+    pub fn to_svg(&self) -> String {
+        let width = self.size + QUIET_ZONE * 2;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.is_dark(x, y)) {
+                path += &format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE);
+            }
+        }
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {w}\" shape-rendering=\"crispEdges\">\
+             <rect width=\"{w}\" height=\"{w}\" fill=\"#fff\"/><path d=\"{}\" fill=\"#000\"/></svg>",
+            path,
+            w = width
+        )
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for &(x, y) in &[(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4..=4_isize {
+                for dx in -4..=4_isize {
+                    let (xx, yy) = (x as isize + dx, y as isize + dy);
+                    if (0..size as isize).contains(&xx) && (0..size as isize).contains(&yy) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let alignments = ALIGNMENTS[version - 1];
+        let last = alignments.len().saturating_sub(1);
+        for (i, &x) in alignments.iter().enumerate() {
+            for (j, &y) in alignments.iter().enumerate() {
+                // Those corners are taken by finder patterns.
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2..=2_isize {
+                    for dx in -2..=2_isize {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function(
+                            (x as isize + dx) as usize,
+                            (y as isize + dy) as usize,
+                            dark,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Reserves the format bits, drawn for real once the mask is picked.
+        self.draw_format(0);
+
+        if version >= 7 {
+            let bits = (version << 12) | bch(version, 0x1f25, 12);
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format(&mut self, mask: usize) {
+        // Level M is 00, so only the mask is left in the data bits.
+        let bits = ((mask << 10) | bch(mask, 0x537, 10)) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    // Fills the free modules in two-module wide columns, zigzagging up and
+    // down from the bottom right.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let y = if upward {
+                    size - 1 - vertical
+                } else {
+                    vertical
+                };
+                for x in [right as usize, right as usize - 1] {
+                    if self.function[y * size + x] || i >= codewords.len() * 8 {
+                        continue;
+                    }
+                    self.modules[y * size + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 == 1;
+                    i += 1;
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    // Applying a mask twice takes it off again.
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let i = y * self.size + x;
+                if flip && !self.function[i] {
+                    self.modules[i] = !self.modules[i];
+                }
+            }
+        }
+    }
+
+    // How hard the code is to scan: long runs, blocks, patterns that look
+    // like finders and an uneven share of dark modules all count against it.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+
+        let rows = (0..size).map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>());
+        let columns = (0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect::<Vec<_>>());
+        let finder_like = [
+            [
+                true, false, true, true, true, false, true, false, false, false, false,
+            ],
+            [
+                false, false, false, false, true, false, true, true, true, false, true,
+            ],
+        ];
+        for line in rows.chain(columns) {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                run = 1;
+            }
+
+            penalty += line
+                .windows(11)
+                .filter(|window| finder_like.iter().any(|pattern| pattern == window))
+                .count()
+                * 40;
+        }
+
+        for y in 1..size {
+            for x in 1..size {
+                let dark = self.is_dark(x, y);
+                if self.is_dark(x - 1, y) == dark
+                    && self.is_dark(x, y - 1) == dark
+                    && self.is_dark(x - 1, y - 1) == dark
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let deviation = (dark * 20).max(total * 10) - (dark * 20).min(total * 10);
+        penalty + deviation.div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+fn data_codewords(version: usize) -> usize {
+    VERSIONS[version - 1]
+        .1
+        .iter()
+        .map(|&(count, codewords)| count * codewords)
+        .sum()
+}
+
+// The BCH code of `value` for the format and version bits.
+fn bch(value: usize, generator: usize, bits: usize) -> usize {
+    let mut remainder = value;
+    for _ in 0..bits {
+        remainder = (remainder << 1) ^ ((remainder >> (bits - 1)) * generator);
+    }
+    remainder & ((1 << bits) - 1)
+}
+
+// The text in byte mode, padded to the data capacity of the version.
+fn data(version: usize, bytes: &[u8]) -> Vec<u8> {
+    let mut bits: Vec<bool> = Vec::new();
+    let mut push = |value: usize, count: usize| {
+        bits.extend((0..count).rev().map(|i| (value >> i) & 1 == 1));
+    };
+    push(0b0100, 4);
+    push(bytes.len(), if version < 10 { 8 } else { 16 });
+    for &byte in bytes {
+        push(byte as usize, 8);
+    }
+
+    let capacity = data_codewords(version) * 8;
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    while !bits.len().is_multiple_of(8) {
+        bits.push(false);
+    }
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, &bit| (acc << 1) | bit as u8))
+        .collect();
+    for pad in [0xec, 0x11].iter().cycle() {
+        if codewords.len() == capacity / 8 {
+            break;
+        }
+        codewords.push(*pad);
+    }
+    codewords
+}
+
+// Splits the data into blocks, adds their error correction and interleaves
+// them codeword by codeword.
+fn interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let (ec_len, groups) = VERSIONS[version - 1];
+    let mut blocks = Vec::new();
+    let mut rest = data;
+    for &(count, len) in groups {
+        for _ in 0..count {
+            let (block, after) = rest.split_at(len);
+            blocks.push((block, reed_solomon(block, ec_len)));
+            rest = after;
+        }
+    }
+
+    let longest = blocks
+        .iter()
+        .map(|(block, _)| block.len())
+        .max()
+        .unwrap_or(0);
+    let mut codewords = Vec::new();
+    for i in 0..longest {
+        codewords.extend(blocks.iter().filter_map(|(block, _)| block.get(i)));
+    }
+    for i in 0..ec_len {
+        codewords.extend(blocks.iter().map(|(_, ec)| ec[i]));
+    }
+    codewords
+}
+
+// Multiplication in GF(256) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(a: u8, b: u8) -> u8 {
+    let mut product: u16 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11d);
+        product ^= ((b >> i) & 1) as u16 * a as u16;
+    }
+    product as u8
+}
+
+fn reed_solomon(data: &[u8], len: usize) -> Vec<u8> {
+    // The generator polynomial (x - 2^0)(x - 2^1)..., highest term left out.
+    let mut generator = vec![0; len];
+    generator[len - 1] = 1;
+    let mut root = 1;
+    for _ in 0..len {
+        for j in 0..len {
+            generator[j] = gf_multiply(generator[j], root);
+            if j + 1 < len {
+                generator[j] ^= generator[j + 1];
+            }
+        }
+        root = gf_multiply(root, 2);
+    }
+
+    let mut remainder = vec![0; len];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (r, &g) in remainder.iter_mut().zip(&generator) {
+            *r ^= gf_multiply(g, factor);
+        }
+    }
+    remainder
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::qr::{bch, reed_solomon, QrCode};
+
+    #[test]
+    fn test_encode() {
+        struct TestCase<'a> {
+            name: &'a str,
+            text: String,
+            want_size: Option<usize>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "QR: Short",
+                text: "HELLO WORLD".to_string(),
+                want_size: Some(21),
+            },
+            TestCase {
+                name: "QR: Link",
+                text: "https://byeongminp.github.io/2048-rust/".to_string(),
+                want_size: Some(29),
+            },
+            TestCase {
+                name: "QR: With Version Bits",
+                text: "x".repeat(150),
+                want_size: Some(49),
+            },
+            TestCase {
+                name: "QR: Too Long",
+                text: "x".repeat(214),
+                want_size: None,
+            },
+        ];
+
+        for t in tests {
+            let qr = QrCode::encode(&t.text);
+
+            assert_eq!(t.want_size, qr.as_ref().map(|qr| qr.size), "{}", t.name);
+            if let Some(qr) = qr {
+                // The top left finder pattern and its separator.
+                assert!(qr.is_dark(0, 0) && qr.is_dark(6, 6), "{}", t.name);
+                assert!(!qr.is_dark(7, 7), "{}", t.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_correction() {
+        // Examples from Thonky's QR code tutorial: the format bits of level M
+        // with mask 5, the version bits of version 7 and the error correction
+        // of "HELLO WORLD" in 1-M.
+        assert_eq!(
+            0b100000011001110,
+            ((5 << 10) | bch(5, 0x537, 10)) ^ 0x5412,
+            "QR: Format Bits"
+        );
+        assert_eq!(0b110010010100, bch(7, 0x1f25, 12), "QR: Version Bits");
+
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        assert_eq!(
+            vec![196, 35, 39, 119, 235, 215, 231, 226, 93, 23],
+            reed_solomon(&data, 10),
+            "QR: Reed-Solomon"
+        );
+    }
+}
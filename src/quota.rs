@@ -0,0 +1,120 @@
+use seed::{prelude::*, *};
+
+// Browsers give local storage about this much per site, in bytes.
+pub const LOCAL_QUOTA: f64 = 5.0 * 1024.0 * 1024.0;
+// Share of a quota past which the player is warned.
+const WARN_AT: f64 = 0.8;
+
+// How full storage is, in bytes.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Usage {
+    // Saves in local (or session) storage.
+    pub local: f64,
+    // Everything the site stores, archived games included, and what the
+    // browser allows for it. Not every browser tells.
+    pub site: Option<(f64, f64)>,
+}
+
+impl Usage {
+    pub fn is_near_quota(&self) -> bool {
+        let site_full = self
+            .site
+            .is_some_and(|(usage, quota)| quota > 0.0 && usage >= quota * WARN_AT);
+        self.local >= LOCAL_QUOTA * WARN_AT || site_full
+    }
+}
+
+// What the browser reports for the whole site, as usage and quota.
+pub async fn estimate() -> Option<(f64, f64)> {
+    let storage = js_sys::Reflect::get(&window().navigator(), &"storage".into()).ok()?;
+    let estimate = js_sys::Reflect::get(&storage, &"estimate".into())
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?
+        .call0(&storage)
+        .ok()?;
+    let estimate = JsFuture::from(js_sys::Promise::from(estimate)).await.ok()?;
+
+    let field = |name: &str| js_sys::Reflect::get(&estimate, &name.into()).ok()?.as_f64();
+    Some((field("usage")?, field("quota")?))
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = 1024.0 * KB;
+    const GB: f64 = 1024.0 * MB;
+
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes / MB)
+    } else {
+        format!("{:.0} KB", (bytes / KB).ceil())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quota::{format_bytes, Usage, LOCAL_QUOTA};
+
+    #[test]
+    fn test_is_near_quota() {
+        struct TestCase<'a> {
+            name: &'a str,
+            usage: Usage,
+            want: bool,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Quota: Empty",
+                usage: Usage::default(),
+                want: false,
+            },
+            TestCase {
+                name: "Quota: Local Almost Full",
+                usage: Usage {
+                    local: LOCAL_QUOTA * 0.9,
+                    site: Some((0.0, 1e9)),
+                },
+                want: true,
+            },
+            TestCase {
+                name: "Quota: Site Almost Full",
+                usage: Usage {
+                    local: 1000.0,
+                    site: Some((85e6, 100e6)),
+                },
+                want: true,
+            },
+            TestCase {
+                name: "Quota: Site Without Quota",
+                usage: Usage {
+                    local: 1000.0,
+                    site: Some((85e6, 0.0)),
+                },
+                want: false,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, t.usage.is_near_quota(), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!("1 KB", format_bytes(10.0), "Bytes: Rounded Up");
+        assert_eq!("512 KB", format_bytes(512.0 * 1024.0), "Bytes: Kilobytes");
+        assert_eq!(
+            "4.5 MB",
+            format_bytes(4.5 * 1024.0 * 1024.0),
+            "Bytes: Megabytes"
+        );
+        assert_eq!(
+            "2.0 GB",
+            format_bytes(2048.0 * 1024.0 * 1024.0),
+            "Bytes: Gigabytes"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::Variant;
+use crate::mode::Mode;
+
+// Only the latest games are kept, every save writes them all.
+const KEPT: usize = 500;
+
+// A finished game, kept to be exported.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GameRecord {
+    // Milliseconds since the epoch.
+    pub ended_at: f64,
+    pub seed: u64,
+    pub variant: Variant,
+    pub mode: Mode,
+    pub score: usize,
+    pub max_tile: usize,
+    pub moves: usize,
+    // Milliseconds, none for games carried over from an earlier visit.
+    pub duration: Option<f64>,
+}
+
+pub fn record(records: &mut Vec<GameRecord>, record: GameRecord) {
+    records.push(record);
+    let dropped = records.len().saturating_sub(KEPT);
+    records.drain(..dropped);
+}
+
+// Adds the games `records` doesn't have yet, e.g. from a backup, keeping the
+// order in which they ended.
+pub fn merge(records: &mut Vec<GameRecord>, other: &[GameRecord]) {
+    for &record in other {
+        if records.iter().all(|r| r.ended_at != record.ended_at) {
+            records.push(record);
+        }
+    }
+    records.sort_by(|a, b| a.ended_at.total_cmp(&b.ended_at));
+    let dropped = records.len().saturating_sub(KEPT);
+    records.drain(..dropped);
+}
+
+// One row per game, oldest first.
+pub fn to_csv(records: &[GameRecord]) -> String {
+    let mut csv = "date,seed,variant,mode,score,max_tile,moves,duration_seconds\n".to_string();
+    for record in records {
+        csv += &format!(
+            "{},{},{:?},{:?},{},{},{},{}\n",
+            iso_date(record.ended_at),
+            record.seed,
+            record.variant,
+            record.mode,
+            record.score,
+            record.max_tile,
+            record.moves,
+            record.duration.map_or(String::new(), |duration| format!(
+                "{:.0}",
+                duration / 1000.0
+            ))
+        );
+    }
+    csv
+}
+
+// UTC, to the second, e.g. 2021-06-01T12:00:00Z.
+fn iso_date(millis: f64) -> String {
+    let seconds = (millis / 1000.0).floor() as i64;
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // Howard Hinnant's days-to-civil conversion.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Variant;
+    use crate::mode::Mode;
+    use crate::records::{iso_date, record, to_csv, GameRecord, KEPT};
+
+    #[test]
+    fn test_iso_date() {
+        struct TestCase<'a> {
+            name: &'a str,
+            millis: f64,
+            want: &'a str,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Date: Epoch",
+                millis: 0.0,
+                want: "1970-01-01T00:00:00Z",
+            },
+            TestCase {
+                name: "Date: Leap Day",
+                millis: 951_782_400_000.0,
+                want: "2000-02-29T00:00:00Z",
+            },
+            TestCase {
+                name: "Date: Time Of Day",
+                millis: 1_622_548_800_999.0,
+                want: "2021-06-01T12:00:00Z",
+            },
+            TestCase {
+                name: "Date: End Of Year",
+                millis: 1_703_980_799_000.0,
+                want: "2023-12-30T23:59:59Z",
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, iso_date(t.millis), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let game = GameRecord {
+            ended_at: 1_622_548_800_000.0,
+            seed: 42,
+            variant: Variant::Obstacles(2),
+            mode: Mode::TimeAttack,
+            score: 2048,
+            max_tile: 256,
+            moves: 300,
+            duration: Some(125_400.0),
+        };
+        let mut records = Vec::new();
+        for _ in 0..KEPT {
+            record(&mut records, game);
+        }
+        record(
+            &mut records,
+            GameRecord {
+                duration: None,
+                ..game
+            },
+        );
+
+        assert_eq!(KEPT, records.len(), "CSV: Keeps The Latest");
+        let csv = to_csv(&records[KEPT - 2..]);
+        assert_eq!(
+            "date,seed,variant,mode,score,max_tile,moves,duration_seconds\n\
+             2021-06-01T12:00:00Z,42,Obstacles(2),TimeAttack,2048,256,300,125\n\
+             2021-06-01T12:00:00Z,42,Obstacles(2),TimeAttack,2048,256,300,\n",
+            csv,
+            "CSV: Rows"
+        );
+    }
+}
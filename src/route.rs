@@ -0,0 +1,103 @@
+use seed::prelude::*;
+
+// The pages of the game, each at its own `#/` address so the browser's back
+// and forward buttons move between them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Page {
+    Game,
+    Stats,
+    Settings,
+    // An archived game, by when it ended.
+    Replay(f64),
+}
+
+impl Page {
+    pub fn from_url(url: &Url) -> Page {
+        let parts: Vec<&str> = url.hash_path().iter().map(String::as_str).collect();
+        Page::from_hash_path(&parts)
+    }
+
+    // Unknown addresses go to the game.
+    fn from_hash_path(parts: &[&str]) -> Page {
+        match parts {
+            ["stats"] => Page::Stats,
+            ["settings"] => Page::Settings,
+            ["replay", id] => id.parse().map_or(Page::Game, Page::Replay),
+            _ => Page::Game,
+        }
+    }
+
+    pub fn href(self) -> String {
+        match self {
+            Page::Game => "#/".to_string(),
+            Page::Stats => "#/stats".to_string(),
+            Page::Settings => "#/settings".to_string(),
+            Page::Replay(ended_at) => format!("#/replay/{}", ended_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::route::Page;
+
+    #[test]
+    fn test_from_hash_path() {
+        struct TestCase<'a> {
+            name: &'a str,
+            parts: &'a [&'a str],
+            want: Page,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Route: Empty",
+                parts: &[],
+                want: Page::Game,
+            },
+            TestCase {
+                name: "Route: Stats",
+                parts: &["stats"],
+                want: Page::Stats,
+            },
+            TestCase {
+                name: "Route: Settings",
+                parts: &["settings"],
+                want: Page::Settings,
+            },
+            TestCase {
+                name: "Route: Replay",
+                parts: &["replay", "1700000000000"],
+                want: Page::Replay(1_700_000_000_000.0),
+            },
+            TestCase {
+                name: "Route: Replay Without Id",
+                parts: &["replay", "latest"],
+                want: Page::Game,
+            },
+            TestCase {
+                name: "Route: Unknown",
+                parts: &["stats", "more"],
+                want: Page::Game,
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, Page::from_hash_path(t.parts), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_href() {
+        for page in [
+            Page::Game,
+            Page::Stats,
+            Page::Settings,
+            Page::Replay(1.5e12),
+        ] {
+            let href = page.href();
+            let parts: Vec<&str> = href[1..].split('/').filter(|p| !p.is_empty()).collect();
+            assert_eq!(page, Page::from_hash_path(&parts), "Href: {}", href);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use seed::{prelude::*, *};
+
+use crate::game_state::GameState;
+use crate::gif::Gif;
+use crate::skin::{self, BoardLayout, TILE_SIZE};
+
+// How long replay frames show, in hundredths of a second.
+const FRAME_DELAY: u16 = 25;
+const LAST_FRAME_DELAY: u16 = 300;
+
+// Draws the board on a canvas of its own and downloads it.
+pub fn save_png(game_state: &GameState, exponents: bool) -> Result<(), JsValue> {
+    let canvas = draw(game_state, exponents)?;
+    download(&canvas.to_data_url_with_type("image/png")?, "2048.png")
+}
+
+// Downloads the game played again from its start as an animated GIF, one
+// frame per action. Returns false for games that can't be replayed.
+pub fn save_gif(game_state: &GameState, exponents: bool) -> Result<bool, JsValue> {
+    let layout = BoardLayout::new(game_state.board());
+    let mut gif = Gif::new(layout.width as u16, layout.height as u16);
+    // Frames are added one behind, the last one shows for longer.
+    let mut previous = None;
+    let mut error = None;
+    let replayed = game_state.replay(|frame| {
+        let pixels = draw(frame, exponents).and_then(|canvas| {
+            context(&canvas)?.get_image_data(0.0, 0.0, layout.width, layout.height)
+        });
+        match pixels {
+            Ok(pixels) => {
+                if let Some(previous) = previous.replace(pixels.data()) {
+                    gif.add_frame(&previous, FRAME_DELAY);
+                }
+            }
+            Err(err) => {
+                error.get_or_insert(err);
+            }
+        }
+    });
+    if let Some(err) = error {
+        return Err(err);
+    }
+    if !replayed {
+        return Ok(false);
+    }
+    if let Some(last) = previous {
+        gif.add_frame(&last, LAST_FRAME_DELAY);
+    }
+
+    download_bytes(&gif.finish(), "2048.gif")?;
+    Ok(true)
+}
+
+fn context(
+    canvas: &web_sys::HtmlCanvasElement,
+) -> Result<web_sys::CanvasRenderingContext2d, JsValue> {
+    canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("No 2D canvas"))?
+        .dyn_into()
+        .map_err(JsValue::from)
+}
+
+// Draws the board from the game rather than the page, so images look the same
+// whatever the page looks like.
+fn draw(game_state: &GameState, exponents: bool) -> Result<web_sys::HtmlCanvasElement, JsValue> {
+    let layout = BoardLayout::new(game_state.board());
+
+    let canvas: web_sys::HtmlCanvasElement = document().create_element("canvas")?.dyn_into()?;
+    canvas.set_width(layout.width as u32);
+    canvas.set_height(layout.height as u32);
+    let context = context(&canvas)?;
+
+    context.set_fill_style(&skin::BOARD_BACKGROUND.into());
+    rounded_rect(&context, 0.0, 0.0, layout.width, layout.height, 6.0);
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+
+    for (&(left, top), cell) in layout.cells.iter().zip(game_state.cells()) {
+        let tile = match cell {
+            Some(tile) => *tile,
+            None => {
+                context.set_fill_style(&skin::EMPTY_CELL.into());
+                rounded_rect(&context, left, top, TILE_SIZE, TILE_SIZE, 3.0);
+                continue;
+            }
+        };
+
+        let (background, text) = skin::tile_colors(tile);
+        context.set_fill_style(&background.into());
+        rounded_rect(&context, left, top, TILE_SIZE, TILE_SIZE, 3.0);
+
+        let label = skin::tile_label(tile, exponents);
+        context.set_font(&format!(
+            "bold {}px {}",
+            skin::font_size(&label),
+            skin::FONT
+        ));
+        context.set_fill_style(&text.into());
+        context.fill_text(&label, left + TILE_SIZE / 2.0, top + TILE_SIZE / 2.0)?;
+    }
+
+    Ok(canvas)
+}
+
+fn rounded_rect(
+    context: &web_sys::CanvasRenderingContext2d,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    radius: f64,
+) {
+    context.begin_path();
+    context.move_to(x + radius, y);
+    let _ = context.arc_to(x + width, y, x + width, y + height, radius);
+    let _ = context.arc_to(x + width, y + height, x, y + height, radius);
+    let _ = context.arc_to(x, y + height, x, y, radius);
+    let _ = context.arc_to(x, y, x + width, y, radius);
+    context.close_path();
+    context.fill();
+}
+
+// Saves `bytes` as a file. Files too big for a data URL go through a blob.
+pub fn download_bytes(bytes: &[u8], filename: &str) -> Result<(), JsValue> {
+    let bytes = js_sys::Uint8Array::from(bytes);
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&js_sys::Array::of1(&bytes))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    download(&url, filename)?;
+    web_sys::Url::revoke_object_url(&url)
+}
+
+// Saves `url` as a file through a throwaway link.
+pub fn download(url: &str, filename: &str) -> Result<(), JsValue> {
+    let link: web_sys::HtmlElement = document().create_element("a")?.dyn_into()?;
+    link.set_attribute("href", url)?;
+    link.set_attribute("download", filename)?;
+
+    let body = document()
+        .body()
+        .ok_or_else(|| JsValue::from_str("No body"))?;
+    body.append_child(&link)?;
+    link.click();
+    body.remove_child(&link)?;
+
+    Ok(())
+}
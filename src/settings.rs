@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{default_target, SpawnPolicy};
+use crate::skin::Theme;
+
+// Preferences that apply to every game, edited below the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub spawn: SpawnPolicy,
+    // Consecutive moves with merges score extra.
+    #[serde(default)]
+    pub combo: bool,
+    // Only picked up by new games.
+    #[serde(default = "default_target")]
+    pub target: Option<usize>,
+    // Label tiles with the exponent of their value.
+    #[serde(default)]
+    pub exponents: bool,
+    // Moves kept while the tiles are still sliding, 0 drops them instead.
+    #[serde(default = "default_input_buffer")]
+    pub input_buffer: usize,
+    // Least time between moves repeated by a held key, in milliseconds.
+    #[serde(default = "default_repeat_interval")]
+    pub repeat_interval: u32,
+    // Held keys only move once in competitive modes.
+    #[serde(default)]
+    pub competitive_no_repeat: bool,
+    // Scrolling over the board plays moves instead of scrolling the page.
+    #[serde(default)]
+    pub wheel_moves: bool,
+    // Tipping the device plays moves, see `tilt`.
+    #[serde(default)]
+    pub tilt: bool,
+    // Degrees the device has to be tipped by to move.
+    #[serde(default = "default_tilt_threshold")]
+    pub tilt_threshold: u32,
+    // Least time between moves while the device stays tipped, in milliseconds.
+    #[serde(default = "default_tilt_cooldown")]
+    pub tilt_cooldown: u32,
+    // One-switch play: Space steps through the directions, Enter or resting
+    // on one for `scan_dwell` milliseconds plays it.
+    #[serde(default)]
+    pub scanning: bool,
+    #[serde(default = "default_scan_dwell")]
+    pub scan_dwell: u32,
+    // Empty cells show the chance of the next tile spawning there.
+    #[serde(default)]
+    pub spawn_odds: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    // Size of the whole game in percent, board and text alike.
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: u32,
+    // A shape on every tile for its value, see `skin::tile_glyph`.
+    #[serde(default)]
+    pub glyphs: bool,
+    // The high-contrast theme was offered once for a system asking for more
+    // contrast, and isn't offered again.
+    #[serde(default)]
+    pub contrast_suggested: bool,
+    // The tutorial was played through or skipped.
+    #[serde(default)]
+    pub tutorial_done: bool,
+    // Finished games make way for a new one by themselves, see `AUTO_RESTART_MS`.
+    #[serde(default)]
+    pub auto_restart: bool,
+    // Times to every milestone of classic games, see `splits`.
+    #[serde(default)]
+    pub show_splits: bool,
+    // The chance of reaching the target and of lasting the next moves, see
+    // `ai::outlook`.
+    #[serde(default)]
+    pub show_outlook: bool,
+    // Each move graded against the computer's, see `grade`.
+    #[serde(default)]
+    pub show_grades: bool,
+    // The range of final scores the game is headed for, see
+    // `ai::projection`.
+    #[serde(default)]
+    pub show_projection: bool,
+}
+
+fn default_ui_scale() -> u32 {
+    100
+}
+
+fn default_input_buffer() -> usize {
+    2
+}
+
+fn default_repeat_interval() -> u32 {
+    150
+}
+
+fn default_tilt_threshold() -> u32 {
+    20
+}
+
+fn default_tilt_cooldown() -> u32 {
+    500
+}
+
+fn default_scan_dwell() -> u32 {
+    2000
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            spawn: SpawnPolicy::default(),
+            combo: false,
+            target: default_target(),
+            exponents: false,
+            input_buffer: default_input_buffer(),
+            repeat_interval: default_repeat_interval(),
+            competitive_no_repeat: false,
+            wheel_moves: false,
+            tilt: false,
+            tilt_threshold: default_tilt_threshold(),
+            tilt_cooldown: default_tilt_cooldown(),
+            scanning: false,
+            scan_dwell: default_scan_dwell(),
+            spawn_odds: false,
+            theme: Theme::default(),
+            ui_scale: default_ui_scale(),
+            glyphs: false,
+            contrast_suggested: false,
+            tutorial_done: false,
+            auto_restart: false,
+            show_splits: false,
+            show_outlook: false,
+            show_grades: false,
+            show_projection: false,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use seed::{prelude::*, *};
+
+use crate::game_state::GameState;
+
+pub enum Shared {
+    // Handed to the share sheet, or the player closed it.
+    Sheet,
+    Clipboard,
+}
+
+// "I scored 35,912 in 2048! ..."
+pub fn message(score: usize, seed: u64, url: &str) -> String {
+    format!(
+        "I scored {} in 2048! Seed: {} URL: {}",
+        thousands(score),
+        seed,
+        url
+    )
+}
+
+// "Can you beat my 35,912 in 2048? ..."
+pub fn challenge(score: usize, url: &str) -> String {
+    format!("Can you beat my {} in 2048? {}", thousands(score), url)
+}
+
+// The board as lined up text with the score and seed below, fenced so chats
+// and issues keep it monospace.
+pub fn board_text(game_state: &GameState) -> String {
+    format!(
+        "```\n{}\n```\nScore: {} Seed: {}",
+        game_state,
+        thousands(game_state.score()),
+        game_state.seed()
+    )
+}
+
+pub fn thousands(number: usize) -> String {
+    let digits = number.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// Opens the share sheet where the browser has one (mostly on phones) and
+// copies the text to the clipboard otherwise. Neither has web-sys bindings
+// without unstable flags, so both are called by name.
+pub async fn share(text: String) -> Result<Shared, String> {
+    let navigator = window().navigator();
+
+    if let Ok(share) = js_sys::Reflect::get(&navigator, &"share".into()) {
+        if share.is_function() {
+            let data = js_sys::Object::new();
+            let _ = js_sys::Reflect::set(&data, &"text".into(), &text.as_str().into());
+            let result = match share
+                .unchecked_into::<js_sys::Function>()
+                .call1(&navigator, &data)
+            {
+                Ok(promise) => JsFuture::from(js_sys::Promise::from(promise)).await,
+                Err(err) => Err(err),
+            };
+
+            // Closing the sheet is no reason to copy instead.
+            let aborted = |err: &JsValue| {
+                js_sys::Reflect::get(err, &"name".into())
+                    .ok()
+                    .and_then(|name| name.as_string())
+                    .as_deref()
+                    == Some("AbortError")
+            };
+            match result {
+                Ok(_) => return Ok(Shared::Sheet),
+                Err(err) if aborted(&err) => return Ok(Shared::Sheet),
+                Err(_) => (),
+            }
+        }
+    }
+
+    copy(text).await?;
+
+    Ok(Shared::Clipboard)
+}
+
+// Puts the text on the clipboard.
+pub async fn copy(text: String) -> Result<(), String> {
+    let navigator = window().navigator();
+    let clipboard = js_sys::Reflect::get(&navigator, &"clipboard".into())
+        .ok()
+        .filter(|clipboard| !clipboard.is_undefined())
+        .ok_or_else(|| "Copying isn't supported by this browser.".to_string())?;
+    let write_text = js_sys::Reflect::get(&clipboard, &"writeText".into())
+        .map_err(|err| format!("{:?}", err))?
+        .unchecked_into::<js_sys::Function>();
+    let promise = write_text
+        .call1(&clipboard, &text.as_str().into())
+        .map_err(|err| format!("{:?}", err))?;
+    JsFuture::from(js_sys::Promise::from(promise))
+        .await
+        .map(|_| ())
+        .map_err(|_| "Couldn't copy to the clipboard.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::share::{board_text, message};
+
+    #[test]
+    fn test_message() {
+        struct TestCase<'a> {
+            name: &'a str,
+            score: usize,
+            want: &'a str,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Small Score",
+                score: 912,
+                want: "I scored 912 in 2048! Seed: 7 URL: https://example.com/",
+            },
+            TestCase {
+                name: "Grouped Thousands",
+                score: 35_912,
+                want: "I scored 35,912 in 2048! Seed: 7 URL: https://example.com/",
+            },
+            TestCase {
+                name: "Millions",
+                score: 1_035_912,
+                want: "I scored 1,035,912 in 2048! Seed: 7 URL: https://example.com/",
+            },
+        ];
+
+        for t in tests {
+            let got = message(t.score, 7, "https://example.com/");
+
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_board_text() {
+        let gs = GameState::from_values(&[2, 0, 0, 1024, 0, 16, 0, 0, 0, 0, 0, 0, 4, 0, 0, 2]);
+
+        assert_eq!(
+            "```\n   2    .    . 1024\n   .   16    .    .\n   .    .    .    .\n   4    .    .    2\n```\nScore: 0 Seed: 0",
+            board_text(&gs)
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ai;
+use crate::evaluate::Weights;
+use crate::game_state::{Direction, GameState, SpawnPolicy, Variant};
+use crate::rng::GameRng;
+use crate::transposition::{TableStats, TranspositionTable};
+
+// Picks the moves of a simulated game.
+pub trait Strategy {
+    // None gives the game up, e.g. on a stuck board.
+    fn choose(&mut self, game_state: &GameState) -> Option<Direction>;
+
+    // How the strategy's transposition table fared, if it searches with one.
+    fn table_stats(&self) -> Option<TableStats> {
+        None
+    }
+}
+
+// The computer of races, see `ai::best_move`.
+pub struct Greedy {
+    weights: Weights,
+}
+
+impl Strategy for Greedy {
+    fn choose(&mut self, game_state: &GameState) -> Option<Direction> {
+        ai::best_move(game_state, &self.weights)
+    }
+}
+
+// Any legal move, a baseline for the others to beat.
+pub struct RandomMoves {
+    rng: GameRng,
+}
+
+impl Strategy for RandomMoves {
+    fn choose(&mut self, game_state: &GameState) -> Option<Direction> {
+        game_state.legal_moves().choose(&mut self.rng)
+    }
+}
+
+// Random games after every move, see `ai::rollout_move`.
+pub struct MonteCarlo {
+    rollouts: usize,
+    depth: usize,
+    rng: GameRng,
+}
+
+impl Strategy for MonteCarlo {
+    fn choose(&mut self, game_state: &GameState) -> Option<Direction> {
+        ai::rollout_move(game_state, self.rollouts, self.depth, &mut self.rng)
+    }
+}
+
+// Looks ahead against the worst spawns, see `ai::minimax_move`.
+pub struct Minimax {
+    plies: usize,
+    weights: Weights,
+    table: TranspositionTable,
+}
+
+impl Strategy for Minimax {
+    fn choose(&mut self, game_state: &GameState) -> Option<Direction> {
+        ai::minimax_move(game_state, self.plies, &self.weights, &mut self.table)
+    }
+
+    fn table_stats(&self) -> Option<TableStats> {
+        Some(self.table.stats())
+    }
+}
+
+// Every strategy by the name the options give it.
+pub fn strategy(options: &Options) -> Result<Box<dyn Strategy>, String> {
+    match options.strategy.as_str() {
+        "greedy" => Ok(Box::new(Greedy {
+            weights: options.weights,
+        })),
+        "random" => Ok(Box::new(RandomMoves {
+            rng: GameRng::new(options.seed),
+        })),
+        "monte-carlo" => Ok(Box::new(MonteCarlo {
+            rollouts: options.rollouts,
+            depth: options.depth,
+            rng: GameRng::new(options.seed),
+        })),
+        "minimax" => Ok(Box::new(Minimax {
+            plies: options.plies,
+            weights: options.weights,
+            table: TranspositionTable::default(),
+        })),
+        name => Err(format!("Unknown strategy \"{}\"", name)),
+    }
+}
+
+// What to simulate, read from JSON with every field optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    pub games: usize,
+    pub strategy: String,
+    // Game `i` is played from `seed + i`, so runs can be repeated.
+    pub seed: u64,
+    pub variant: Variant,
+    pub spawn: SpawnPolicy,
+    // None plays on past 2048 until the board is stuck.
+    pub target: Option<usize>,
+    // Cuts off games a strategy never finishes.
+    pub max_moves: usize,
+    // What the strategies that evaluate boards go by.
+    pub weights: Weights,
+    // Games played after each move by the Monte Carlo strategy, and how
+    // many moves each goes on for at most.
+    pub rollouts: usize,
+    pub depth: usize,
+    // Moves the minimax strategy looks ahead.
+    pub plies: usize,
+    // When recording samples, the share of moves recorded, picked at
+    // random, and how many at most.
+    pub sample_rate: f64,
+    pub max_samples: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            games: 100,
+            strategy: "greedy".to_string(),
+            seed: 0,
+            variant: Variant::Classic,
+            spawn: SpawnPolicy::Classic,
+            target: None,
+            max_moves: 100_000,
+            weights: Weights::default(),
+            rollouts: 50,
+            depth: 100,
+            plies: 2,
+            sample_rate: 1.0,
+            max_samples: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scores {
+    pub min: usize,
+    pub p25: usize,
+    pub median: usize,
+    pub p75: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl Scores {
+    fn new(mut scores: Vec<usize>) -> Scores {
+        scores.sort_unstable();
+        let at = |fraction: f64| {
+            let last = scores.len().saturating_sub(1);
+            scores
+                .get((last as f64 * fraction).round() as usize)
+                .copied()
+                .unwrap_or(0)
+        };
+        Scores {
+            min: at(0.0),
+            p25: at(0.25),
+            median: at(0.5),
+            p75: at(0.75),
+            max: at(1.0),
+            mean: scores.iter().sum::<usize>() as f64 / scores.len().max(1) as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub strategy: String,
+    pub games: usize,
+    pub scores: Scores,
+    // How many games ended on each best tile.
+    pub max_tiles: BTreeMap<usize, usize>,
+    pub average_moves: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<TableStats>,
+}
+
+// A move of a simulated game and how the game ended, for training models on.
+// The board is the values of its cells, 0 for empty ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sample {
+    pub board: Vec<usize>,
+    pub score: usize,
+    #[serde(rename = "move")]
+    pub direction: Direction,
+    pub final_score: usize,
+    pub final_max_tile: usize,
+}
+
+pub fn run(options: &Options) -> Result<Report, String> {
+    play(options, None)
+}
+
+// Also writes samples of the moves to `out`, a line of JSON each. The games
+// are the same as without.
+pub fn run_sampled(options: &Options, out: &mut dyn Write) -> Result<Report, String> {
+    play(options, Some(out))
+}
+
+fn play(options: &Options, mut out: Option<&mut dyn Write>) -> Result<Report, String> {
+    let mut strategy = strategy(options)?;
+    // Apart from the games' own, so sampling doesn't change them.
+    let mut sampler = GameRng::new(options.seed);
+    let mut sampled = 0;
+    let mut scores = Vec::with_capacity(options.games);
+    let mut max_tiles = BTreeMap::new();
+    let mut moves = 0;
+
+    for i in 0..options.games {
+        let seed = options.seed.wrapping_add(i as u64);
+        let mut game_state = GameState::with_seed(options.variant, options.spawn, seed);
+        game_state.set_target(options.target);
+        let mut samples = Vec::new();
+        for _ in 0..options.max_moves {
+            let direction = match strategy.choose(&game_state) {
+                Some(direction) => direction,
+                None => break,
+            };
+            let sample = (out.is_some()
+                && sampled + samples.len() < options.max_samples
+                && sampler.gen::<f64>() < options.sample_rate)
+                .then(|| Sample {
+                    board: game_state
+                        .cells()
+                        .iter()
+                        .map(|cell| cell.map_or(0, |tile| tile.get_value()))
+                        .collect(),
+                    score: game_state.score(),
+                    direction,
+                    // Known once the game is over.
+                    final_score: 0,
+                    final_max_tile: 0,
+                });
+            if game_state.apply_moves(&[direction]).applied == 0 {
+                break;
+            }
+            samples.extend(sample);
+        }
+
+        if let Some(out) = out.as_mut() {
+            sampled += samples.len();
+            for mut sample in samples {
+                sample.final_score = game_state.score();
+                sample.final_max_tile = game_state.max_tile();
+                serde_json::to_writer(&mut **out, &sample).map_err(|err| err.to_string())?;
+                out.write_all(b"\n").map_err(|err| err.to_string())?;
+            }
+        }
+
+        scores.push(game_state.score());
+        *max_tiles.entry(game_state.max_tile()).or_insert(0) += 1;
+        moves += game_state.move_count();
+    }
+
+    Ok(Report {
+        strategy: options.strategy.clone(),
+        games: options.games,
+        scores: Scores::new(scores),
+        max_tiles,
+        average_moves: moves as f64 / options.games.max(1) as f64,
+        table: strategy.table_stats(),
+    })
+}
+
+// Options in and the report out as JSON, for the command line and the page.
+pub fn run_json(options: &str) -> Result<String, String> {
+    let options: Options =
+        serde_json::from_str(options).map_err(|err| format!("Invalid options: {}", err))?;
+    serde_json::to_string(&run(&options)?).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulate::{run, run_json, run_sampled, Options, Scores};
+
+    #[test]
+    fn test_scores() {
+        let scores = Scores::new(vec![40, 10, 30, 20, 50]);
+        assert_eq!(
+            (10, 20, 30, 40, 50),
+            (
+                scores.min,
+                scores.p25,
+                scores.median,
+                scores.p75,
+                scores.max
+            ),
+            "Scores: Percentiles"
+        );
+        assert_eq!(30.0, scores.mean, "Scores: Mean");
+    }
+
+    #[test]
+    fn test_run() {
+        let options = Options {
+            games: 3,
+            ..Options::default()
+        };
+        let report = run(&options).unwrap();
+        assert_eq!(3, report.max_tiles.values().sum::<usize>(), "Run: Games");
+        assert!(report.average_moves > 0.0, "Run: Moves");
+        assert_eq!(report, run(&options).unwrap(), "Run: Repeatable");
+
+        let mut out = Vec::new();
+        let sampled = Options {
+            sample_rate: 0.5,
+            max_samples: 5,
+            ..options.clone()
+        };
+        assert_eq!(
+            report,
+            run_sampled(&sampled, &mut out).unwrap(),
+            "Run: Same Games Sampled"
+        );
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(5, lines.len(), "Run: Max Samples");
+        assert!(
+            lines
+                .iter()
+                .all(|line| line["board"].as_array().unwrap().len() == 16
+                    && line["finalScore"].as_u64().unwrap() >= line["score"].as_u64().unwrap()),
+            "Run: Samples"
+        );
+
+        let greedy = run_json(r#"{"games": 2}"#).unwrap();
+        assert!(greedy.contains(r#""strategy":"greedy""#), "JSON: Defaults");
+        assert!(
+            run_json(r#"{"games": 1, "strategy": "monte-carlo", "rollouts": 2, "depth": 5}"#)
+                .is_ok(),
+            "JSON: Monte Carlo"
+        );
+        assert!(
+            run_json(r#"{"games": 1, "strategy": "minimax", "spawn": "Evil", "plies": 1}"#).is_ok(),
+            "JSON: Minimax"
+        );
+        assert!(
+            run_json(r#"{"strategy": "psychic"}"#).is_err(),
+            "JSON: Unknown Strategy"
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::Tile;
+use crate::topology::Board;
+
+pub const TILE_SIZE: f64 = 107.0;
+pub const GRID_SPACING: f64 = 15.0;
+pub const BOARD_BACKGROUND: &str = "#bbada0";
+pub const EMPTY_CELL: &str = "rgba(238, 228, 218, 0.35)";
+pub const FONT: &str = "\"Clear Sans\", \"Helvetica Neue\", Arial, sans-serif";
+
+const DARK_TEXT: &str = "#776e65";
+const BRIGHT_TEXT: &str = "#f9f6f2";
+
+// Background and text color of the number tiles up to 2048, as in main.css.
+const TILE_COLORS: [(usize, &str, &str); 11] = [
+    (2, "#eee4da", DARK_TEXT),
+    (4, "#ede0c8", DARK_TEXT),
+    (8, "#f2b179", BRIGHT_TEXT),
+    (16, "#f59563", BRIGHT_TEXT),
+    (32, "#f67c5f", BRIGHT_TEXT),
+    (64, "#f65e3b", BRIGHT_TEXT),
+    (128, "#edcf72", BRIGHT_TEXT),
+    (256, "#edcc61", BRIGHT_TEXT),
+    (512, "#edc850", BRIGHT_TEXT),
+    (1024, "#edc53f", BRIGHT_TEXT),
+    (2048, "#edc22e", BRIGHT_TEXT),
+];
+
+// Tile colors of a theme that are set from the view instead of main.css.
+struct Palette {
+    values: [(usize, &'static str, &'static str); 11],
+    obstacle: (&'static str, &'static str),
+    bomb: (&'static str, &'static str),
+    multiplier: (&'static str, &'static str),
+    // Every value past 2048.
+    larger: (&'static str, &'static str),
+}
+
+// Every pair is at least 7:1 apart (WCAG AAA), and neighbouring values
+// switch between light and dark so they differ in more than hue.
+const HIGH_CONTRAST: Palette = Palette {
+    values: [
+        (2, "#ffffff", "#000000"),
+        (4, "#fff176", "#000000"),
+        (8, "#ffb74d", "#000000"),
+        (16, "#ff8a80", "#000000"),
+        (32, "#80d8ff", "#000000"),
+        (64, "#b9f6ca", "#000000"),
+        (128, "#000000", "#ffffff"),
+        (256, "#0d47a1", "#ffffff"),
+        (512, "#1b5e20", "#ffffff"),
+        (1024, "#8e0000", "#ffffff"),
+        (2048, "#4a148c", "#ffffff"),
+    ],
+    obstacle: ("#4d4d4d", "#ffffff"),
+    bomb: ("#000000", "#ffff00"),
+    multiplier: ("#ffff00", "#000000"),
+    larger: ("#000000", "#00e5ff"),
+};
+
+// Yellow to blue, getting darker with every value (cividis), so no two
+// values hinge on telling red from green. Text is at least 4.5:1 (WCAG AA).
+const DEUTERANOPIA: Palette = Palette {
+    values: [
+        (2, "#ffea46", "#000000"),
+        (4, "#e4cf5b", "#000000"),
+        (8, "#c4b56c", "#000000"),
+        (16, "#a69d75", "#000000"),
+        (32, "#8a8779", "#000000"),
+        (64, "#707173", "#ffffff"),
+        (128, "#575c6d", "#ffffff"),
+        (256, "#39486b", "#ffffff"),
+        (512, "#00336f", "#ffffff"),
+        (1024, "#00204d", "#ffffff"),
+        (2048, "#000000", "#ffea46"),
+    ],
+    obstacle: ("#5a534b", "#ffffff"),
+    bomb: ("#000000", "#ffffff"),
+    multiplier: ("#ffea46", "#000000"),
+    larger: ("#000000", "#ffffff"),
+};
+
+// Yellow through green to violet (viridis), reds look dark without the
+// red cones and are left out altogether.
+const PROTANOPIA: Palette = Palette {
+    values: [
+        (2, "#fde725", "#000000"),
+        (4, "#b5de2b", "#000000"),
+        (8, "#6ece58", "#000000"),
+        (16, "#35b779", "#000000"),
+        (32, "#1f9e89", "#000000"),
+        (64, "#26828e", "#ffffff"),
+        (128, "#31688e", "#ffffff"),
+        (256, "#3e4989", "#ffffff"),
+        (512, "#482878", "#ffffff"),
+        (1024, "#440154", "#ffffff"),
+        (2048, "#000000", "#fde725"),
+    ],
+    obstacle: ("#5a534b", "#ffffff"),
+    bomb: ("#000000", "#ffffff"),
+    multiplier: ("#fde725", "#000000"),
+    larger: ("#000000", "#ffffff"),
+};
+
+// Marks every value with its own shape, for telling tiles apart without
+// their colors.
+const GLYPHS: [&str; 11] = ["●", "▲", "■", "◆", "★", "✚", "♥", "♠", "♣", "⬟", "✿"];
+
+// How tiles and the board look, picked in the settings.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Theme {
+    #[default]
+    Classic,
+    HighContrast,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl Theme {
+    fn palette(self) -> Option<&'static Palette> {
+        match self {
+            Theme::Classic => None,
+            Theme::HighContrast => Some(&HIGH_CONTRAST),
+            Theme::Deuteranopia => Some(&DEUTERANOPIA),
+            Theme::Protanopia => Some(&PROTANOPIA),
+        }
+    }
+
+    // Added to the board's classes, none for the classic look of main.css.
+    pub fn class(self) -> Option<&'static str> {
+        match self {
+            Theme::Classic | Theme::Deuteranopia | Theme::Protanopia => None,
+            Theme::HighContrast => Some("high-contrast"),
+        }
+    }
+
+    // Background and text color of a tile, none where main.css draws it.
+    pub fn tile_colors(self, tile: Tile) -> Option<(&'static str, &'static str)> {
+        let palette = self.palette()?;
+        Some(if tile.is_obstacle() {
+            palette.obstacle
+        } else if tile.is_bomb() {
+            palette.bomb
+        } else if tile.is_multiplier() {
+            palette.multiplier
+        } else if tile.get_value() > 2048 {
+            palette.larger
+        } else {
+            by_value(&palette.values, tile.get_value())
+        })
+    }
+}
+
+// The shape overlaid on a number tile, the same for values between powers
+// of two as for the power below them.
+pub fn tile_glyph(tile: Tile) -> Option<&'static str> {
+    let value = tile.get_value();
+    if !tile.is_number() || value < 2 {
+        return None;
+    }
+    let exponent = (usize::BITS - 1 - value.leading_zeros()) as usize;
+    GLYPHS.get(exponent - 1).copied()
+}
+
+// The colors of the highest value in `palette` up to `value`.
+fn by_value(
+    palette: &[(usize, &'static str, &'static str)],
+    value: usize,
+) -> (&'static str, &'static str) {
+    let (_, background, text) = palette
+        .iter()
+        .rev()
+        .find(|&&(at_least, _, _)| value >= at_least)
+        .unwrap_or(&palette[0]);
+    (background, text)
+}
+
+// The text shown on a tile. With `exponents`, powers of two are shown as
+// their exponent (11 for 2048); other values, like those of Threes, stay as they are.
+pub fn tile_label(tile: Tile, exponents: bool) -> String {
+    let value = tile.get_value();
+
+    if tile.is_multiplier() {
+        "×2".to_string()
+    } else if !tile.is_number() {
+        String::new()
+    } else if exponents && value.is_power_of_two() {
+        value.trailing_zeros().to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// Background and text color of a tile, for drawing it without the page's CSS.
+// Values between powers of two (e.g. in Threes) take the color below them.
+pub fn tile_colors(tile: Tile) -> (&'static str, &'static str) {
+    let value = tile.get_value();
+
+    if tile.is_obstacle() {
+        ("#5a534b", BRIGHT_TEXT)
+    } else if tile.is_bomb() {
+        ("#3c3a32", BRIGHT_TEXT)
+    } else if tile.is_multiplier() {
+        ("#edc22e", BRIGHT_TEXT)
+    } else if value > 2048 {
+        ("#3c3a32", BRIGHT_TEXT)
+    } else {
+        by_value(&TILE_COLORS, value)
+    }
+}
+
+// Where the cells of a board go in images of it, in pixels.
+pub struct BoardLayout {
+    // Top left corner of every cell.
+    pub cells: Vec<(f64, f64)>,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl BoardLayout {
+    pub fn new(board: Board) -> BoardLayout {
+        let topology = board.topology();
+        let positions: Vec<(f64, f64)> = (0..topology.cell_count())
+            .map(|index| topology.position(index))
+            .collect();
+        let columns = positions.iter().map(|&(x, _)| x).fold(0.0, f64::max) + 1.0;
+        let rows = positions.iter().map(|&(_, y)| y).fold(0.0, f64::max) + 1.0;
+        let corner = |cells: f64| GRID_SPACING + cells * (TILE_SIZE + GRID_SPACING);
+
+        BoardLayout {
+            cells: positions
+                .iter()
+                .map(|&(x, y)| (corner(x), corner(y)))
+                .collect(),
+            width: corner(columns),
+            height: corner(rows),
+        }
+    }
+}
+
+// Smaller type for longer labels, like the page does.
+pub fn font_size(label: &str) -> u32 {
+    match label.chars().count() {
+        0..=2 => 55,
+        3 => 45,
+        4 => 35,
+        _ => 25,
+    }
+}
+
+// How a cell is read out by screen readers.
+pub fn cell_label(tile: Option<Tile>) -> String {
+    match tile {
+        None => "Empty".to_string(),
+        Some(tile) if tile.is_obstacle() => "Obstacle".to_string(),
+        Some(tile) if tile.is_bomb() => "Bomb".to_string(),
+        Some(tile) => tile_label(tile, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{GameState, MergeRule, Tile};
+    use crate::skin::{tile_colors, tile_glyph, tile_label, Theme};
+
+    // A tile of the value, from the Threes rules for values between powers
+    // of two.
+    fn tile(value: usize) -> Tile {
+        let rules = if value.is_power_of_two() {
+            MergeRule::Classic
+        } else {
+            MergeRule::Threes
+        };
+        GameState::builder()
+            .rules(rules)
+            .with_tiles(&[(0, value)])
+            .generate_tiles(false)
+            .build()
+            .unwrap()
+            .cells()[0]
+            .unwrap()
+    }
+
+    // WCAG contrast ratio of two `#rrggbb` colors.
+    fn contrast(a: &str, b: &str) -> f64 {
+        let luminance = |color: &str| {
+            let channel = |i: usize| {
+                let c = f64::from(u8::from_str_radix(&color[i..i + 2], 16).unwrap()) / 255.0;
+                if c <= 0.03928 {
+                    c / 12.92
+                } else {
+                    ((c + 0.055) / 1.055).powf(2.4)
+                }
+            };
+            0.2126 * channel(1) + 0.7152 * channel(3) + 0.0722 * channel(5)
+        };
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn test_tile_label() {
+        struct TestCase<'a> {
+            name: &'a str,
+            value: usize,
+            exponents: bool,
+            want: &'a str,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Label: Value",
+                value: 2048,
+                exponents: false,
+                want: "2048",
+            },
+            TestCase {
+                name: "Label: Exponent",
+                value: 2048,
+                exponents: true,
+                want: "11",
+            },
+            TestCase {
+                name: "Label: Smallest Exponent",
+                value: 2,
+                exponents: true,
+                want: "1",
+            },
+            TestCase {
+                name: "Label: Not A Power Of Two",
+                value: 768,
+                exponents: true,
+                want: "768",
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, tile_label(tile(t.value), t.exponents), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_tile_colors() {
+        struct TestCase<'a> {
+            name: &'a str,
+            tile: Tile,
+            want: (&'a str, &'a str),
+        }
+
+        let tests = [
+            TestCase {
+                name: "Colors: Smallest",
+                tile: tile(2),
+                want: ("#eee4da", "#776e65"),
+            },
+            TestCase {
+                name: "Colors: 2048",
+                tile: tile(2048),
+                want: ("#edc22e", "#f9f6f2"),
+            },
+            TestCase {
+                name: "Colors: Super",
+                tile: tile(8192),
+                want: ("#3c3a32", "#f9f6f2"),
+            },
+            TestCase {
+                name: "Colors: Between Powers",
+                tile: tile(12),
+                want: ("#f2b179", "#f9f6f2"),
+            },
+        ];
+
+        for t in tests {
+            assert_eq!(t.want, tile_colors(t.tile), "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_themes() {
+        assert_eq!(None, Theme::Classic.tile_colors(tile(2)), "Theme: Classic");
+        for (theme, min_contrast) in [
+            (Theme::HighContrast, 7.0),
+            (Theme::Deuteranopia, 4.5),
+            (Theme::Protanopia, 4.5),
+        ] {
+            let palette = theme.palette().unwrap();
+            for &(value, background, text) in &palette.values {
+                assert_eq!(
+                    Some((background, text)),
+                    theme.tile_colors(tile(value)),
+                    "{:?}: {}",
+                    theme,
+                    value
+                );
+            }
+            for value in [2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 8192, 12] {
+                let (background, text) = theme.tile_colors(tile(value)).unwrap();
+                assert!(
+                    contrast(background, text) >= min_contrast,
+                    "{:?}: {} is {:.1}:1",
+                    theme,
+                    value,
+                    contrast(background, text)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_glyph() {
+        assert_eq!(Some("●"), tile_glyph(tile(2)), "Glyph: Smallest");
+        assert_eq!(Some("✿"), tile_glyph(tile(2048)), "Glyph: 2048");
+        assert_eq!(Some("■"), tile_glyph(tile(12)), "Glyph: Between Powers");
+        assert_eq!(None, tile_glyph(tile(4096)), "Glyph: Past 2048");
+        let glyphs: std::collections::BTreeSet<_> = (1..=11)
+            .filter_map(|exponent| tile_glyph(tile(1 << exponent)))
+            .collect();
+        assert_eq!(11, glyphs.len(), "Glyph: One Per Value");
+    }
+}
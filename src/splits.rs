@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+// The tiles a speedrun is timed at.
+pub const MILESTONES: [usize; 5] = [128, 256, 512, 1024, 2048];
+
+// How long a game took to reach each milestone, in milliseconds from its
+// start, in the order of `MILESTONES`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Splits {
+    times: [Option<f64>; 5],
+}
+
+impl Splits {
+    // Notes the milestones the tile reaches for the first time. Returns
+    // whether there were any.
+    pub fn reach(&mut self, max_tile: usize, elapsed: f64) -> bool {
+        let mut reached = false;
+        for (time, &milestone) in self.times.iter_mut().zip(&MILESTONES) {
+            if time.is_none() && max_tile >= milestone {
+                *time = Some(elapsed);
+                reached = true;
+            }
+        }
+        reached
+    }
+
+    // The quicker time of every milestone.
+    pub fn best(&self, other: &Splits) -> Splits {
+        let mut best = *self;
+        for (time, &other) in best.times.iter_mut().zip(&other.times) {
+            *time = match (*time, other) {
+                (Some(time), Some(other)) => Some(time.min(other)),
+                (time, other) => time.or(other),
+            };
+        }
+        best
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Option<f64>)> + '_ {
+        MILESTONES.iter().copied().zip(self.times.iter().copied())
+    }
+}
+
+// Minutes, seconds and tenths, e.g. "1:05.3".
+pub fn format_time(ms: f64) -> String {
+    let tenths = (ms / 100.0).round() as u64;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+// Seconds ahead of (-) or behind (+) the best split.
+pub fn format_delta(ms: f64) -> String {
+    let sign = if ms < 0.0 { '-' } else { '+' };
+    format!("{}{:.1}", sign, ms.abs() / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::splits::{format_delta, format_time, Splits};
+
+    #[test]
+    fn test_splits() {
+        let mut splits = Splits::default();
+        assert!(!splits.reach(64, 1000.0), "Splits: Below The First");
+        assert!(splits.reach(256, 2000.0), "Splits: Two At Once");
+        assert!(!splits.reach(256, 3000.0), "Splits: Only Once");
+        assert!(splits.reach(512, 4000.0), "Splits: Next");
+
+        let mut best = Splits::default();
+        best.reach(128, 1500.0);
+        best.reach(1024, 9000.0);
+        let times: Vec<Option<f64>> = splits.best(&best).iter().map(|(_, time)| time).collect();
+        assert_eq!(
+            vec![Some(1500.0), Some(2000.0), Some(4000.0), Some(9000.0), None],
+            times,
+            "Splits: Best"
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("0:00.0", format_time(0.0), "Time: Zero");
+        assert_eq!("1:05.3", format_time(65_280.0), "Time: Minutes");
+        assert_eq!("0:59.9", format_time(59_940.0), "Time: Tenths");
+        assert_eq!("+1.5", format_delta(1_500.0), "Delta: Behind");
+        assert_eq!("-0.3", format_delta(-320.0), "Delta: Ahead");
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::game_state::{GameEvent, GameState};
+use crate::topology::Board;
+
+// Tiles from this value up count towards the heatmap.
+pub const HIGH_TILE: usize = 256;
+
+// What one device counted.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Counters {
+    games_played: usize,
+    games_won: usize,
+    // How often each cell of the square board held a high tile when a game
+    // ended, row by row.
+    #[serde(default)]
+    heatmap: [usize; 16],
+    #[serde(default)]
+    tiles_merged: usize,
+}
+
+impl Counters {
+    fn zip(&self, other: &Counters, f: impl Fn(usize, usize) -> usize) -> Counters {
+        let mut heatmap = self.heatmap;
+        for (count, &other) in heatmap.iter_mut().zip(&other.heatmap) {
+            *count = f(*count, other);
+        }
+        Counters {
+            games_played: f(self.games_played, other.games_played),
+            games_won: f(self.games_won, other.games_won),
+            heatmap,
+            tiles_merged: f(self.tiles_merged, other.tiles_merged),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stats {
+    // The key this device counts under in `devices`. Every device keeps its
+    // own, so it isn't saved with the stats.
+    #[serde(skip)]
+    device: String,
+    // Counted before every device kept its own counters. They were synced
+    // alike everywhere, so they only ever merge by the maximum.
+    #[serde(flatten)]
+    earlier: Counters,
+    // Each device's own counters. A device only ever counts up its own, so
+    // merging keeps the larger of each device's and the totals add the
+    // devices up.
+    #[serde(default)]
+    devices: BTreeMap<String, Counters>,
+    best_score: usize,
+    best_tile: usize,
+}
+
+impl Stats {
+    pub fn set_device(&mut self, device: &str) {
+        self.device = device.to_string();
+    }
+
+    // Takes over the other stats, e.g. from a backup, still counting for
+    // this device.
+    pub fn adopt(&mut self, other: Stats) {
+        *self = Stats {
+            device: std::mem::take(&mut self.device),
+            ..other
+        };
+    }
+
+    fn counters(&mut self) -> &mut Counters {
+        self.devices.entry(self.device.clone()).or_default()
+    }
+
+    fn total(&self) -> Counters {
+        self.devices.values().fold(self.earlier, |total, counters| {
+            total.zip(counters, |a, b| a + b)
+        })
+    }
+
+    pub fn record_new_game(&mut self) {
+        self.counters().games_played += 1;
+    }
+
+    pub fn record_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::GameWon => self.counters().games_won += 1,
+            GameEvent::ScoreChanged { score } => self.best_score = self.best_score.max(score),
+            GameEvent::TilesMerged { value, .. } => {
+                self.counters().tiles_merged += 1;
+                self.best_tile = self.best_tile.max(value);
+            }
+            GameEvent::TileSpawned { value, .. } => self.best_tile = self.best_tile.max(value),
+            GameEvent::GameOver => (),
+        }
+    }
+
+    // Only square boards have corners worth keeping the big tiles in.
+    pub fn record_final_board(&mut self, game_state: &GameState) {
+        if game_state.board() != Board::Square {
+            return;
+        }
+
+        let counters = self.counters();
+        for (count, cell) in counters.heatmap.iter_mut().zip(game_state.cells()) {
+            if cell.is_some_and(|tile| tile.is_number() && tile.get_value() >= HIGH_TILE) {
+                *count += 1;
+            }
+        }
+    }
+
+    pub fn games_played(&self) -> usize {
+        self.total().games_played
+    }
+
+    pub fn games_won(&self) -> usize {
+        self.total().games_won
+    }
+
+    pub fn best_score(&self) -> usize {
+        self.best_score
+    }
+
+    pub fn best_tile(&self) -> usize {
+        self.best_tile
+    }
+
+    pub fn heatmap(&self) -> [usize; 16] {
+        self.total().heatmap
+    }
+
+    pub fn tiles_merged(&self) -> usize {
+        self.total().tiles_merged
+    }
+
+    // Every counter is merged by the maximum, device by device, so syncing
+    // the same stats back and forth never inflates them while the games of
+    // different devices all count. The device stays this one.
+    pub fn merge(&self, other: &Stats) -> Stats {
+        let mut devices = self.devices.clone();
+        for (device, other) in &other.devices {
+            let counters = devices.entry(device.clone()).or_default();
+            *counters = counters.zip(other, usize::max);
+        }
+        Stats {
+            device: self.device.clone(),
+            earlier: self.earlier.zip(&other.earlier, usize::max),
+            devices,
+            best_score: self.best_score.max(other.best_score),
+            best_tile: self.best_tile.max(other.best_tile),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::stats::Stats;
+
+    #[test]
+    fn test_record_final_board() {
+        let mut stats = Stats::default();
+        stats.record_final_board(&GameState::from_values(&[
+            512, 256, 0, 0, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1024,
+        ]));
+        stats.record_final_board(&GameState::from_values(&[
+            2048, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]));
+
+        assert_eq!(
+            [2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+            stats.heatmap()
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        struct TestCase<'a> {
+            name: &'a str,
+            // Games played on each device, by device.
+            local: &'a [(&'a str, usize)],
+            remote: &'a [(&'a str, usize)],
+            want_games_played: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Merge: Different Devices",
+                local: &[("a", 10)],
+                remote: &[("b", 10)],
+                want_games_played: 20,
+            },
+            TestCase {
+                name: "Merge: Same Device Synced Back",
+                local: &[("a", 10)],
+                remote: &[("a", 7)],
+                want_games_played: 10,
+            },
+            TestCase {
+                name: "Merge: Both Devices On Both",
+                local: &[("a", 10), ("b", 3)],
+                remote: &[("a", 8), ("b", 5)],
+                want_games_played: 15,
+            },
+        ];
+
+        for t in tests {
+            let played = |devices: &[(&str, usize)]| {
+                let mut stats = Stats::default();
+                for &(device, games) in devices {
+                    stats.set_device(device);
+                    for _ in 0..games {
+                        stats.record_new_game();
+                    }
+                }
+                stats
+            };
+            let merged = played(t.local).merge(&played(t.remote));
+            assert_eq!(t.want_games_played, merged.games_played(), "{}", t.name);
+            assert_eq!(
+                merged,
+                merged.merge(&played(t.remote)),
+                "{}: Idempotent",
+                t.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_earlier_stats() {
+        // Stats saved before devices kept their own counters.
+        let mut stats: Stats = serde_json::from_str(
+            r#"{"games_played": 4, "games_won": 1, "best_score": 100, "best_tile": 64}"#,
+        )
+        .unwrap();
+        stats.set_device("a");
+        stats.record_new_game();
+        assert_eq!(5, stats.games_played(), "Earlier Stats: Added Up");
+
+        let other: Stats = serde_json::from_str(
+            r#"{"games_played": 6, "games_won": 1, "best_score": 100, "best_tile": 64}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            7,
+            stats.merge(&other).games_played(),
+            "Earlier Stats: Merged"
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use seed::{prelude::*, *};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+
+use crate::crypt::{Key, Vault};
+
+// Kept in the clear next to encrypted saves, see `Seal`.
+const VAULT_KEY: &str = "vault";
+// Set in session storage for as long as the tab plays incognito.
+const INCOGNITO_KEY: &str = "incognito";
+
+// Where saves go. Local storage can be full or blocked (e.g. in private
+// browsing), then saves fall back to session storage and, when that fails
+// too, the game carries on in memory only.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Local,
+    Session,
+    Memory,
+}
+
+impl Backend {
+    fn fallback(self) -> Backend {
+        match self {
+            Backend::Local => Backend::Session,
+            Backend::Session | Backend::Memory => Backend::Memory,
+        }
+    }
+
+    fn web_storage(self) -> Option<web_sys::Storage> {
+        match self {
+            Backend::Session => window().session_storage().ok().flatten(),
+            Backend::Local | Backend::Memory => window().local_storage().ok().flatten(),
+        }
+    }
+}
+
+// How saves are written: as plain JSON, encrypted with the key of the
+// player's passphrase, or not at all while encrypted saves are locked.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Seal {
+    #[default]
+    Plain,
+    Sealed(Key),
+    Locked,
+}
+
+impl Seal {
+    // What is stored for a value serialized as `json`.
+    pub fn encode(self, json: String) -> Option<String> {
+        match self {
+            Seal::Plain => Some(json),
+            Seal::Sealed(key) => Some(key.seal(&json)),
+            Seal::Locked => None,
+        }
+    }
+
+    // The JSON of a stored value.
+    pub fn decode(self, stored: String) -> Option<String> {
+        match self {
+            Seal::Plain => Some(stored),
+            Seal::Sealed(key) => key.open(&stored),
+            Seal::Locked => None,
+        }
+    }
+}
+
+// A write that failed, after which saves moved on to `backend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageError {
+    pub key: String,
+    pub message: String,
+    pub backend: Backend,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let consequence = match self.backend {
+            Backend::Local => "",
+            Backend::Session => "Progress is only kept until this tab is closed.",
+            Backend::Memory => "You can keep playing, but progress is lost on reload.",
+        };
+
+        write!(
+            f,
+            "Couldn't save {} ({}). {}",
+            self.key, self.message, consequence
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Storage {
+    // Prefixes the keys of a game that shares the page with others.
+    namespace: &'static str,
+    backend: Backend,
+    seal: Seal,
+    // Saves only last as long as the tab, the player's own are left alone.
+    incognito: bool,
+}
+
+impl Storage {
+    // Saves that are encrypted start out locked, until `unlock` is given the
+    // passphrase. Meanwhile nothing is read and nothing is written.
+    pub fn open(namespace: &'static str) -> (Storage, Option<Vault>) {
+        let mut storage = Storage {
+            namespace,
+            ..Storage::default()
+        };
+        let vault: Option<Vault> = LocalStorage::get(storage.key(VAULT_KEY)).ok();
+        storage.seal = if vault.is_some() {
+            Seal::Locked
+        } else {
+            Seal::Plain
+        };
+        // Reloading an incognito tab keeps it incognito.
+        if let Ok(true) = SessionStorage::get(storage.key(INCOGNITO_KEY)) {
+            storage.incognito = true;
+            storage.backend = Backend::Session;
+        }
+        (storage, vault)
+    }
+
+    pub fn namespace(&self) -> &'static str {
+        self.namespace
+    }
+
+    fn key(&self, key: &str) -> String {
+        if self.namespace.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}:{}", self.namespace, key)
+        }
+    }
+
+    // The key a stored one was given by `key`, if it belongs to this game.
+    fn own_key(&self, stored: &str) -> Option<String> {
+        if self.namespace.is_empty() {
+            return (!stored.contains(':')).then(|| stored.to_string());
+        }
+        stored
+            .strip_prefix(self.namespace)?
+            .strip_prefix(':')
+            .map(str::to_string)
+    }
+
+    pub fn is_incognito(&self) -> bool {
+        self.incognito
+    }
+
+    pub fn set_incognito(&mut self, incognito: bool) {
+        self.incognito = incognito;
+        let _ = if incognito {
+            SessionStorage::insert(self.key(INCOGNITO_KEY), &true)
+        } else {
+            SessionStorage::remove(self.key(INCOGNITO_KEY))
+        };
+        self.retry();
+    }
+
+    pub fn seal(&self) -> Seal {
+        self.seal
+    }
+
+    pub fn unlock(&mut self, key: Key) {
+        self.seal = Seal::Sealed(key);
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let stored = self
+            .backend
+            .web_storage()?
+            .get_item(&self.key(key))
+            .ok()??;
+        serde_json::from_str(&self.seal.decode(stored)?).ok()
+    }
+
+    // Falls back as far as needed for the write to succeed. The error tells
+    // about the fallback; the value may still have been saved.
+    pub fn insert<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), StorageError> {
+        let json = serde_json::to_string(value).map_err(|err| StorageError {
+            key: key.to_string(),
+            message: err.to_string(),
+            backend: self.backend,
+        })?;
+        let stored = match self.seal.encode(json) {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+        self.write(key, &stored)
+    }
+
+    fn write(&mut self, key: &str, stored: &str) -> Result<(), StorageError> {
+        let full_key = self.key(key);
+        let mut error = None;
+
+        loop {
+            let result = match self.backend {
+                Backend::Local | Backend::Session => match self.backend.web_storage() {
+                    Some(storage) => storage.set_item(&full_key, stored),
+                    None => Err(JsValue::from("Storage isn't available")),
+                },
+                Backend::Memory => Ok(()),
+            };
+
+            match (result, error) {
+                (Ok(()), None) => return Ok(()),
+                (Ok(()), Some(error)) => return Err(error),
+                (Err(err), _) => {
+                    self.backend = self.backend.fallback();
+                    error = Some(StorageError {
+                        key: key.to_string(),
+                        message: format!("{:?}", err),
+                        backend: self.backend,
+                    });
+                }
+            }
+        }
+    }
+
+    // Roughly how many bytes the saves of this game take, browsers count two
+    // per character.
+    pub fn usage(&self) -> f64 {
+        let storage = match self.backend.web_storage() {
+            Some(storage) => storage,
+            None => return 0.0,
+        };
+        let characters: usize = (0..storage.length().unwrap_or(0))
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter(|key| self.own_key(key).is_some())
+            .map(|key| {
+                let value = storage.get_item(&key).ok().flatten().unwrap_or_default();
+                key.encode_utf16().count() + value.encode_utf16().count()
+            })
+            .sum();
+        2.0 * characters as f64
+    }
+
+    pub fn remove(&self, key: &str) {
+        if self.seal == Seal::Locked || self.backend == Backend::Memory {
+            return;
+        }
+        // Failing to remove leaves the old value, which is harmless.
+        if let Some(storage) = self.backend.web_storage() {
+            let _ = storage.remove_item(&self.key(key));
+        }
+    }
+
+    // Rewrites every saved value under `seal`, keeping the vault of encrypted
+    // saves in step. Values that can't be read are left as they are.
+    pub fn reseal(&mut self, seal: Seal, vault: Option<&Vault>) -> Result<(), StorageError> {
+        if let Some(storage) = self.backend.web_storage() {
+            let keys: Vec<String> = (0..storage.length().unwrap_or(0))
+                .filter_map(|i| storage.key(i).ok().flatten())
+                .filter_map(|key| self.own_key(&key))
+                .filter(|key| key != VAULT_KEY && key != INCOGNITO_KEY)
+                .collect();
+            for key in keys {
+                let json = storage
+                    .get_item(&self.key(&key))
+                    .ok()
+                    .flatten()
+                    .and_then(|stored| self.seal.decode(stored));
+                if let Some(stored) = json.and_then(|json| seal.encode(json)) {
+                    self.write(&key, &stored)?;
+                }
+            }
+        }
+
+        self.seal = Seal::Plain;
+        match vault {
+            Some(vault) => self.insert(VAULT_KEY, vault)?,
+            None => self.remove(VAULT_KEY),
+        }
+        self.seal = seal;
+        Ok(())
+    }
+
+    // Tries local storage again with the next write, or session storage
+    // when incognito.
+    pub fn retry(&mut self) {
+        self.backend = if self.incognito {
+            Backend::Session
+        } else {
+            Backend::Local
+        };
+    }
+}
@@ -0,0 +1,110 @@
+use crate::game_state::GameState;
+use crate::skin::{self, BoardLayout, TILE_SIZE};
+
+// A standalone SVG image of the board, drawn like the PNG export. Only
+// depends on the game, so it works anywhere a board has to be pictured.
+pub fn board_svg(game_state: &GameState, exponents: bool) -> String {
+    let layout = BoardLayout::new(game_state.board());
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = layout.width,
+        h = layout.height
+    );
+    svg += &format!(
+        "  <rect width=\"{}\" height=\"{}\" rx=\"6\" fill=\"{}\"/>\n",
+        layout.width,
+        layout.height,
+        skin::BOARD_BACKGROUND
+    );
+
+    for (&(left, top), cell) in layout.cells.iter().zip(game_state.cells()) {
+        let background = match cell {
+            Some(tile) => skin::tile_colors(*tile).0,
+            None => skin::EMPTY_CELL,
+        };
+        svg += &format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" rx=\"3\" fill=\"{}\"/>\n",
+            left,
+            top,
+            background,
+            s = TILE_SIZE
+        );
+
+        let tile = match cell {
+            Some(tile) => *tile,
+            None => continue,
+        };
+        let label = skin::tile_label(tile, exponents);
+        if label.is_empty() {
+            continue;
+        }
+        svg += &format!(
+            "  <text x=\"{}\" y=\"{}\" font-family='{}' font-size=\"{}\" font-weight=\"bold\" \
+             fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+            left + TILE_SIZE / 2.0,
+            top + TILE_SIZE / 2.0,
+            skin::FONT,
+            skin::font_size(&label),
+            skin::tile_colors(tile).1,
+            label
+        );
+    }
+
+    svg + "</svg>\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::GameState;
+    use crate::svg::board_svg;
+
+    #[test]
+    fn test_board_svg() {
+        struct TestCase<'a> {
+            name: &'a str,
+            values: Vec<usize>,
+            exponents: bool,
+            want_texts: Vec<&'a str>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "SVG: Empty Board",
+                values: vec![],
+                exponents: false,
+                want_texts: vec![],
+            },
+            TestCase {
+                name: "SVG: Values",
+                values: vec![2, 0, 2048],
+                exponents: false,
+                want_texts: vec![">2<", ">2048<"],
+            },
+            TestCase {
+                name: "SVG: Exponents",
+                values: vec![2048],
+                exponents: true,
+                want_texts: vec![">11<"],
+            },
+        ];
+
+        for t in tests {
+            let got = board_svg(&GameState::from_values(&t.values), t.exponents);
+
+            assert!(got.starts_with("<svg "), "{}", t.name);
+            assert!(got.ends_with("</svg>\n"), "{}", t.name);
+            // The background and one rect per cell.
+            assert_eq!(17, got.matches("<rect ").count(), "{}", t.name);
+            assert_eq!(
+                t.want_texts.len(),
+                got.matches("<text ").count(),
+                "{}",
+                t.name
+            );
+            for text in t.want_texts {
+                assert!(got.contains(text), "{}: {}", t.name, text);
+            }
+        }
+    }
+}
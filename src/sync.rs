@@ -0,0 +1,177 @@
+use seed::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{GameState, Variant};
+use crate::settings::Settings;
+use crate::stats::Stats;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SyncConfig {
+    pub endpoint: String,
+    pub token: String,
+}
+
+impl SyncConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.endpoint.is_empty() && !self.token.is_empty()
+    }
+}
+
+pub enum SyncStatus {
+    Idle,
+    Syncing,
+    Synced,
+    Failed(String),
+}
+
+// Everything that is shared between devices, stamped with the time of the last local change.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub updated_at: f64,
+    #[serde(default)]
+    pub variant: Variant,
+    pub game_state: GameState,
+    pub stats: Stats,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+// The most recently changed game and settings win; stats are always merged.
+pub fn resolve(local: Snapshot, remote: Option<Snapshot>) -> Snapshot {
+    match remote {
+        None => local,
+        Some(remote) => {
+            let stats = local.stats.merge(&remote.stats);
+            let newer = if remote.updated_at > local.updated_at {
+                remote
+            } else {
+                local
+            };
+
+            Snapshot { stats, ..newer }
+        }
+    }
+}
+
+async fn pull(config: &SyncConfig) -> fetch::Result<Option<Snapshot>> {
+    let response = Request::new(config.endpoint.as_str())
+        .header(Header::bearer(config.token.as_str()))
+        .fetch()
+        .await?;
+
+    // Nothing has been pushed to this endpoint yet.
+    if response.status().code == 404 {
+        return Ok(None);
+    }
+
+    response.check_status()?.json().await.map(Some)
+}
+
+async fn push(config: &SyncConfig, snapshot: &Snapshot) -> fetch::Result<()> {
+    Request::new(config.endpoint.as_str())
+        .method(Method::Put)
+        .header(Header::bearer(config.token.as_str()))
+        .json(snapshot)?
+        .fetch()
+        .await?
+        .check_status()?;
+
+    Ok(())
+}
+
+// Pulls the remote snapshot, resolves it against the local one and pushes the result back.
+pub async fn sync(config: SyncConfig, local: Snapshot) -> fetch::Result<Snapshot> {
+    let remote = pull(&config).await?;
+    let resolved = resolve(local, remote);
+    push(&config, &resolved).await?;
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{GameState, Variant};
+    use crate::settings::Settings;
+    use crate::stats::Stats;
+    use crate::sync::{resolve, Snapshot};
+
+    fn snapshot(updated_at: f64, games_played: usize) -> Snapshot {
+        let mut stats = Stats::default();
+        for _ in 0..games_played {
+            stats.record_new_game();
+        }
+
+        Snapshot {
+            updated_at,
+            variant: Variant::Classic,
+            game_state: GameState::default(),
+            stats,
+            settings: Settings::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        struct TestCase<'a> {
+            name: &'a str,
+            local: Snapshot,
+            remote: Option<Snapshot>,
+            want_remote_game: bool,
+            want_updated_at: f64,
+            want_games_played: usize,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Resolve: No Remote",
+                local: snapshot(1.0, 2),
+                remote: None,
+                want_remote_game: false,
+                want_updated_at: 1.0,
+                want_games_played: 2,
+            },
+            TestCase {
+                name: "Resolve: Remote Newer",
+                local: snapshot(1.0, 2),
+                remote: Some(snapshot(2.0, 1)),
+                want_remote_game: true,
+                want_updated_at: 2.0,
+                want_games_played: 2,
+            },
+            TestCase {
+                name: "Resolve: Local Newer",
+                local: snapshot(3.0, 1),
+                remote: Some(snapshot(2.0, 5)),
+                want_remote_game: false,
+                want_updated_at: 3.0,
+                want_games_played: 5,
+            },
+            TestCase {
+                name: "Resolve: Tie Keeps Local",
+                local: snapshot(2.0, 0),
+                remote: Some(snapshot(2.0, 0)),
+                want_remote_game: false,
+                want_updated_at: 2.0,
+                want_games_played: 0,
+            },
+        ];
+
+        for t in tests {
+            let want_game = match &t.remote {
+                Some(remote) if t.want_remote_game => remote.game_state.clone(),
+                _ => t.local.game_state.clone(),
+            };
+
+            let got = resolve(t.local, t.remote);
+
+            let mut want_stats = Stats::default();
+            for _ in 0..t.want_games_played {
+                want_stats.record_new_game();
+            }
+
+            assert_eq!(t.want_updated_at, got.updated_at, "{}", t.name);
+            assert_eq!(want_stats, got.stats, "{}", t.name);
+            assert_eq!(want_game, got.game_state, "{}", t.name);
+        }
+    }
+}
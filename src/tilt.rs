@@ -0,0 +1,130 @@
+use seed::{prelude::*, *};
+
+use crate::game_state::Direction;
+use crate::topology::Board;
+
+// How a device is held, in degrees as reported by `deviceorientation`: `beta`
+// tips its top edge towards or away from the player, `gamma` rolls it sideways.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tilt {
+    pub beta: f64,
+    pub gamma: f64,
+}
+
+impl Tilt {
+    // Where the tiles roll once the device is tipped past `threshold` degrees
+    // away from how it was held at `rest`.
+    pub fn direction(self, rest: Tilt, threshold: f64, board: Board) -> Option<Direction> {
+        let dx = self.gamma - rest.gamma;
+        // Tipping the top edge away lowers it, so the tiles roll up.
+        let dy = self.beta - rest.beta;
+        if dx.abs().max(dy.abs()) < threshold {
+            return None;
+        }
+
+        board.swipe_direction(dx, dy)
+    }
+}
+
+// iOS only sends orientation events to pages the player allowed them for, and
+// only asks from a tap. Other browsers send them right away.
+pub async fn request_permission() -> Result<(), String> {
+    let class = js_sys::Reflect::get(&window(), &"DeviceOrientationEvent".into())
+        .map_err(|err| format!("{:?}", err))?;
+    if class.is_undefined() {
+        return Err("This device doesn't report its tilt.".to_string());
+    }
+
+    let request = match js_sys::Reflect::get(&class, &"requestPermission".into()) {
+        Ok(request) if request.is_function() => request.unchecked_into::<js_sys::Function>(),
+        _ => return Ok(()),
+    };
+    let promise = request
+        .call0(&class)
+        .map_err(|_| "Tap the setting again to allow motion access.".to_string())?;
+    let state = JsFuture::from(js_sys::Promise::from(promise))
+        .await
+        .map_err(|_| "Tap the setting again to allow motion access.".to_string())?;
+
+    match state.as_string().as_deref() {
+        Some("granted") => Ok(()),
+        _ => Err("Motion access was denied.".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Direction;
+    use crate::tilt::Tilt;
+    use crate::topology::Board;
+
+    #[test]
+    fn test_direction() {
+        struct TestCase<'a> {
+            name: &'a str,
+            tilt: Tilt,
+            board: Board,
+            want: Option<Direction>,
+        }
+
+        // Held upright in front of the player.
+        let rest = Tilt {
+            beta: 60.0,
+            gamma: 0.0,
+        };
+
+        let tests = [
+            TestCase {
+                name: "Square: Below Threshold",
+                tilt: Tilt {
+                    beta: 70.0,
+                    gamma: -15.0,
+                },
+                board: Board::Square,
+                want: None,
+            },
+            TestCase {
+                name: "Square: Top Away",
+                tilt: Tilt {
+                    beta: 30.0,
+                    gamma: 5.0,
+                },
+                board: Board::Square,
+                want: Some(Direction::Up),
+            },
+            TestCase {
+                name: "Square: Top Closer",
+                tilt: Tilt {
+                    beta: 85.0,
+                    gamma: 0.0,
+                },
+                board: Board::Square,
+                want: Some(Direction::Down),
+            },
+            TestCase {
+                name: "Square: Rolled Left",
+                tilt: Tilt {
+                    beta: 65.0,
+                    gamma: -30.0,
+                },
+                board: Board::Square,
+                want: Some(Direction::Left),
+            },
+            TestCase {
+                name: "Hex: Rolled Right And Top Away",
+                tilt: Tilt {
+                    beta: 35.0,
+                    gamma: 20.0,
+                },
+                board: Board::Hex,
+                want: Some(Direction::UpRight),
+            },
+        ];
+
+        for t in tests {
+            let got = t.tilt.direction(rest, 20.0, t.board);
+
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+// More at once would cover the board on small screens, the oldest make way.
+const MAX_SHOWN: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn class(self) -> &'static str {
+        match self {
+            Severity::Info => "toast-info",
+            Severity::Success => "toast-success",
+            Severity::Warning => "toast-warning",
+            Severity::Error => "toast-error",
+        }
+    }
+
+    // How long a toast stays, in milliseconds. Problems take longer to read
+    // up on than confirmations.
+    fn duration(self) -> u32 {
+        match self {
+            Severity::Info | Severity::Success => 2500,
+            Severity::Warning => 5000,
+            Severity::Error => 8000,
+        }
+    }
+}
+
+// A short note shown over the page, e.g. after copying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub id: u32,
+    pub message: String,
+    pub severity: Severity,
+    // Milliseconds until it is dismissed by itself.
+    pub duration: u32,
+}
+
+// The toasts on screen, oldest first. Any part of the game can add one.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    shown: VecDeque<Toast>,
+    next_id: u32,
+}
+
+impl Toasts {
+    pub fn push(&mut self, message: String, severity: Severity) -> &Toast {
+        if self.shown.len() == MAX_SHOWN {
+            self.shown.pop_front();
+        }
+        self.next_id += 1;
+        self.shown.push_back(Toast {
+            id: self.next_id,
+            message,
+            severity,
+            duration: severity.duration(),
+        });
+        &self.shown[self.shown.len() - 1]
+    }
+
+    // Toasts already gone, e.g. closed before their time ran out, are ignored.
+    pub fn dismiss(&mut self, id: u32) {
+        self.shown.retain(|toast| toast.id != id);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.shown.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::toast::{Severity, Toasts, MAX_SHOWN};
+
+    #[test]
+    fn test_toasts() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push("Copied.".to_string(), Severity::Success).id;
+        let error = toasts.push("Couldn't save.".to_string(), Severity::Error);
+        assert!(error.duration > 2500, "Toast: Errors Stay Longer");
+        let error = error.id;
+
+        toasts.dismiss(first);
+        toasts.dismiss(first);
+        let ids: Vec<u32> = toasts.iter().map(|toast| toast.id).collect();
+        assert_eq!(vec![error], ids, "Toast: Dismissed");
+
+        for _ in 0..MAX_SHOWN {
+            toasts.push("Copied.".to_string(), Severity::Info);
+        }
+        assert_eq!(MAX_SHOWN, toasts.iter().count(), "Toast: Capped");
+        assert!(
+            toasts.iter().all(|toast| toast.id != error),
+            "Toast: Oldest Make Way"
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::Direction;
+
+// Describes the shape of a board: how many cells it has, how they line up
+// for each direction and where each cell is drawn.
+pub trait Topology {
+    fn cell_count(&self) -> usize;
+
+    // Cells along each side.
+    fn size(&self) -> usize;
+
+    // Every line starts at the edge the tiles slide towards. Directions the
+    // board doesn't support have no lines.
+    fn lines(&self, direction: Direction) -> Vec<Vec<usize>>;
+
+    // Column and row of a cell in units of one tile.
+    fn position(&self, index: usize) -> (f64, f64);
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Board {
+    #[default]
+    Square,
+    Hex,
+    Cube,
+}
+
+impl Board {
+    pub fn topology(self) -> &'static dyn Topology {
+        match self {
+            Board::Square => &Square,
+            Board::Hex => &Hex,
+            Board::Cube => &Cube,
+        }
+    }
+
+    // The lines of the topology, worked out once per board since every move
+    // walks them.
+    pub fn lines(self, direction: Direction) -> &'static [Vec<usize>] {
+        static LINES: [OnceLock<Vec<Vec<Vec<usize>>>>; 3] =
+            [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        let lines = LINES[self as usize].get_or_init(|| {
+            Direction::ALL
+                .iter()
+                .map(|&direction| self.topology().lines(direction))
+                .collect()
+        });
+        &lines[direction as usize]
+    }
+
+    // The direction of a drag by (dx, dy) on screen, y pointing down. Hex
+    // boards take the nearest of their six sides, the layers of the cube are
+    // only reached with keys.
+    pub fn swipe_direction(self, dx: f64, dy: f64) -> Option<Direction> {
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+
+        match self {
+            Board::Square | Board::Cube if dx.abs() >= dy.abs() => Some(if dx < 0.0 {
+                Direction::Left
+            } else {
+                Direction::Right
+            }),
+            Board::Square | Board::Cube => Some(if dy < 0.0 {
+                Direction::Up
+            } else {
+                Direction::Down
+            }),
+            Board::Hex => {
+                // Counterclockwise from the right, sides are 60 degrees apart.
+                let angle = (-dy).atan2(dx).to_degrees();
+                let side = ((angle + 390.0) % 360.0 / 60.0) as usize;
+                Some(
+                    [
+                        Direction::Right,
+                        Direction::UpRight,
+                        Direction::UpLeft,
+                        Direction::Left,
+                        Direction::DownLeft,
+                        Direction::DownRight,
+                    ][side],
+                )
+            }
+        }
+    }
+}
+
+// The classic 4x4 grid, walked with the linear `increment()` scheme.
+pub struct Square;
+
+impl Topology for Square {
+    fn cell_count(&self) -> usize {
+        16
+    }
+
+    fn size(&self) -> usize {
+        4
+    }
+
+    fn lines(&self, direction: Direction) -> Vec<Vec<usize>> {
+        let (mut index, step, next_line) = match direction.increment() {
+            Some(increment) => increment,
+            None => return Vec::new(),
+        };
+
+        let mut lines = Vec::new();
+        for _ in 0..4 {
+            let mut line = Vec::new();
+            for _ in 0..4 {
+                line.push(index as usize);
+                index += step;
+            }
+            lines.push(line);
+
+            index = (index + next_line + 16) % 16;
+        }
+        lines
+    }
+
+    fn position(&self, index: usize) -> (f64, f64) {
+        ((index % 4) as f64, (index / 4) as f64)
+    }
+}
+
+// A hexagon of pointy-topped cells with sides of three cells (19 cells),
+// addressed with axial coordinates and stored row by row.
+pub struct Hex;
+
+impl Hex {
+    const RADIUS: i32 = 2;
+
+    fn cells() -> impl Iterator<Item = (i32, i32)> {
+        (-Hex::RADIUS..=Hex::RADIUS).flat_map(|r| {
+            (-Hex::RADIUS..=Hex::RADIUS)
+                .filter(move |q| (q + r).abs() <= Hex::RADIUS)
+                .map(move |q| (q, r))
+        })
+    }
+
+    fn index(cell: (i32, i32)) -> Option<usize> {
+        Hex::cells().position(|c| c == cell)
+    }
+
+    fn offset(direction: Direction) -> Option<(i32, i32)> {
+        match direction {
+            Direction::Left => Some((-1, 0)),
+            Direction::Right => Some((1, 0)),
+            Direction::UpLeft => Some((0, -1)),
+            Direction::UpRight => Some((1, -1)),
+            Direction::DownLeft => Some((-1, 1)),
+            Direction::DownRight => Some((0, 1)),
+            _ => None,
+        }
+    }
+}
+
+impl Topology for Hex {
+    fn cell_count(&self) -> usize {
+        Hex::cells().count()
+    }
+
+    fn size(&self) -> usize {
+        Hex::RADIUS as usize + 1
+    }
+
+    fn lines(&self, direction: Direction) -> Vec<Vec<usize>> {
+        let (dq, dr) = match Hex::offset(direction) {
+            Some(offset) => offset,
+            None => return Vec::new(),
+        };
+
+        // Start from every cell on the far edge and walk back against the direction.
+        Hex::cells()
+            .filter(|&(q, r)| Hex::index((q + dq, r + dr)).is_none())
+            .map(|(q, r)| {
+                (0..)
+                    .map(|step| Hex::index((q - dq * step, r - dr * step)))
+                    .take_while(Option::is_some)
+                    .flatten()
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn position(&self, index: usize) -> (f64, f64) {
+        let (q, r) = Hex::cells().nth(index).unwrap_or_default();
+
+        (
+            f64::from(q + Hex::RADIUS) + f64::from(r) / 2.0,
+            f64::from(r + Hex::RADIUS),
+        )
+    }
+}
+
+// Four 4x4 layers stacked on top of each other (64 cells), stored layer by layer.
+// Layers are drawn next to each other in a 2x2 block.
+pub struct Cube;
+
+impl Cube {
+    const LAYERS: usize = 4;
+}
+
+impl Topology for Cube {
+    fn cell_count(&self) -> usize {
+        Square.cell_count() * Cube::LAYERS
+    }
+
+    fn size(&self) -> usize {
+        Square.size()
+    }
+
+    fn lines(&self, direction: Direction) -> Vec<Vec<usize>> {
+        let layer_size = Square.cell_count();
+        let layers: Vec<usize> = match direction {
+            Direction::Above => (0..Cube::LAYERS).collect(),
+            Direction::Below => (0..Cube::LAYERS).rev().collect(),
+            // Every other direction moves within each layer on its own.
+            _ => {
+                return (0..Cube::LAYERS)
+                    .flat_map(|layer| {
+                        Square.lines(direction).into_iter().map(move |line| {
+                            line.into_iter().map(|i| i + layer * layer_size).collect()
+                        })
+                    })
+                    .collect();
+            }
+        };
+
+        (0..layer_size)
+            .map(|i| layers.iter().map(|layer| i + layer * layer_size).collect())
+            .collect()
+    }
+
+    fn position(&self, index: usize) -> (f64, f64) {
+        let layer_size = Square.cell_count();
+        let layer = index / layer_size;
+        let (x, y) = Square.position(index % layer_size);
+
+        // Half a tile of space between the layers.
+        (x + (layer % 2) as f64 * 4.5, y + (layer / 2) as f64 * 4.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Direction;
+    use crate::topology::{Board, Cube, Hex, Square, Topology};
+
+    #[test]
+    fn test_lines() {
+        struct TestCase<'a> {
+            name: &'a str,
+            topology: &'a dyn Topology,
+            direction: Direction,
+            want: Vec<Vec<usize>>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Square: Left",
+                topology: &Square,
+                direction: Direction::Left,
+                want: vec![
+                    vec![0, 1, 2, 3],
+                    vec![4, 5, 6, 7],
+                    vec![8, 9, 10, 11],
+                    vec![12, 13, 14, 15],
+                ],
+            },
+            TestCase {
+                name: "Square: Down",
+                topology: &Square,
+                direction: Direction::Down,
+                want: vec![
+                    vec![15, 11, 7, 3],
+                    vec![14, 10, 6, 2],
+                    vec![13, 9, 5, 1],
+                    vec![12, 8, 4, 0],
+                ],
+            },
+            TestCase {
+                name: "Square: Diagonal",
+                topology: &Square,
+                direction: Direction::UpLeft,
+                want: vec![],
+            },
+            TestCase {
+                name: "Hex: Right",
+                topology: &Hex,
+                direction: Direction::Right,
+                want: vec![
+                    vec![2, 1, 0],
+                    vec![6, 5, 4, 3],
+                    vec![11, 10, 9, 8, 7],
+                    vec![15, 14, 13, 12],
+                    vec![18, 17, 16],
+                ],
+            },
+            TestCase {
+                name: "Hex: Up Left",
+                topology: &Hex,
+                direction: Direction::UpLeft,
+                want: vec![
+                    vec![0, 4, 9, 14, 18],
+                    vec![1, 5, 10, 15],
+                    vec![2, 6, 11],
+                    vec![3, 8, 13, 17],
+                    vec![7, 12, 16],
+                ],
+            },
+            TestCase {
+                name: "Hex: Down Left",
+                topology: &Hex,
+                direction: Direction::DownLeft,
+                want: vec![
+                    vec![7, 3, 0],
+                    vec![12, 8, 4, 1],
+                    vec![16, 13, 9, 5, 2],
+                    vec![17, 14, 10, 6],
+                    vec![18, 15, 11],
+                ],
+            },
+            TestCase {
+                name: "Cube: Up",
+                topology: &Cube,
+                direction: Direction::Up,
+                want: (0..16)
+                    .map(|line| {
+                        let start = line / 4 * 16 + line % 4;
+                        vec![start, start + 4, start + 8, start + 12]
+                    })
+                    .collect(),
+            },
+            TestCase {
+                name: "Cube: Below",
+                topology: &Cube,
+                direction: Direction::Below,
+                want: (0..16).map(|i| vec![i + 48, i + 32, i + 16, i]).collect(),
+            },
+            TestCase {
+                name: "Cube: Diagonal",
+                topology: &Cube,
+                direction: Direction::DownRight,
+                want: vec![],
+            },
+        ];
+
+        for t in tests {
+            let mut got = t.topology.lines(t.direction);
+            let mut want = t.want;
+            got.sort();
+            want.sort();
+
+            assert_eq!(want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_swipe_direction() {
+        struct TestCase<'a> {
+            name: &'a str,
+            board: Board,
+            dx: f64,
+            dy: f64,
+            want: Option<Direction>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Square: Mostly Left",
+                board: Board::Square,
+                dx: -40.0,
+                dy: 30.0,
+                want: Some(Direction::Left),
+            },
+            TestCase {
+                name: "Square: Mostly Up",
+                board: Board::Square,
+                dx: 10.0,
+                dy: -40.0,
+                want: Some(Direction::Up),
+            },
+            TestCase {
+                name: "Square: No Drag",
+                board: Board::Square,
+                dx: 0.0,
+                dy: 0.0,
+                want: None,
+            },
+            TestCase {
+                name: "Cube: Down",
+                board: Board::Cube,
+                dx: 0.0,
+                dy: 50.0,
+                want: Some(Direction::Down),
+            },
+            TestCase {
+                name: "Hex: Right",
+                board: Board::Hex,
+                dx: 50.0,
+                dy: 10.0,
+                want: Some(Direction::Right),
+            },
+            TestCase {
+                name: "Hex: Straight Up Leans Left",
+                board: Board::Hex,
+                dx: -10.0,
+                dy: -50.0,
+                want: Some(Direction::UpLeft),
+            },
+            TestCase {
+                name: "Hex: Down Right",
+                board: Board::Hex,
+                dx: 30.0,
+                dy: 50.0,
+                want: Some(Direction::DownRight),
+            },
+            TestCase {
+                name: "Hex: Left",
+                board: Board::Hex,
+                dx: -50.0,
+                dy: 0.0,
+                want: Some(Direction::Left),
+            },
+        ];
+
+        for t in tests {
+            let got = t.board.swipe_direction(t.dx, t.dy);
+
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+}
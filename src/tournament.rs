@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::Variant;
+use crate::mode::Mode;
+
+// More would take the evening, and the bracket wouldn't fit the page.
+pub const MAX_PLAYERS: usize = 8;
+
+// Two players on the same board, or one alone who goes through.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Match {
+    // Indices into the players, the second is missing for a bye.
+    pub players: (usize, Option<usize>),
+    pub scores: (Option<usize>, Option<usize>),
+    pub seed: u64,
+}
+
+impl Match {
+    fn is_played(&self) -> bool {
+        self.scores.0.is_some() && (self.players.1.is_none() || self.scores.1.is_some())
+    }
+
+    // Ties go to the player listed first.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_played() {
+            return None;
+        }
+        match (self.players.1, self.scores) {
+            (Some(second), (Some(first_score), Some(second_score)))
+                if second_score > first_score =>
+            {
+                Some(second)
+            }
+            _ => Some(self.players.0),
+        }
+    }
+}
+
+// Whose turn it is, on which board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn<'a> {
+    pub player: &'a str,
+    pub seed: u64,
+}
+
+// A knockout tournament played in turns on one device. Both players of a
+// match play the same seed with the same budget of moves or time, the
+// higher score goes through to the next round.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Tournament {
+    players: Vec<String>,
+    variant: Variant,
+    // Time attack or the move limit, setting the budget of every game.
+    mode: Mode,
+    seed: u64,
+    rounds: Vec<Vec<Match>>,
+}
+
+impl Tournament {
+    pub fn new(names: &str, variant: Variant, mode: Mode, seed: u64) -> Result<Tournament, String> {
+        let players: Vec<String> = names
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        if players.len() < 2 {
+            return Err("A tournament needs at least two players.".to_string());
+        }
+        if players.len() > MAX_PLAYERS {
+            return Err(format!(
+                "A tournament takes at most {} players.",
+                MAX_PLAYERS
+            ));
+        }
+
+        let mut tournament = Tournament {
+            variant,
+            mode,
+            seed,
+            rounds: Vec::new(),
+            players: Vec::new(),
+        };
+        tournament.add_round((0..players.len()).collect());
+        tournament.players = players;
+        Ok(tournament)
+    }
+
+    // Pairs the players in order, the last one alone gets a bye.
+    fn add_round(&mut self, players: Vec<usize>) {
+        let round = self.rounds.len() as u64;
+        let matches = players
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| Match {
+                players: (pair[0], pair.get(1).copied()),
+                scores: (None, None),
+                // Every match has a board of its own.
+                seed: self.seed.wrapping_add(
+                    (round * MAX_PLAYERS as u64 + i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15),
+                ),
+            })
+            .collect();
+        self.rounds.push(matches);
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn player(&self, index: usize) -> &str {
+        &self.players[index]
+    }
+
+    pub fn rounds(&self) -> &[Vec<Match>] {
+        &self.rounds
+    }
+
+    // The first game of the bracket still to be played.
+    pub fn turn(&self) -> Option<Turn<'_>> {
+        let round = self.rounds.last()?;
+        round.iter().find_map(|m| {
+            let player = match (m.scores, m.players.1) {
+                ((None, _), _) => m.players.0,
+                ((Some(_), None), Some(second)) => second,
+                _ => return None,
+            };
+            Some(Turn {
+                player: &self.players[player],
+                seed: m.seed,
+            })
+        })
+    }
+
+    // Records the score of whoever's turn it was, and starts the next round
+    // once every match of this one is played.
+    pub fn record(&mut self, score: usize) {
+        let round = match self.rounds.last_mut() {
+            Some(round) => round,
+            None => return,
+        };
+        if let Some(m) = round.iter_mut().find(|m| !m.is_played()) {
+            if m.scores.0.is_none() {
+                m.scores.0 = Some(score);
+            } else {
+                m.scores.1 = Some(score);
+            }
+        }
+        // Byes need no game, they go through as soon as they come up.
+        for m in round.iter_mut() {
+            if m.players.1.is_none() {
+                m.scores.0.get_or_insert(0);
+            }
+        }
+
+        let winners: Option<Vec<usize>> = round.iter().map(Match::winner).collect();
+        if let Some(winners) = winners.filter(|winners| winners.len() > 1) {
+            self.add_round(winners);
+        }
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        match self.rounds.last()?.as_slice() {
+            [last] if last.players.1.is_some() => last.winner().map(|i| self.player(i)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Variant;
+    use crate::mode::Mode;
+    use crate::tournament::Tournament;
+
+    #[test]
+    fn test_new() {
+        struct TestCase<'a> {
+            name: &'a str,
+            names: &'a str,
+            want: Result<usize, ()>,
+        }
+
+        let tests = [
+            TestCase {
+                name: "Tournament: Alone",
+                names: "Ann\n\n  ",
+                want: Err(()),
+            },
+            TestCase {
+                name: "Tournament: Blank Lines Skipped",
+                names: "Ann\n\n Bo \nCy",
+                want: Ok(2),
+            },
+            TestCase {
+                name: "Tournament: Too Many",
+                names: "A\nB\nC\nD\nE\nF\nG\nH\nI",
+                want: Err(()),
+            },
+        ];
+
+        for t in tests {
+            let got = Tournament::new(t.names, Variant::Classic, Mode::MoveLimit, 1)
+                .map(|tournament| tournament.rounds()[0].len())
+                .map_err(|_| ());
+            assert_eq!(t.want, got, "{}", t.name);
+        }
+    }
+
+    #[test]
+    fn test_bracket() {
+        let mut tournament =
+            Tournament::new("Ann\nBo\nCy", Variant::Classic, Mode::TimeAttack, 1).unwrap();
+        let first = tournament.turn().unwrap();
+        assert_eq!("Ann", first.player, "Turn: First");
+        let seed = first.seed;
+
+        tournament.record(100);
+        let second = tournament.turn().unwrap();
+        assert_eq!(
+            ("Bo", seed),
+            (second.player, second.seed),
+            "Turn: Same Board"
+        );
+
+        // Bo wins the match, Cy has a bye.
+        tournament.record(300);
+        assert_eq!(2, tournament.rounds().len(), "Next Round");
+        assert_eq!("Bo", tournament.turn().unwrap().player, "Turn: Final");
+        assert_ne!(seed, tournament.turn().unwrap().seed, "Final: Own Board");
+        assert_eq!(None, tournament.winner(), "No Winner Yet");
+
+        tournament.record(200);
+        assert_eq!(
+            "Cy",
+            tournament.turn().unwrap().player,
+            "Turn: Bye Plays Final"
+        );
+        tournament.record(200);
+        assert_eq!(None, tournament.turn(), "Over");
+        assert_eq!(Some("Bo"), tournament.winner(), "Tie Goes To The First");
+    }
+}
@@ -0,0 +1,150 @@
+use serde::Serialize;
+
+// What an entry's value says about the position, since alpha-beta cuts a
+// search off as soon as its value can't matter anymore.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // The search was cut off, the value is at least this.
+    Lower,
+    // Every move fell short, the value is at most this.
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Entry {
+    hash: u64,
+    plies: usize,
+    value: f64,
+    bound: Bound,
+}
+
+// How often a search found what it looked up, for tuning the table's size.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+}
+
+// Values of positions already searched, by `GameState::board_hash`, so a
+// board reached by moves in another order isn't searched again. A table of
+// fixed size, each hash has a single slot and the newest entry takes it.
+// Values only hold for the weights they were searched with.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    stats: TableStats,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> TranspositionTable {
+        TranspositionTable::new(1 << 16)
+    }
+}
+
+impl TranspositionTable {
+    // Room for `capacity` entries, rounded up to a power of two.
+    pub fn new(capacity: usize) -> TranspositionTable {
+        TranspositionTable {
+            entries: vec![None; capacity.max(1).next_power_of_two()],
+            stats: TableStats::default(),
+        }
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        hash as usize & (self.entries.len() - 1)
+    }
+
+    // The value of the position if it was searched at least `plies` deep
+    // and the value decides the search between `alpha` and `beta`.
+    pub fn probe(&mut self, hash: u64, plies: usize, alpha: f64, beta: f64) -> Option<f64> {
+        self.stats.probes += 1;
+        let entry = self.entries[self.slot(hash)].filter(|entry| entry.hash == hash)?;
+        if entry.plies < plies {
+            return None;
+        }
+        let found = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => entry.value >= beta,
+            Bound::Upper => entry.value <= alpha,
+        };
+        if found {
+            self.stats.hits += 1;
+        }
+        found.then_some(entry.value)
+    }
+
+    pub fn store(&mut self, hash: u64, plies: usize, value: f64, bound: Bound) {
+        let slot = self.slot(hash);
+        self.entries[slot] = Some(Entry {
+            hash,
+            plies,
+            value,
+            bound,
+        });
+    }
+
+    pub fn stats(&self) -> TableStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transposition::{Bound, TableStats, TranspositionTable};
+
+    #[test]
+    fn test_probe() {
+        struct TestCase<'a> {
+            name: &'a str,
+            bound: Bound,
+            plies: usize,
+            want: Option<f64>,
+        }
+        let tests = [
+            TestCase {
+                name: "Probe: Exact",
+                bound: Bound::Exact,
+                plies: 2,
+                want: Some(5.0),
+            },
+            TestCase {
+                name: "Probe: Too Shallow",
+                bound: Bound::Exact,
+                plies: 1,
+                want: None,
+            },
+            TestCase {
+                name: "Probe: Lower Bound Cut Off",
+                bound: Bound::Lower,
+                plies: 3,
+                want: Some(5.0),
+            },
+            TestCase {
+                name: "Probe: Upper Bound Inside The Window",
+                bound: Bound::Upper,
+                plies: 3,
+                want: None,
+            },
+        ];
+
+        for t in tests {
+            let mut table = TranspositionTable::new(4);
+            table.store(7, t.plies, 5.0, t.bound);
+            assert_eq!(t.want, table.probe(7, 2, 1.0, 4.0), "{}", t.name);
+            // Same slot, another board.
+            assert_eq!(None, table.probe(3, 0, 1.0, 4.0), "{}: Collision", t.name);
+        }
+
+        let mut table = TranspositionTable::new(4);
+        table.store(7, 2, 5.0, Bound::Exact);
+        table.probe(7, 2, 1.0, 4.0);
+        table.probe(6, 2, 1.0, 4.0);
+        assert_eq!(
+            TableStats { probes: 2, hits: 1 },
+            table.stats(),
+            "Probe: Stats"
+        );
+    }
+}
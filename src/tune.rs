@@ -0,0 +1,165 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::evaluate::Weights;
+use crate::rng::GameRng;
+use crate::simulate::{self, Options};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Search {
+    // The starting weights times every combination of `factors`.
+    Grid,
+    // Mutations of the best weights so far, `generations` generations of
+    // `population` candidates.
+    Evolve,
+}
+
+// How to tune the weights, read from JSON with every field optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TuneOptions {
+    // How every candidate is played, its weights left out. All candidates
+    // play the same seeds, so luck doesn't pick the winner.
+    pub simulation: Options,
+    pub search: Search,
+    pub start: Weights,
+    pub factors: Vec<f64>,
+    pub generations: usize,
+    pub population: usize,
+    // Where the mutations are drawn from.
+    pub seed: u64,
+}
+
+impl Default for TuneOptions {
+    fn default() -> TuneOptions {
+        TuneOptions {
+            simulation: Options {
+                games: 20,
+                ..Options::default()
+            },
+            search: Search::Grid,
+            start: Weights::default(),
+            factors: vec![0.5, 1.0, 2.0],
+            generations: 20,
+            population: 8,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TuneReport {
+    // Ready to be the game's `aiWeights`.
+    pub weights: Weights,
+    pub mean_score: f64,
+    // What the starting weights scored, to see what tuning won.
+    pub start_mean_score: f64,
+    pub candidates: usize,
+}
+
+const FIELDS: usize = 5;
+
+// The weights, one after the other, to be changed alike.
+fn fields(weights: &mut Weights) -> [&mut f64; FIELDS] {
+    [
+        &mut weights.empty,
+        &mut weights.monotonicity,
+        &mut weights.smoothness,
+        &mut weights.corner_max,
+        &mut weights.merges,
+    ]
+}
+
+pub fn tune(options: &TuneOptions) -> Result<TuneReport, String> {
+    let mut candidates = 0;
+    let mut mean_score = |weights: Weights| -> Result<f64, String> {
+        candidates += 1;
+        let simulation = Options {
+            weights,
+            ..options.simulation.clone()
+        };
+        Ok(simulate::run(&simulation)?.scores.mean)
+    };
+
+    let start_mean_score = mean_score(options.start)?;
+    let mut best = (options.start, start_mean_score);
+    match options.search {
+        Search::Grid => {
+            let combinations = options.factors.len().pow(FIELDS as u32);
+            for mut combination in 0..combinations {
+                let mut weights = options.start;
+                for field in fields(&mut weights).iter_mut() {
+                    **field *= options.factors[combination % options.factors.len()];
+                    combination /= options.factors.len();
+                }
+                let score = mean_score(weights)?;
+                if score > best.1 {
+                    best = (weights, score);
+                }
+            }
+        }
+        Search::Evolve => {
+            let mut rng = GameRng::new(options.seed);
+            for _ in 0..options.generations {
+                let parent = best.0;
+                for _ in 0..options.population {
+                    // Each weight from half to twice what it was.
+                    let mut weights = parent;
+                    for field in fields(&mut weights).iter_mut() {
+                        **field *= 2f64.powf(rng.gen_range(-1.0..=1.0));
+                    }
+                    let score = mean_score(weights)?;
+                    if score > best.1 {
+                        best = (weights, score);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(TuneReport {
+        weights: best.0,
+        mean_score: best.1,
+        start_mean_score,
+        candidates,
+    })
+}
+
+// Options in and the report out as JSON, for the command line.
+pub fn tune_json(options: &str) -> Result<String, String> {
+    let options: TuneOptions =
+        serde_json::from_str(options).map_err(|err| format!("Invalid options: {}", err))?;
+    serde_json::to_string(&tune(&options)?).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tune::{tune, tune_json, TuneOptions};
+
+    #[test]
+    fn test_tune() {
+        let grid =
+            tune_json(r#"{"simulation": {"games": 1, "maxMoves": 20}, "factors": [0.5, 1.0]}"#)
+                .unwrap();
+        assert!(grid.contains(r#""candidates":33"#), "Tune: Grid");
+
+        let options: TuneOptions = serde_json::from_str(
+            r#"{"simulation": {"games": 1, "maxMoves": 20}, "search": "evolve", "generations": 2, "population": 3}"#,
+        )
+        .unwrap();
+        let report = tune(&options).unwrap();
+        assert_eq!(7, report.candidates, "Tune: Evolve");
+        assert!(
+            report.mean_score >= report.start_mean_score,
+            "Tune: Never Worse"
+        );
+        assert_eq!(report, tune(&options).unwrap(), "Tune: Repeatable");
+
+        assert!(
+            tune_json(r#"{"search": "anneal"}"#).is_err(),
+            "Tune: Unknown Search"
+        );
+    }
+}
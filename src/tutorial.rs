@@ -0,0 +1,173 @@
+use seed::{prelude::*, *};
+
+use crate::game_state::{Direction, GameState};
+use crate::skin;
+
+// One thing the player is asked to do, on the board left by the step before
+// unless the step sets up its own.
+struct Step {
+    board: Option<[usize; 16]>,
+    direction: Direction,
+    arrow: &'static str,
+    prompt: &'static str,
+}
+
+const STEPS: [Step; 5] = [
+    Step {
+        board: Some([0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+        direction: Direction::Left,
+        arrow: "←",
+        prompt: "Press ← or swipe left. Every tile slides as far as it can.",
+    },
+    Step {
+        board: None,
+        direction: Direction::Right,
+        arrow: "→",
+        prompt: "Now to the right, with → or a swipe.",
+    },
+    Step {
+        board: None,
+        direction: Direction::Up,
+        arrow: "↑",
+        prompt: "Up, with ↑ or a swipe.",
+    },
+    Step {
+        board: None,
+        direction: Direction::Down,
+        arrow: "↓",
+        prompt: "And down, with ↓ or a swipe.",
+    },
+    Step {
+        board: Some([0, 0, 0, 0, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0]),
+        direction: Direction::Left,
+        arrow: "←",
+        prompt: "Tiles with the same number merge when they meet. Slide left to join the 2s.",
+    },
+];
+
+// The walk through the moves shown to first-time players. Its board is
+// scripted, no tiles spawn on it.
+pub struct Tutorial {
+    step: usize,
+    game_state: GameState,
+}
+
+pub enum Msg {
+    Moved(Direction),
+    Finished,
+}
+
+impl Tutorial {
+    pub fn new() -> Tutorial {
+        Tutorial::at(0, GameState::from_values(&[0; 16]))
+    }
+
+    fn at(step: usize, game_state: GameState) -> Tutorial {
+        let game_state = match STEPS.get(step).and_then(|step| step.board) {
+            Some(board) => GameState::from_values(&board),
+            None => game_state,
+        };
+        Tutorial { step, game_state }
+    }
+
+    fn is_done(&self) -> bool {
+        self.step == STEPS.len()
+    }
+
+    // Whether the player is done with the tutorial.
+    pub fn update(&mut self, msg: Msg) -> bool {
+        match msg {
+            Msg::Moved(direction) => {
+                let expected = STEPS.get(self.step).map(|step| step.direction);
+                if Some(direction) != expected {
+                    return false;
+                }
+                if let Some(next) = self.game_state.preview_move(direction) {
+                    *self = Tutorial::at(self.step + 1, next);
+                }
+                false
+            }
+            Msg::Finished => true,
+        }
+    }
+
+    pub fn view(&self) -> Node<Msg> {
+        let step = STEPS.get(self.step);
+
+        div![
+            C!["qr-modal"],
+            attrs! {At::from("role") => "dialog"},
+            div![
+                C!["qr-dialog", "tutorial-dialog"],
+                h2!["How to play"],
+                div![
+                    C!["tutorial-board"],
+                    self.game_state.cells().iter().map(|cell| {
+                        let colors = cell.map(skin::tile_colors);
+                        div![
+                            C!["tutorial-cell"],
+                            colors.map(|(background, text)| style! {
+                                St::Background => background,
+                                St::Color => text,
+                            }),
+                            cell.map(|tile| skin::tile_label(tile, false))
+                        ]
+                    })
+                ],
+                p![
+                    C!["tutorial-prompt"],
+                    attrs! {At::from("aria-live") => "polite"},
+                    step.map_or(
+                        "That's all there is to it. Keep merging to reach 2048!",
+                        |step| step.prompt
+                    )
+                ],
+                step.map(|step| {
+                    let direction = step.direction;
+                    a![
+                        C!["qr-close"],
+                        attrs! {At::AriaLabel => format!("Move {:?}", direction)},
+                        step.arrow,
+                        ev(Ev::Click, move |_| Msg::Moved(direction))
+                    ]
+                }),
+                a![
+                    C!["qr-close"],
+                    if self.is_done() {
+                        "Start Playing"
+                    } else {
+                        "Skip"
+                    },
+                    ev(Ev::Click, |_| Msg::Finished)
+                ]
+            ]
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::Direction;
+    use crate::tutorial::{Msg, Tutorial, STEPS};
+
+    #[test]
+    fn test_tutorial() {
+        let mut tutorial = Tutorial::new();
+        assert!(!tutorial.update(Msg::Moved(Direction::Up)), "Wrong Move");
+        assert_eq!(0, tutorial.step, "Wrong Move: Stays");
+
+        for step in &STEPS {
+            assert!(!tutorial.update(Msg::Moved(step.direction)), "Step");
+        }
+        assert!(tutorial.is_done(), "Done");
+        let values: Vec<usize> = tutorial
+            .game_state
+            .cells()
+            .iter()
+            .flatten()
+            .map(|tile| tile.get_value())
+            .collect();
+        assert_eq!(vec![4], values, "Merged");
+        assert!(tutorial.update(Msg::Finished), "Finished");
+    }
+}
@@ -0,0 +1,58 @@
+// Keeps the game playable offline. Registered from Rust (src/pwa.rs) as
+// `/sw.js?build=<build id>`; every build gets a cache of its own and the
+// caches of older builds are dropped once it takes over.
+const BUILD = new URL(self.location).searchParams.get('build') || 'dev';
+const CACHE = `seed2048-${BUILD}`;
+
+const APP_SHELL = [
+    '/',
+    '/index.html',
+    '/manifest.webmanifest',
+    '/pkg/package.js',
+    '/pkg/package_bg.wasm',
+    '/public/icon.svg',
+    '/style/main.css',
+    '/style/fonts/clear-sans.css',
+    '/style/fonts/ClearSans-Bold-webfont.woff',
+    '/style/fonts/ClearSans-Light-webfont.woff',
+    '/style/fonts/ClearSans-Regular-webfont.woff',
+];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil(
+        caches.open(CACHE)
+            .then((cache) => cache.addAll(APP_SHELL))
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(
+                keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))
+            ))
+            .then(() => self.clients.claim())
+    );
+});
+
+// Network first, so a freshly deployed wasm build is picked up even before
+// its service worker is, and the cache only answers while offline.
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+
+    event.respondWith(
+        fetch(request)
+            .then((response) => {
+                if (response.ok) {
+                    const copy = response.clone();
+                    caches.open(CACHE).then((cache) => cache.put(request, copy));
+                }
+                return response;
+            })
+            .catch(() => caches.match(request, { ignoreSearch: true }))
+    );
+});